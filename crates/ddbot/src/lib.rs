@@ -1,10 +1,14 @@
-use bincode::deserialize_from;
-use camino::Utf8PathBuf;
+use bincode::{deserialize_from, serialize_into};
+use camino::{Utf8Path, Utf8PathBuf};
+use flate2::read::MultiGzDecoder;
+use log::debug;
 use std::collections::HashMap;
 use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read};
+use walkdir::WalkDir;
 
 mod error;
-mod model;
+pub mod model;
 use crate::model::target::Target;
 pub use error::{Error, Result};
 
@@ -14,3 +18,56 @@ pub fn bincode2targets(input: &Utf8PathBuf) -> Result<HashMap<String, Target>> {
     println!("{} targets returned", targets.len());
     Ok(targets)
 }
+
+/// Whether `path` is a target part file, gzipped (`.json.gz`) or plain (`.json`).
+fn is_part(path: &Utf8Path) -> bool {
+    let name = path.file_name().unwrap_or_default();
+    name.ends_with(".json.gz") || name.ends_with(".json")
+}
+
+/// Targets of the JSONL part files below `input_dir`, as shipped by Open
+/// Targets. The parts are read line by line in path order, gzipped parts may
+/// consist of several concatenated members; a later record of the same
+/// Ensembl id replaces the earlier one.
+pub fn dir2targets(input_dir: &Utf8PathBuf) -> Result<HashMap<String, Target>> {
+    let mut parts = vec![];
+    for entry in WalkDir::new(input_dir).sort_by_file_name() {
+        let entry = entry.map_err(|e| Error::Specified(format!("Cannot walk {input_dir}: {e}")))?;
+        let path = Utf8PathBuf::try_from(entry.into_path())
+            .map_err(|e| Error::Specified(format!("Non UTF-8 path: {e}")))?;
+        if path.is_file() && is_part(&path) {
+            parts.push(path);
+        }
+    }
+    if parts.is_empty() {
+        return Err(Error::Specified(format!(
+            "No .json.gz or .json part files in {input_dir}"
+        )));
+    }
+    let mut targets = HashMap::new();
+    for path in parts {
+        debug!("Parsing targets from {path}");
+        let fileh = File::open(&path)?;
+        let reader: Box<dyn Read> = match path.as_str().ends_with(".gz") {
+            true => Box::new(MultiGzDecoder::new(fileh)),
+            false => Box::new(fileh),
+        };
+        for (n, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let target: Target = serde_json::from_str(&line)
+                .map_err(|e| Error::Specified(format!("{path}:{}: {e}", n + 1)))?;
+            targets.insert(target.id.clone(), target);
+        }
+    }
+    Ok(targets)
+}
+
+/// Writes `targets` as the bincode cache read by [`bincode2targets`].
+pub fn targets2bincode(targets: &HashMap<String, Target>, output: &Utf8PathBuf) -> Result<()> {
+    let f = BufWriter::new(File::create(output)?);
+    serialize_into(f, targets)?;
+    Ok(())
+}
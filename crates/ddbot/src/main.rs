@@ -0,0 +1,33 @@
+use camino::Utf8PathBuf;
+use clap::{Parser, Subcommand};
+
+#[derive(Parser, Debug)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Builds the target bincode cache from an Open Targets target release
+    BuildTargets {
+        /// Directory of the `.json.gz` (or `.json`) part files
+        #[arg(short, long)]
+        input: Utf8PathBuf,
+        /// Bincode file written
+        #[arg(short, long, default_value = "target.bincode")]
+        output: Utf8PathBuf,
+    },
+}
+
+fn main() -> ddbot::Result<()> {
+    let cli = Cli::parse();
+    match cli.command {
+        Command::BuildTargets { input, output } => {
+            let targets = ddbot::dir2targets(&input)?;
+            ddbot::targets2bincode(&targets, &output)?;
+            println!("{} targets written to {output}", targets.len());
+        }
+    }
+    Ok(())
+}
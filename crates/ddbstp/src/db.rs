@@ -0,0 +1,117 @@
+//! Database the report pages read: the Postgres analysis database, or a
+//! SQLite file exported from it, which builds the report without a Postgres
+//! server. Statements are written in the SQL both understand, the remaining
+//! differences go through [`Dialect`].
+
+use crate::error::{Error, Result};
+use camino::Utf8Path;
+use ddbtbl::dialect::Dialect;
+use ddbtbl::gls::scoresetting::ScoreSettingBmc;
+use iwf::sql::ModelManager;
+use sea_query_binder::SqlxValues;
+use sqlx::postgres::{PgPool, PgRow};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqliteRow};
+use sqlx::FromRow;
+use std::str::FromStr;
+
+pub enum ReportDb {
+    Postgres(PgPool),
+    Sqlite(SqlitePool),
+}
+
+/// Row of a report statement, decoded from either database.
+pub trait ReportRow:
+    for<'r> FromRow<'r, PgRow> + for<'r> FromRow<'r, SqliteRow> + Send + Unpin
+{
+}
+
+impl<T> ReportRow for T where
+    T: for<'r> FromRow<'r, PgRow> + for<'r> FromRow<'r, SqliteRow> + Send + Unpin
+{
+}
+
+impl ReportDb {
+    #[must_use]
+    pub fn postgres(mm: &ModelManager) -> Self {
+        Self::Postgres(mm.db().clone())
+    }
+
+    /// Opens the SQLite file `path` written by the `sqlite` export read-only.
+    pub async fn sqlite(path: &Utf8Path) -> Result<Self> {
+        if !path.exists() {
+            return Err(Error::Specified(format!("No SQLite database at {path}")));
+        }
+        let options = SqliteConnectOptions::from_str(path.as_str())?.read_only(true);
+        Ok(Self::Sqlite(SqlitePool::connect_with(options).await?))
+    }
+
+    #[must_use]
+    pub fn dialect(&self) -> Dialect {
+        match self {
+            Self::Postgres(_) => Dialect::Postgres,
+            Self::Sqlite(_) => Dialect::Sqlite,
+        }
+    }
+
+    pub async fn fetch_all<T: ReportRow>(&self, stmt: &str) -> Result<Vec<T>> {
+        Ok(match self {
+            Self::Postgres(pool) => sqlx::query_as::<_, T>(stmt).fetch_all(pool).await?,
+            Self::Sqlite(pool) => sqlx::query_as::<_, T>(stmt).fetch_all(pool).await?,
+        })
+    }
+
+    /// Rows of `stmt` with `id` bound as `$1`.
+    pub async fn fetch_all_by_id<T: ReportRow>(&self, stmt: &str, id: i32) -> Result<Vec<T>> {
+        Ok(match self {
+            Self::Postgres(pool) => {
+                sqlx::query_as::<_, T>(stmt)
+                    .bind(id)
+                    .fetch_all(pool)
+                    .await?
+            }
+            Self::Sqlite(pool) => {
+                sqlx::query_as::<_, T>(stmt)
+                    .bind(id)
+                    .fetch_all(pool)
+                    .await?
+            }
+        })
+    }
+
+    /// Rows of a statement built by `ddbtbl::query` for [`Self::dialect`].
+    pub async fn fetch_all_with<T: ReportRow>(
+        &self,
+        (stmt, values): (String, SqlxValues),
+    ) -> Result<Vec<T>> {
+        Ok(match self {
+            Self::Postgres(pool) => {
+                sqlx::query_as_with::<_, T, _>(&stmt, values)
+                    .fetch_all(pool)
+                    .await?
+            }
+            Self::Sqlite(pool) => {
+                sqlx::query_as_with::<_, T, _>(&stmt, values)
+                    .fetch_all(pool)
+                    .await?
+            }
+        })
+    }
+
+    /// Id of the score setting the report shows, see
+    /// [`ScoreSettingBmc::REFERENCE_ID_SQL`].
+    pub async fn reference_id(&self) -> Result<i32> {
+        let (id,): (Option<i32>,) = match self {
+            Self::Postgres(pool) => {
+                sqlx::query_as(ScoreSettingBmc::REFERENCE_ID_SQL)
+                    .fetch_one(pool)
+                    .await?
+            }
+            Self::Sqlite(pool) => {
+                sqlx::query_as(ScoreSettingBmc::REFERENCE_ID_SQL)
+                    .fetch_one(pool)
+                    .await?
+            }
+        };
+        id.ok_or_else(|| Error::Specified("No score setting stored".to_string()))
+    }
+}
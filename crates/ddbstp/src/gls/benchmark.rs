@@ -0,0 +1,53 @@
+use crate::db::ReportDb;
+use crate::error::Result;
+use iwf::md::model::mpage::StdPage;
+use iwf::plot::dotplot::{DotGlyph, DotLO};
+use serde::{Deserialize, Serialize};
+use tabled::Tabled;
+
+#[derive(Debug, sqlx::FromRow, Default, Serialize, Deserialize, Tabled)]
+pub struct Benchmark {
+    label_column: String,
+    tissue: String,
+    n_tp: i32,
+    n_fp: i32,
+    n_fn: i32,
+    precision: f64,
+    recall: f64,
+    f1: f64,
+}
+
+/// Per tissue precision, recall and F1 of the reference score setting.
+pub async fn get(db: &ReportDb) -> Result<StdPage<Benchmark>> {
+    let name = String::from("benchmark");
+    let stmt = "select label_column,tissue,n_tp,n_fp,n_fn,precision,recall,f1 from benchmark where score_setting_id = $1 order by label_column,tissue";
+    let items: Vec<Benchmark> = db.fetch_all_by_id(stmt, db.reference_id().await?).await?;
+    let mut glyphs = vec![];
+    for item in &items {
+        glyphs.push(DotGlyph {
+            trace: item.label_column.clone(),
+            x: item.f1,
+            y: item.tissue.clone(),
+            size: 10,
+            ..Default::default()
+        });
+    }
+    let fig = DotLO {
+        x_lab: "F1".into(),
+        y_lab: "Tissue".into(),
+        width: 900,
+        height: 1200,
+        ..Default::default()
+    };
+    let plot = fig.get_plotly(glyphs, None, &name)?;
+    let stdpage = StdPage::<Benchmark> {
+        plot_path: Some(format!("{name}.png")),
+        figure_text: None,
+        plot: Some(plot),
+        table: Some(items),
+        name,
+        ..Default::default()
+    };
+
+    Ok(stdpage)
+}
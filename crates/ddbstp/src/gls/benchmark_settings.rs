@@ -0,0 +1,51 @@
+use crate::db::ReportDb;
+use crate::error::Result;
+use iwf::md::model::mpage::StdPage;
+use iwf::plot::stacked_bar::{BarGlyph, StackedBarLO};
+use serde::{Deserialize, Serialize};
+use tabled::Tabled;
+
+#[derive(Debug, sqlx::FromRow, Default, Serialize, Deserialize, Tabled)]
+pub struct BenchmarkSettings {
+    score_setting_id: i32,
+    multi_label_weight: f64,
+    delta_to_include: f64,
+    max_labels: i32,
+    common_threashold: i32,
+    cell_atlas_weight: f64,
+    macro_f1: f64,
+}
+
+/// Macro F1 of the global label per score setting.
+pub async fn get(db: &ReportDb) -> Result<StdPage<BenchmarkSettings>> {
+    let name = String::from("benchmark_settings");
+    let stmt = "select score_setting_id,multi_label_weight,delta_to_include,max_labels,common_threashold,cell_atlas_weight,avg(f1) as macro_f1 from benchmark inner join scoresetting on score_setting_id = scoresetting.id where label_column = 'global' and n_tp + n_fn > 0 group by score_setting_id,multi_label_weight,delta_to_include,max_labels,common_threashold,cell_atlas_weight order by score_setting_id";
+    let items: Vec<BenchmarkSettings> = db.fetch_all(stmt).await?;
+    let mut glyphs = vec![];
+    for item in &items {
+        glyphs.push(BarGlyph {
+            trace: "global".into(),
+            x: format!("{}", item.score_setting_id),
+            y: item.macro_f1,
+            ..Default::default()
+        });
+    }
+    let fig = StackedBarLO {
+        x_lab: "Score setting".into(),
+        y_lab: "Macro F1".into(),
+        width: 900,
+        height: 900,
+        ..Default::default()
+    };
+    let plot = fig.get_plotly(glyphs, None, &name)?;
+    let stdpage = StdPage::<BenchmarkSettings> {
+        plot_path: Some(format!("{name}.png")),
+        figure_text: None,
+        plot: Some(plot),
+        table: Some(items),
+        name,
+        ..Default::default()
+    };
+
+    Ok(stdpage)
+}
@@ -0,0 +1,48 @@
+use crate::db::ReportDb;
+use crate::error::Result;
+use iwf::md::model::mpage::StdPage;
+use iwf::plot::stacked_bar::{BarGlyph, StackedBarLO};
+use serde::{Deserialize, Serialize};
+use tabled::Tabled;
+
+#[derive(Debug, sqlx::FromRow, Default, Serialize, Deserialize, Tabled)]
+pub struct Confusion {
+    expected: String,
+    predicted: String,
+    n: i32,
+}
+
+/// Confusion matrix of the global label of the reference score setting,
+/// one stacked bar per expected tissue.
+pub async fn get(db: &ReportDb) -> Result<StdPage<Confusion>> {
+    let name = String::from("confusion");
+    let stmt = "select expected,predicted,n from confusion where score_setting_id = $1 and label_column = 'global' order by expected,predicted";
+    let items: Vec<Confusion> = db.fetch_all_by_id(stmt, db.reference_id().await?).await?;
+    let mut glyphs = vec![];
+    for item in &items {
+        glyphs.push(BarGlyph {
+            trace: item.predicted.clone(),
+            x: item.expected.clone(),
+            y: f64::from(item.n),
+            ..Default::default()
+        });
+    }
+    let fig = StackedBarLO {
+        x_lab: "Expected".into(),
+        y_lab: "Count".into(),
+        width: 2400,
+        height: 900,
+        ..Default::default()
+    };
+    let plot = fig.get_plotly(glyphs, None, &name)?;
+    let stdpage = StdPage::<Confusion> {
+        plot_path: Some(format!("{name}.png")),
+        figure_text: None,
+        plot: Some(plot),
+        table: Some(items),
+        name,
+        ..Default::default()
+    };
+
+    Ok(stdpage)
+}
@@ -1,7 +1,7 @@
+use crate::db::ReportDb;
 use crate::error::Result;
 use iwf::md::model::mpage::StdPage;
 use iwf::plot::scatter::{ScatterGlyph, ScatterLO};
-use iwf::sql::ModelManager;
 use log::info;
 use serde::{Deserialize, Serialize};
 use tabled::Tabled;
@@ -13,10 +13,10 @@ pub struct Fig1D {
     y2: f64,
 }
 
-pub async fn get(mm: &ModelManager) -> Result<StdPage<Fig1D>> {
-    let stmt = "select labels as name,y1,y2 from umap inner join ac_labels on idx::int4 = ac_labels.ac_id and umap.kind = ac_labels.kind where umap.kind = 'haatlas';";
+pub async fn get(db: &ReportDb) -> Result<StdPage<Fig1D>> {
+    let stmt = "select labels as name,y1,y2 from umap inner join ac_labels on cast(idx as integer) = ac_labels.ac_id and umap.kind = ac_labels.kind where umap.kind = 'haatlas';";
     let name = String::from("fig1d");
-    let items: Vec<Fig1D> = sqlx::query_as::<_, Fig1D>(stmt).fetch_all(&mm.db).await?;
+    let items: Vec<Fig1D> = db.fetch_all(stmt).await?;
 
     info!("N items: {}", items.len());
     let mut glyphs = vec![];
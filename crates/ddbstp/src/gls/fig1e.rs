@@ -1,7 +1,7 @@
+use crate::db::ReportDb;
 use crate::error::Result;
 use iwf::md::model::mpage::StdPage;
 use iwf::plot::dotplot::{DotGlyph, DotLO};
-use iwf::sql::ModelManager;
 use log::info;
 use serde::{Deserialize, Serialize};
 use tabled::Tabled;
@@ -10,21 +10,26 @@ use tabled::Tabled;
 pub struct Fig1E {
     kind: String,
     cluster_id: String,
+    #[sqlx(default)]
     nlog_pvalue: f64,
+    #[sqlx(default)]
     log_cs: f64,
     pvalue: f64,
+    combined_score: f64,
     term: String,
     genes: String,
 }
 
-pub async fn get(mm: &ModelManager) -> Result<StdPage<Fig1E>> {
+pub async fn get(db: &ReportDb) -> Result<StdPage<Fig1E>> {
     let name = String::from("fig1e");
 
-    let stmt = "select kind,cluster_id,-log(pvalue) as nlog_pvalue,log(combined_score)*10 as log_cs,pvalue,term,genes from cluster_gsea where kind = 'wkde_haatlas';";
-    let items: Vec<Fig1E> = sqlx::query_as::<_, Fig1E>(stmt).fetch_all(&mm.db).await?;
+    let stmt = "select kind,cluster_id,pvalue,combined_score,term,genes from cluster_gsea where kind = 'wkde_haatlas';";
+    let mut items: Vec<Fig1E> = db.fetch_all(stmt).await?;
     info!("N items: {}", items.len());
     let mut glyphs = vec![];
-    for item in &items {
+    for item in &mut items {
+        item.nlog_pvalue = -item.pvalue.log10();
+        item.log_cs = item.combined_score.log10() * 10.0;
         glyphs.push(DotGlyph {
             trace: item.cluster_id.clone(),
             x: item.nlog_pvalue,
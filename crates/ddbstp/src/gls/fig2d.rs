@@ -1,7 +1,7 @@
+use crate::db::ReportDb;
 use crate::error::Result;
 use iwf::md::model::mpage::StdPage;
 use iwf::plot::stacked_bar::{BarGlyph, StackedBarLO};
-use iwf::sql::ModelManager;
 use serde::{Deserialize, Serialize};
 use tabled::Tabled;
 
@@ -13,10 +13,10 @@ pub struct Fig2d {
     n: f64,
 }
 
-pub async fn get(mm: &ModelManager) -> Result<StdPage<Fig2d>> {
+pub async fn get(db: &ReportDb) -> Result<StdPage<Fig2d>> {
     let name = String::from("fig2d");
-    let stmt = "select gls::int,global_grp,sum(scaled_value) as v,count(*)::float as n  from (select gls,global_label_string,global_grp,scaled_value from gls inner join qm on gls.ac::int = qm.ac_id inner join qmatrix on qmatrix_id = qmatrix.id inner join ann on ann_id = ann.id where qmatrix.name = 'haatlas' and global_label_string = 'brain') a group by gls::int,global_grp order by global_grp";
-    let items: Vec<Fig2d> = sqlx::query_as::<_, Fig2d>(stmt).fetch_all(&mm.db).await?;
+    let stmt = "select cast(gls as integer) as gls,global_grp,sum(share_value) as v,cast(count(*) as double precision) as n  from (select gls,global_label_string,global_grp,share_value from gls inner join qm on cast(gls.ac as integer) = qm.ac_id inner join qmatrix on qmatrix_id = qmatrix.id inner join ann on ann_id = ann.id where qmatrix.name = 'haatlas' and global_label_string = 'brain') a group by cast(gls as integer),global_grp order by global_grp";
+    let items: Vec<Fig2d> = db.fetch_all(stmt).await?;
     let mut glyphs = vec![];
     for item in &items {
         glyphs.push(BarGlyph {
@@ -1,7 +1,8 @@
+use crate::db::ReportDb;
 use crate::error::Result;
+use ddbtbl::query;
 use iwf::md::model::mpage::StdPage;
 use iwf::plot::dotplot::{DotGlyph, DotLO};
-use iwf::sql::ModelManager;
 use serde::{Deserialize, Serialize};
 use tabled::Tabled;
 
@@ -11,16 +12,23 @@ pub struct Fig2e {
     overlap: String,
     genes: String,
     cluster_id: String,
+    pvalue: f64,
+    combined_score: f64,
+    #[sqlx(default)]
     nlog_pvalue: f64,
+    #[sqlx(default)]
     log_cs: f64,
 }
 
-pub async fn get(mm: &ModelManager, filter: &str) -> Result<StdPage<Fig2e>> {
+pub async fn get(db: &ReportDb, filter: &str) -> Result<StdPage<Fig2e>> {
     let name = String::from("fig2e");
-    let stmt = format!("select term,overlap,genes,kind as cluster_id,-log(pvalue) as nlog_pvalue,log(combined_score)*10 as log_cs from cluster_gsea where kind like '{filter}%'");
-    let items: Vec<Fig2e> = sqlx::query_as::<_, Fig2e>(&stmt).fetch_all(&mm.db).await?;
+    let mut items: Vec<Fig2e> = db
+        .fetch_all_with(query::cluster_gsea_terms(filter, db.dialect()))
+        .await?;
     let mut glyphs = vec![];
-    for item in &items {
+    for item in &mut items {
+        item.nlog_pvalue = -item.pvalue.log10();
+        item.log_cs = item.combined_score.log10() * 10.0;
         glyphs.push(DotGlyph {
             trace: item.cluster_id.clone(),
             x: item.nlog_pvalue,
@@ -1,7 +1,7 @@
+use crate::db::ReportDb;
 use crate::error::Result;
 use iwf::md::model::mpage::StdPage;
 use iwf::plot::stacked_bar::{BarGlyph, StackedBarLO};
-use iwf::sql::ModelManager;
 use serde::{Deserialize, Serialize};
 use tabled::Tabled;
 
@@ -11,11 +11,11 @@ pub struct Fig2f {
     n: i64,
 }
 
-pub async fn get(mm: &ModelManager) -> Result<StdPage<Fig2f>> {
+pub async fn get(db: &ReportDb) -> Result<StdPage<Fig2f>> {
     let name = String::from("fig2f");
     let stmt =
-    "select global_label_string,count(*) as n from labeldata where global_label_string = tissue_label_string group by global_label_string having count(*) > 10 order by count(*) desc;";
-    let items: Vec<Fig2f> = sqlx::query_as::<_, Fig2f>(stmt).fetch_all(&mm.db).await?;
+    "select global_label_string,count(*) as n from labeldata where score_setting_id = $1 and global_label_string = tissue_label_string group by global_label_string having count(*) > 10 order by count(*) desc;";
+    let items: Vec<Fig2f> = db.fetch_all_by_id(stmt, db.reference_id().await?).await?;
     let mut glyphs = vec![];
     for item in &items {
         glyphs.push(BarGlyph {
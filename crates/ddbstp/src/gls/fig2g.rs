@@ -1,7 +1,7 @@
+use crate::db::ReportDb;
 use crate::error::Result;
 use iwf::md::model::mpage::StdPage;
 use iwf::plot::boxplot::{BoxGlyph, BoxLO};
-use iwf::sql::ModelManager;
 use serde::{Deserialize, Serialize};
 use tabled::Tabled;
 
@@ -14,10 +14,10 @@ pub struct Fig2g {
     value: f64,
 }
 
-pub async fn get(mm: &ModelManager) -> Result<StdPage<Fig2g>> {
+pub async fn get(db: &ReportDb) -> Result<StdPage<Fig2g>> {
     let name = String::from("fig2g");
-    let stmt = "select entry,global_label_string,global_grp,count(distinct ann.id) as n2,avg(scaled_value) as value from labeldata inner join qm on ac::int = qm.ac_id inner join ac on qm.ac_id = ac.id inner join qmatrix on qmatrix_id = qmatrix.id inner join ann on ann_id = ann.id where n_tissue = 1 and n_measure > 1 and global_label_string = tissue_label_string and qmatrix.name in ('haatlas', 'emblatlas', 'msratlas', 'mspatlas') and global_label_string = global_grp group by ac.entry,global_label_string,global_grp;";
-    let items: Vec<Fig2g> = sqlx::query_as::<_, Fig2g>(stmt).fetch_all(&mm.db).await?;
+    let stmt = "select entry,global_label_string,global_grp,count(distinct ann.id) as n2,avg(share_value) as value from labeldata inner join qm on cast(ac as integer) = qm.ac_id inner join ac on qm.ac_id = ac.id inner join qmatrix on qmatrix_id = qmatrix.id inner join ann on ann_id = ann.id where score_setting_id = $1 and n_tissue = 1 and n_measure > 1 and global_label_string = tissue_label_string and qmatrix.name in ('haatlas', 'emblatlas', 'msratlas', 'mspatlas') and global_label_string = global_grp group by ac.entry,global_label_string,global_grp;";
+    let items: Vec<Fig2g> = db.fetch_all_by_id(stmt, db.reference_id().await?).await?;
     let mut glyphs = vec![];
     for item in &items {
         glyphs.push(BoxGlyph {
@@ -1,7 +1,8 @@
+use crate::db::ReportDb;
 use crate::error::Result;
+use ddbtbl::query;
 use iwf::md::model::mpage::StdPage;
 use iwf::plot::boxplot::{BoxGlyph, BoxLO};
-use iwf::sql::ModelManager;
 #[allow(unused_imports)]
 use log::{debug, info};
 use serde::{Deserialize, Serialize};
@@ -12,20 +13,24 @@ pub struct Fig3a {
     name: String,
     entry: String,
     global_grp: String,
-    scaled_value: f64,
+    share_value: f64,
 }
 
-pub async fn get(mm: &ModelManager, protein_list: &Vec<String>) -> Result<StdPage<Fig3a>> {
+pub async fn get(db: &ReportDb, protein_list: &Vec<String>) -> Result<StdPage<Fig3a>> {
     let name = String::from("fig3a");
-    let selstr: Vec<String> = protein_list.iter().map(|e| format!("'{e}'")).collect();
-    let stmt = format!("select qmatrix.name,entry,global_grp,scaled_value from qm inner join ac on ac_id = ac.id inner join ann on ann_id = ann.id inner join qmatrix on qmatrix_id= qmatrix.id where entry IN ({}) and global_grp = 'liver' and name in ('emblatlas', 'mspatlas','msratlas','haatlas')", selstr.join(","));
+    let (stmt, values) = query::group_values(
+        protein_list,
+        "liver",
+        &["emblatlas", "mspatlas", "msratlas", "haatlas"],
+        db.dialect(),
+    );
     debug!("Statment {}", stmt);
-    let items: Vec<Fig3a> = sqlx::query_as::<_, Fig3a>(&stmt).fetch_all(&mm.db).await?;
+    let items: Vec<Fig3a> = db.fetch_all_with((stmt, values)).await?;
     let mut glyphs = vec![];
     for item in &items {
         glyphs.push(BoxGlyph {
             trace: item.name.clone(),
-            value: item.scaled_value,
+            value: item.share_value,
             ..Default::default()
         });
     }
@@ -1,23 +1,27 @@
+use crate::db::ReportDb;
 use crate::error::Result;
 use iwf::md::model::mpage::StdPage;
 use iwf::plot::stacked_bar::{BarGlyph, StackedBarLO};
-use iwf::sql::ModelManager;
 use serde::{Deserialize, Serialize};
 use tabled::Tabled;
 
 #[derive(Debug, sqlx::FromRow, Default, Serialize, Deserialize, Tabled)]
 pub struct Fig3b {
     term: String,
+    pvalue: f64,
+    #[sqlx(default)]
     nlog_pvalue: f64,
 }
 
-pub async fn get(mm: &ModelManager) -> Result<StdPage<Fig3b>> {
+pub async fn get(db: &ReportDb) -> Result<StdPage<Fig3b>> {
     let name = String::from("fig3b");
-    let stmt = "select term,-log(pvalue) as nlog_pvalue from cluster_gsea where kind = 'fig3b' order by nlog_pvalue desc limit 40";
+    let stmt =
+        "select term,pvalue from cluster_gsea where kind = 'fig3b' order by pvalue asc limit 40";
 
-    let items: Vec<Fig3b> = sqlx::query_as::<_, Fig3b>(stmt).fetch_all(&mm.db).await?;
+    let mut items: Vec<Fig3b> = db.fetch_all(stmt).await?;
     let mut glyphs = vec![];
-    for item in &items {
+    for item in &mut items {
+        item.nlog_pvalue = -item.pvalue.log10();
         glyphs.push(BarGlyph {
             trace: "trace".into(),
             x: item.term.clone(),
@@ -1,7 +1,7 @@
+use crate::db::ReportDb;
 use crate::error::Result;
 use iwf::md::model::mpage::StdPage;
 use iwf::plot::scatter::{ScatterGlyph, ScatterLO};
-use iwf::sql::ModelManager;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use tabled::Tabled;
@@ -15,10 +15,10 @@ pub struct Fig3c {
     patient_variance: f64,
 }
 
-pub async fn get(mm: &ModelManager, grp_mpr: &BTreeMap<String, String>) -> Result<StdPage<Fig3c>> {
+pub async fn get(db: &ReportDb, grp_mpr: &BTreeMap<String, String>) -> Result<StdPage<Fig3c>> {
     let name = String::from("fig3c");
     let stat = "select ac.entry, 'grp' as grp, a.ac_id,a.variance as time_variance,b.variance as patient_variance from variance a inner join variance b on a.ac_id = b.ac_id inner join ac on a.ac_id = ac.id where a.kind = 'time' and b.kind = 'patient'";
-    let mut items: Vec<Fig3c> = sqlx::query_as::<_, Fig3c>(stat).fetch_all(&mm.db).await?;
+    let mut items: Vec<Fig3c> = db.fetch_all(stat).await?;
     let mut glyphs = vec![];
     for item in &mut items {
         item.grp = grp_mpr.get(&item.entry).unwrap_or(&String::new()).clone();
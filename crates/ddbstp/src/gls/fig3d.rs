@@ -1,7 +1,7 @@
+use crate::db::ReportDb;
 use crate::error::Result;
 use iwf::md::model::mpage::StdPage;
 use iwf::plot::boxplot::{BoxGlyph, BoxLO};
-use iwf::sql::ModelManager;
 use serde::{Deserialize, Serialize};
 use tabled::Tabled;
 
@@ -12,10 +12,10 @@ pub struct Fig3d {
     patient_variance: f64,
 }
 
-pub async fn get(mm: &ModelManager) -> Result<StdPage<Fig3d>> {
+pub async fn get(db: &ReportDb) -> Result<StdPage<Fig3d>> {
     let name = String::from("fig3d");
     let stmt = "select a.ac_id,a.variance as time_variance,b.variance as patient_variance from variance a inner join variance b on a.ac_id = b.ac_id where a.kind = 'time' and b.kind = 'patient'";
-    let items: Vec<Fig3d> = sqlx::query_as::<_, Fig3d>(stmt).fetch_all(&mm.db).await?;
+    let items: Vec<Fig3d> = db.fetch_all(stmt).await?;
     let mut glyphs = vec![];
     for item in &items {
         glyphs.push(BoxGlyph {
@@ -1,7 +1,7 @@
+use crate::db::ReportDb;
 use crate::error::Result;
 use iwf::md::model::mpage::StdPage;
 use iwf::plot::stacked_bar::{BarGlyph, StackedBarLO};
-use iwf::sql::ModelManager;
 use serde::{Deserialize, Serialize};
 use tabled::Tabled;
 
@@ -13,10 +13,10 @@ pub struct Fig4d {
     sample_grp: String,
 }
 
-pub async fn get(mm: &ModelManager) -> Result<StdPage<Fig4d>> {
+pub async fn get(db: &ReportDb) -> Result<StdPage<Fig4d>> {
     let name = String::from("fig4d");
-    let stmt = "select entry_name,scaled_value,global_grp,sample_grp from qm inner join qmatrix on qmatrix_id = qmatrix.id inner join ac on qm.ac_id = ac.id inner join ann on ann_id = ann.id where qmatrix.name = 'plpancr' and entry_name in ('CBPA1_HUMAN', 'CEL2A_HUMAN', 'CEL3A_HUMAN', 'CBPB1_HUMAN', 'CTRB2_HUMAN', 'GP2_HUMAN', 'REG1B_HUMAN', 'LIPP_HUMAN', 'AMYP_HUMAN') and measured = 'true';";
-    let items: Vec<Fig4d> = sqlx::query_as::<_, Fig4d>(stmt).fetch_all(&mm.db).await?;
+    let stmt = "select entry_name,scaled_value,global_grp,sample_grp from qm inner join qmatrix on qmatrix_id = qmatrix.id inner join ac on qm.ac_id = ac.id inner join ann on ann_id = ann.id where qmatrix.name = 'plpancr' and entry_name in ('CBPA1_HUMAN', 'CEL2A_HUMAN', 'CEL3A_HUMAN', 'CBPB1_HUMAN', 'CTRB2_HUMAN', 'GP2_HUMAN', 'REG1B_HUMAN', 'LIPP_HUMAN', 'AMYP_HUMAN') and measured = true;";
+    let items: Vec<Fig4d> = db.fetch_all(stmt).await?;
     let mut glyphs = vec![];
     for item in &items {
         glyphs.push(BarGlyph {
@@ -1,7 +1,7 @@
+use crate::db::ReportDb;
 use crate::error::Result;
 use iwf::md::model::mpage::StdPage;
 use iwf::plot::boxplot::{BoxGlyph, BoxLO};
-use iwf::sql::ModelManager;
 use serde::{Deserialize, Serialize};
 use tabled::Tabled;
 
@@ -12,10 +12,10 @@ pub struct Fig4e {
     norm_value: f64,
 }
 
-pub async fn get(mm: &ModelManager) -> Result<StdPage<Fig4e>> {
+pub async fn get(db: &ReportDb) -> Result<StdPage<Fig4e>> {
     let name = String::from("fig4e");
     let stmt = "select entry_name,norm_value,global_grp from qm inner join qmatrix on qmatrix_id = qmatrix.id inner join ac on ac_id = ac.id inner join ann on ann_id = ann.id where qmatrix.name = 'plpancr' and entry_name in ('CBPA1_HUMAN', 'CEL3A_HUMAN', 'LIPP_HUMAN', 'AMYP_HUMAN');";
-    let items: Vec<Fig4e> = sqlx::query_as::<_, Fig4e>(stmt).fetch_all(&mm.db).await?;
+    let items: Vec<Fig4e> = db.fetch_all(stmt).await?;
     let mut glyphs = vec![];
     for item in &items {
         glyphs.push(BoxGlyph {
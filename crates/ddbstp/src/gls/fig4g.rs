@@ -1,7 +1,8 @@
+use crate::db::ReportDb;
 use crate::error::Result;
+use ddbtbl::query;
 use iwf::md::model::mpage::StdPage;
 use iwf::plot::scatter::{ScatterGlyph, ScatterLO};
-use iwf::sql::ModelManager;
 use serde::{Deserialize, Serialize};
 use tabled::Tabled;
 
@@ -13,10 +14,10 @@ pub struct Fig4g {
     y2: f64,
 }
 
-pub async fn get(mm: &ModelManager, kind: &str) -> Result<StdPage<Fig4g>> {
+pub async fn get(db: &ReportDb, kind: &str) -> Result<StdPage<Fig4g>> {
     let name = String::from("fig4g");
-    let stmt = format!("select ann.id,global_grp,y1,y2 from umap inner join ann on CAST(REGEXP_REPLACE(umap.idx, '[^0-9]', '', 'g') AS INTEGER) = ann.id where kind = '{kind}';");
-    let items: Vec<Fig4g> = sqlx::query_as::<_, Fig4g>(&stmt).fetch_all(&mm.db).await?;
+    let (stmt, values) = query::umap_samples(kind, db.dialect());
+    let items: Vec<Fig4g> = db.fetch_all_with((stmt, values)).await?;
     let mut glyphs = vec![];
     for item in &items {
         glyphs.push(ScatterGlyph {
@@ -1,7 +1,7 @@
+use crate::db::ReportDb;
 use crate::error::Result;
 use iwf::md::model::mpage::StdPage;
 use iwf::plot::boxplot::{BoxGlyph, BoxLO};
-use iwf::sql::ModelManager;
 use serde::{Deserialize, Serialize};
 use tabled::Tabled;
 
@@ -12,10 +12,10 @@ pub struct Fig4i {
     norm_value: f64,
 }
 
-pub async fn get(mm: &ModelManager) -> Result<StdPage<Fig4i>> {
+pub async fn get(db: &ReportDb) -> Result<StdPage<Fig4i>> {
     let name = String::from("fig4i");
     let stmt = "select entry_name,norm_value,global_grp from qm inner join qmatrix on qmatrix_id = qmatrix.id inner join ac on ac_id = ac.id inner join ann on ann_id = ann.id where qmatrix.name = 'plmi' and entry_name in ('FABPH_HUMAN', 'NKX25_HUMAN','MYG_HUMAN');";
-    let items: Vec<Fig4i> = sqlx::query_as::<_, Fig4i>(stmt).fetch_all(&mm.db).await?;
+    let items: Vec<Fig4i> = db.fetch_all(stmt).await?;
     let mut glyphs = vec![];
     for item in &items {
         glyphs.push(BoxGlyph {
@@ -1,7 +1,7 @@
+use crate::db::ReportDb;
 use crate::error::Result;
 use iwf::md::model::mpage::StdPage;
 use iwf::plot::scatter::{ScatterGlyph, ScatterLO};
-use iwf::sql::ModelManager;
 use serde::{Deserialize, Serialize};
 use tabled::Tabled;
 
@@ -13,10 +13,13 @@ pub struct Fig4n {
     y2: f64,
 }
 
-pub async fn get(mm: &ModelManager) -> Result<StdPage<Fig4n>> {
+pub async fn get(db: &ReportDb) -> Result<StdPage<Fig4n>> {
     let name = String::from("fig4n");
-    let stmt = "select ann.id,global_grp,y1,y2 from umap inner join ann on CAST(REGEXP_REPLACE(umap.idx, '[^0-9]', '', 'g') AS INTEGER) = ann.id where kind = 'fig4n';";
-    let items: Vec<Fig4n> = sqlx::query_as::<_, Fig4n>(stmt).fetch_all(&mm.db).await?;
+    let stmt = format!(
+        "select ann.id,global_grp,y1,y2 from umap inner join ann on {} = ann.id where kind = 'fig4n';",
+        db.dialect().sample_id("umap.idx")
+    );
+    let items: Vec<Fig4n> = db.fetch_all(&stmt).await?;
     let mut glyphs = vec![];
     for item in &items {
         glyphs.push(ScatterGlyph {
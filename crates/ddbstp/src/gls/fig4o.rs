@@ -1,7 +1,7 @@
+use crate::db::ReportDb;
 use crate::error::Result;
 use iwf::md::model::mpage::StdPage;
 use iwf::plot::boxplot::{BoxGlyph, BoxLO};
-use iwf::sql::ModelManager;
 use serde::{Deserialize, Serialize};
 use tabled::Tabled;
 
@@ -12,11 +12,9 @@ pub struct Fig4o {
     norm_value: f64,
 }
 
-pub async fn get(mm: &ModelManager) -> Result<StdPage<Fig4o>> {
+pub async fn get(db: &ReportDb) -> Result<StdPage<Fig4o>> {
     let name = String::from("fig4o");
-    let items: Vec<Fig4o> = sqlx::query_as::<_, Fig4o>("select entry_name,norm_value,global_grp from qm inner join qmatrix on qmatrix_id = qmatrix.id inner join ac on ac_id = ac.id inner join ann on ann_id = ann.id where qmatrix.name = 'plsepsis' and entry_name in ('CRHBP_HUMAN', 'CRP_HUMAN', 'HLAC_HUMAN');")
-        .fetch_all(&mm.db)
-        .await?;
+    let items: Vec<Fig4o> = db.fetch_all("select entry_name,norm_value,global_grp from qm inner join qmatrix on qmatrix_id = qmatrix.id inner join ac on ac_id = ac.id inner join ann on ann_id = ann.id where qmatrix.name = 'plsepsis' and entry_name in ('CRHBP_HUMAN', 'CRP_HUMAN', 'HLAC_HUMAN');").await?;
     let mut glyphs = vec![];
     for item in &items {
         glyphs.push(BoxGlyph {
@@ -1,3 +1,6 @@
+pub mod benchmark;
+pub mod benchmark_settings;
+pub mod confusion;
 pub mod fig1d;
 pub mod fig1e;
 pub mod fig2c;
@@ -0,0 +1,231 @@
+//! Bulk loading with `COPY ... FROM STDIN (FORMAT csv)`.
+//!
+//! Rows are encoded client side first; a row that cannot be encoded is
+//! rejected with its reason. The encoded rows are copied in batches inside one
+//! transaction. A batch refused by the server is bisected under savepoints
+//! until the offending rows are isolated and rejected with the database error.
+//! The load fails and leaves the table untouched when more than the allowed
+//! share of rows is rejected.
+
+use iwf::sql::ModelManager;
+use iwf::sql::{Error, Result};
+use log::warn;
+use sqlx::{Acquire, PgConnection};
+
+/// Share of rejected rows above which a load fails.
+pub const MAX_REJECT_RATE: f64 = 0.001;
+const BATCH_SIZE: usize = 50_000;
+const N_REPORTED: usize = 20;
+
+/// A value that can be written as a CSV field of a `COPY` row.
+pub trait CopyField {
+    /// Appends the encoded field, or returns why the value cannot be stored.
+    fn write_copy(&self, out: &mut String) -> std::result::Result<(), String>;
+}
+
+/// A row of a table loaded with `copy_in`; `copy_fields` follows `COLUMNS`.
+pub trait CopyRow {
+    const COLUMNS: &'static [&'static str];
+    fn copy_fields(&self) -> Vec<&dyn CopyField>;
+}
+
+impl CopyField for i32 {
+    fn write_copy(&self, out: &mut String) -> std::result::Result<(), String> {
+        out.push_str(&self.to_string());
+        Ok(())
+    }
+}
+
+impl CopyField for i64 {
+    fn write_copy(&self, out: &mut String) -> std::result::Result<(), String> {
+        out.push_str(&self.to_string());
+        Ok(())
+    }
+}
+
+impl CopyField for f64 {
+    fn write_copy(&self, out: &mut String) -> std::result::Result<(), String> {
+        match self {
+            v if v.is_nan() => out.push_str("NaN"),
+            v if v.is_infinite() && *v > 0.0 => out.push_str("Infinity"),
+            v if v.is_infinite() => out.push_str("-Infinity"),
+            v => out.push_str(&v.to_string()),
+        }
+        Ok(())
+    }
+}
+
+impl CopyField for bool {
+    fn write_copy(&self, out: &mut String) -> std::result::Result<(), String> {
+        out.push(if *self { 't' } else { 'f' });
+        Ok(())
+    }
+}
+
+/// Always quoted, so an empty string is not read as NULL.
+impl CopyField for String {
+    fn write_copy(&self, out: &mut String) -> std::result::Result<(), String> {
+        if self.contains('\0') {
+            return Err(String::from("text contains a NUL byte"));
+        }
+        out.push('"');
+        out.push_str(&self.replace('"', "\"\""));
+        out.push('"');
+        Ok(())
+    }
+}
+
+impl CopyField for Vec<i32> {
+    fn write_copy(&self, out: &mut String) -> std::result::Result<(), String> {
+        let values: Vec<String> = self.iter().map(ToString::to_string).collect();
+        out.push_str(&format!("\"{{{}}}\"", values.join(",")));
+        Ok(())
+    }
+}
+
+/// `None` is an unquoted empty field, which `COPY` reads as NULL.
+impl<T: CopyField> CopyField for Option<T> {
+    fn write_copy(&self, out: &mut String) -> std::result::Result<(), String> {
+        match self {
+            Some(value) => value.write_copy(out),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Rejected {
+    /// Position of the row in the input.
+    pub row: usize,
+    pub reason: String,
+}
+
+#[derive(Debug, Default)]
+pub struct BulkReport {
+    pub n_rows: usize,
+    pub n_loaded: u64,
+    pub rejected: Vec<Rejected>,
+}
+
+fn encode<R: CopyRow>(row: &R) -> std::result::Result<String, String> {
+    let fields = row.copy_fields();
+    if fields.len() != R::COLUMNS.len() {
+        return Err(format!(
+            "{} values for {} columns",
+            fields.len(),
+            R::COLUMNS.len()
+        ));
+    }
+    let mut line = String::new();
+    for (i, (field, column)) in fields.iter().zip(R::COLUMNS).enumerate() {
+        if i > 0 {
+            line.push(',');
+        }
+        field
+            .write_copy(&mut line)
+            .map_err(|e| format!("{column}: {e}"))?;
+    }
+    line.push('\n');
+    Ok(line)
+}
+
+async fn copy_batch(
+    conn: &mut PgConnection,
+    stmt: &str,
+    batch: &[(usize, String)],
+) -> std::result::Result<u64, sqlx::Error> {
+    let data: String = batch.iter().map(|(_, line)| line.as_str()).collect();
+    let mut copy = conn.copy_in_raw(stmt).await?;
+    copy.send(data.into_bytes()).await?;
+    copy.finish().await
+}
+
+/// Loads `rows` into `table` with the default rejection threshold.
+pub async fn copy_in<R: CopyRow>(mm: &ModelManager, table: &str, rows: &[R]) -> Result<BulkReport> {
+    copy_in_with(mm, table, rows, MAX_REJECT_RATE).await
+}
+
+pub async fn copy_in_with<R: CopyRow>(
+    mm: &ModelManager,
+    table: &str,
+    rows: &[R],
+    max_reject_rate: f64,
+) -> Result<BulkReport> {
+    let mut report = BulkReport {
+        n_rows: rows.len(),
+        ..Default::default()
+    };
+    let max_rejected = (max_reject_rate * rows.len() as f64).floor() as usize;
+    let fail = |report: &BulkReport| {
+        let first = &report.rejected[0];
+        Error::Specified(format!(
+            "Bulk load of {table} failed: {} of {} rows rejected, row {}: {}",
+            report.rejected.len(),
+            report.n_rows,
+            first.row,
+            first.reason
+        ))
+    };
+    let mut encoded = Vec::with_capacity(rows.len());
+    for (i, row) in rows.iter().enumerate() {
+        match encode(row) {
+            Ok(line) => encoded.push((i, line)),
+            Err(reason) => report.rejected.push(Rejected { row: i, reason }),
+        }
+    }
+    if report.rejected.len() > max_rejected {
+        return Err(fail(&report));
+    }
+    if encoded.is_empty() {
+        return Ok(report);
+    }
+
+    let stmt = format!(
+        "COPY {table} ({}) FROM STDIN (FORMAT csv)",
+        R::COLUMNS.join(",")
+    );
+    let mut tx = mm.db().begin().await?;
+    let mut pending: Vec<&[(usize, String)]> = encoded.chunks(BATCH_SIZE).rev().collect();
+    while let Some(batch) = pending.pop() {
+        let mut savepoint = (&mut tx).begin().await?;
+        match copy_batch(&mut savepoint, &stmt, batch).await {
+            Ok(n) => {
+                savepoint.commit().await?;
+                report.n_loaded += n;
+            }
+            Err(e) => {
+                savepoint.rollback().await?;
+                if batch.len() == 1 {
+                    report.rejected.push(Rejected {
+                        row: batch[0].0,
+                        reason: e.to_string(),
+                    });
+                    if report.rejected.len() > max_rejected {
+                        report.rejected.sort_by_key(|e| e.row);
+                        return Err(fail(&report));
+                    }
+                } else {
+                    let (head, tail) = batch.split_at(batch.len() / 2);
+                    pending.push(tail);
+                    pending.push(head);
+                }
+            }
+        }
+    }
+    tx.commit().await?;
+
+    report.rejected.sort_by_key(|e| e.row);
+    for rejected in report.rejected.iter().take(N_REPORTED) {
+        warn!(
+            "{table}: rejected row {}: {}",
+            rejected.row, rejected.reason
+        );
+    }
+    if report.rejected.len() > N_REPORTED {
+        warn!(
+            "{table}: {} more rows rejected",
+            report.rejected.len() - N_REPORTED
+        );
+    }
+    Ok(report)
+}
@@ -1,5 +1,6 @@
+use crate::dialect::Dialect;
 use iwf::ctx::Ctx;
-use iwf::sql::base::{self, DbBmc, BTREE};
+use iwf::sql::base::{self, DbBmc};
 use iwf::sql::ModelManager;
 use iwf::sql::Result;
 use modql::field::Fields;
@@ -15,15 +16,17 @@ pub struct ClusterForCreate {
 
 impl ClusterBmc {
     #[must_use]
-    pub fn get_create_sql(drop_table: bool) -> String {
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
         let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
         format!(
             r##"{}
 create table if not exists {table} (
-  id serial primary key,
+  id {pk},
   label character varying not null
 );
-create index if not exists "IDX_{table}_label" ON {table} {BTREE} (label);
+create index if not exists "IDX_{table}_label" ON {table} {btree} (label);
         "##,
             match drop_table {
                 true => format!("drop table if exists {table};"),
@@ -1,5 +1,7 @@
+use crate::bulk::{self, CopyField, CopyRow};
+use crate::dialect::Dialect;
 use iwf::ctx::Ctx;
-use iwf::sql::base::{self, DbBmc, BTREE};
+use iwf::sql::base::{self, DbBmc};
 use iwf::sql::ModelManager;
 use iwf::sql::Result;
 use modql::field::Fields;
@@ -23,39 +25,51 @@ pub struct GseaEnrForCreate {
     pub n_query_genes: i32,
 }
 
+impl CopyRow for GseaEnrForCreate {
+    const COLUMNS: &'static [&'static str] = &[
+        "kind",
+        "adjusted_pvalue",
+        "cluster_id",
+        "combined_score",
+        "gene_set",
+        "genes",
+        "n_query_genes",
+        "odds_ratio",
+        "overlap",
+        "pvalue",
+        "term",
+    ];
+    fn copy_fields(&self) -> Vec<&dyn CopyField> {
+        vec![
+            &self.kind,
+            &self.adjusted_pvalue,
+            &self.cluster_id,
+            &self.combined_score,
+            &self.gene_set,
+            &self.genes,
+            &self.n_query_genes,
+            &self.odds_ratio,
+            &self.overlap,
+            &self.pvalue,
+            &self.term,
+        ]
+    }
+}
+
 impl GseaEnrBmc {
     pub async fn bulk_import(mm: &ModelManager, entries: Vec<GseaEnrForCreate>) -> Result<()> {
-        let mut tx = mm.db().begin().await?;
-        for entry in entries {
-            let _result = sqlx::query(&format!(
-            "INSERT INTO {} (kind,adjusted_pvalue,cluster_id,combined_score,gene_set,genes,n_query_genes,odds_ratio,overlap,pvalue,term)
-    VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11)",
-            Self::TABLE
-        ))
-        .bind(entry.kind)
-        .bind(entry.adjusted_pvalue)
-        .bind(entry.cluster_id)
-        .bind(entry.combined_score)
-        .bind(entry.gene_set)
-        .bind(entry.genes)
-        .bind(entry.n_query_genes)
-        .bind(entry.odds_ratio)
-        .bind(entry.overlap)
-        .bind(entry.pvalue)
-        .bind(entry.term)
-        .execute(&mut *tx)
-        .await;
-        }
-        tx.commit().await?;
+        bulk::copy_in(mm, Self::TABLE, &entries).await?;
         Ok(())
     }
     #[must_use]
-    pub fn get_create_sql(drop_table: bool) -> String {
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
         let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
         format!(
             r##"{}
 create table if not exists {table} (
-  id serial primary key,
+  id {pk},
   kind character varying not null,
   gene_set character varying not null,
   term character varying not null,
@@ -68,10 +82,10 @@ create table if not exists {table} (
   cluster_id character varying not null,
   n_query_genes integer not null
 );
-create index if not exists "IDX_{table}_kind" ON {table} {BTREE} (kind);
-create index if not exists "IDX_{table}_cluster_id" ON {table} {BTREE} (cluster_id);
-create index if not exists "IDX_{table}_term" ON {table} {BTREE} (term);
-create index if not exists "IDX_{table}_pvalue" ON {table} {BTREE} (pvalue);
+create index if not exists "IDX_{table}_kind" ON {table} {btree} (kind);
+create index if not exists "IDX_{table}_cluster_id" ON {table} {btree} (cluster_id);
+create index if not exists "IDX_{table}_term" ON {table} {btree} (term);
+create index if not exists "IDX_{table}_pvalue" ON {table} {btree} (pvalue);
         "##,
             match drop_table {
                 true => format!("drop table if exists {table};"),
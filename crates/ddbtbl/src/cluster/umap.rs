@@ -1,5 +1,7 @@
+use crate::cluster::cluster::ClusterBmc;
+use crate::dialect::Dialect;
 use iwf::ctx::Ctx;
-use iwf::sql::base::{self, DbBmc, BTREE};
+use iwf::sql::base::{self, DbBmc};
 use iwf::sql::ModelManager;
 use iwf::sql::Result;
 use modql::field::Fields;
@@ -18,21 +20,36 @@ pub struct UmapForCreate {
 }
 
 impl UmapBmc {
+    /// Deletes the embeddings of `kinds` together with their clusters.
+    pub async fn delete_kinds(mm: &ModelManager, kinds: &[String]) -> Result<()> {
+        sqlx::query(&format!(
+            "delete from {} where id in (select cluster_id from {} where kind = any($1))",
+            ClusterBmc::TABLE,
+            Self::TABLE
+        ))
+        .bind(kinds)
+        .execute(mm.db())
+        .await?;
+        crate::schema::delete_kinds(mm, Self::TABLE, kinds).await
+    }
+
     #[must_use]
-    pub fn get_create_sql(drop_table: bool) -> String {
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
         let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
         format!(
             r##"{}
 create table if not exists {table} (
-  id serial primary key,
+  id {pk},
   kind character varying not null,
   idx character varying not null,
   cluster_id integer not null,
   y1 float not null,
   y2 float not null
 );
-create index if not exists "IDX_{table}_cluster_id" ON {table} {BTREE} (cluster_id);
-create index if not exists "IDX_{table}_idx" ON {table} {BTREE} (idx);
+create index if not exists "IDX_{table}_cluster_id" ON {table} {btree} (cluster_id);
+create index if not exists "IDX_{table}_idx" ON {table} {btree} (idx);
         "##,
             match drop_table {
                 true => format!("drop table if exists {table};"),
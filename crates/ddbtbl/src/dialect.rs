@@ -0,0 +1,89 @@
+//! SQL fragments that differ between the Postgres analysis database and a
+//! SQLite file. Integer arrays are stored as JSON text in SQLite.
+
+use iwf::sql::base::BTREE;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Dialect {
+    #[default]
+    Postgres,
+    Sqlite,
+}
+
+impl Dialect {
+    /// Type of an auto-incrementing integer primary key.
+    #[must_use]
+    pub fn primary_key(self) -> &'static str {
+        match self {
+            Self::Postgres => "serial primary key",
+            Self::Sqlite => "integer primary key autoincrement",
+        }
+    }
+
+    /// Index method placed between the table and the column list.
+    #[must_use]
+    pub fn index_method(self) -> &'static str {
+        match self {
+            Self::Postgres => BTREE,
+            Self::Sqlite => "",
+        }
+    }
+
+    #[must_use]
+    pub fn int_array(self) -> &'static str {
+        match self {
+            Self::Postgres => "integer[]",
+            Self::Sqlite => "text",
+        }
+    }
+
+    /// Condition that `value` is an element of the integer array `array`.
+    #[must_use]
+    pub fn any(self, value: &str, array: &str) -> String {
+        match self {
+            Self::Postgres => format!("{value} = any({array})"),
+            Self::Sqlite => format!("{value} in (select value from json_each({array}))"),
+        }
+    }
+
+    /// `expr` values concatenated in order, separated by `separator`.
+    #[must_use]
+    pub fn string_agg(self, expr: &str, separator: &str) -> String {
+        match self {
+            Self::Postgres => format!("string_agg({expr}, '{separator}' order by {expr})"),
+            Self::Sqlite => format!("group_concat({expr}, '{separator}' order by {expr})"),
+        }
+    }
+
+    /// Adds the column `definition` to `table`; Postgres skips an existing
+    /// column, SQLite has no `if not exists` for columns.
+    #[must_use]
+    pub fn add_column(self, table: &str, definition: &str) -> String {
+        match self {
+            Self::Postgres => format!("alter table {table} add column if not exists {definition};"),
+            Self::Sqlite => format!("alter table {table} add column {definition};"),
+        }
+    }
+
+    /// Sample id of a sample embedding `idx` such as `c123`, the digits after
+    /// the letter prefix.
+    #[must_use]
+    pub fn sample_id(self, idx: &str) -> String {
+        match self {
+            Self::Postgres => format!("CAST(REGEXP_REPLACE({idx}, '[^0-9]', '', 'g') AS INTEGER)"),
+            Self::Sqlite => {
+                format!("CAST(ltrim({idx}, 'abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ_') AS INTEGER)")
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn greatest(self, a: &str, b: &str) -> String {
+        match self {
+            Self::Postgres => format!("greatest({a},{b})"),
+            Self::Sqlite => format!("max({a},{b})"),
+        }
+    }
+}
@@ -1,12 +1,15 @@
+use crate::bulk::{self, CopyField, CopyRow};
+use crate::dialect::Dialect;
 use camino::Utf8PathBuf;
 use iwf::ctx::Ctx;
-use iwf::sql::base::{self, DbBmc, BTREE};
+use iwf::sql::base::{self, DbBmc};
 use iwf::sql::ModelManager;
 use iwf::sql::Result;
 use modql::field::Fields;
 use modql::filter::{FilterNodes, ListOptions, OpValsInt64, OpValsString};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::collections::HashMap;
 use tabled::Tabled;
 
 #[derive(Fields, Deserialize, Clone, Debug)]
@@ -19,6 +22,13 @@ pub struct AcForCreate {
     pub frm: String,
 }
 
+impl CopyRow for AcForCreate {
+    const COLUMNS: &'static [&'static str] = &["entry", "entry_name", "frm"];
+    fn copy_fields(&self) -> Vec<&dyn CopyField> {
+        vec![&self.entry, &self.entry_name, &self.frm]
+    }
+}
+
 impl AcBmc {
     pub fn parse(input_path: &Utf8PathBuf) -> Result<Vec<AcForCreate>> {
         let mut ret = vec![];
@@ -32,35 +42,50 @@ impl AcBmc {
         Ok(ret)
     }
     pub async fn bulk_import(mm: &ModelManager, entries: Vec<AcForCreate>) -> Result<()> {
-        let mut tx = mm.db().begin().await?;
-        for entry in entries {
-            let _result = sqlx::query(&format!(
-                "INSERT INTO {} (entry,entry_name,frm)
-        VALUES ($1, $2, $3)",
-                Self::TABLE
-            ))
-            .bind(entry.entry)
-            .bind(entry.entry_name)
-            .bind(entry.frm)
-            .execute(&mut *tx)
-            .await;
-        }
-        tx.commit().await?;
+        bulk::copy_in(mm, Self::TABLE, &entries).await?;
         Ok(())
     }
+    /// Maps each entry to its source ids and its entry name mnemonic, used to
+    /// match accessions against gene symbol based gene sets.
+    pub async fn get_aliases(mm: &ModelManager) -> Result<HashMap<String, Vec<String>>> {
+        let rows: Vec<(String, String, String)> = sqlx::query_as(&format!(
+            "select entry,entry_name,frm from {} order by id",
+            Self::TABLE
+        ))
+        .fetch_all(mm.db())
+        .await?;
+        let mut ret: HashMap<String, Vec<String>> = HashMap::new();
+        for (entry, entry_name, frm) in rows {
+            let aliases = ret.entry(entry).or_default();
+            for alias in Self::aliases(&entry_name, &frm) {
+                if !aliases.iter().any(|e| e == alias) {
+                    aliases.push(alias.to_string());
+                }
+            }
+        }
+        Ok(ret)
+    }
+    /// Non-empty source id `frm` and mnemonic of `entry_name` (`ALBU` of
+    /// `ALBU_HUMAN`), the names gene sets and reference lists use.
+    pub fn aliases<'a>(entry_name: &'a str, frm: &'a str) -> impl Iterator<Item = &'a str> {
+        let mnemonic = entry_name.split('_').next().unwrap_or_default();
+        [frm, mnemonic].into_iter().filter(|e| !e.is_empty())
+    }
     #[must_use]
-    pub fn get_create_sql(drop_table: bool) -> String {
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
         let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
         format!(
             r##"{}
 create table if not exists {table} (
-  id serial primary key,
+  id {pk},
   entry character varying not null,
   entry_name character varying not null,
   frm character varying not null,
   description character varying not null default ''
 );
-create index if not exists "IDX_{table}_frm" ON {table} {BTREE} (frm);
+create index if not exists "IDX_{table}_frm" ON {table} {btree} (frm);
         "##,
             match drop_table {
                 true => format!("drop table if exists {table};"),
@@ -1,6 +1,8 @@
+use crate::bulk::{self, CopyField, CopyRow};
+use crate::dialect::Dialect;
 use camino::Utf8PathBuf;
 use iwf::ctx::Ctx;
-use iwf::sql::base::{self, DbBmc, BTREE};
+use iwf::sql::base::{self, DbBmc};
 use iwf::sql::ModelManager;
 use iwf::sql::Result;
 use modql::field::Fields;
@@ -20,6 +22,29 @@ pub struct AnnForCreate {
     pub qmatrix_type: String,
 }
 
+impl CopyRow for AnnForCreate {
+    const COLUMNS: &'static [&'static str] = &[
+        "measurement",
+        "display_name",
+        "permid",
+        "global_grp",
+        "sample_grp",
+        "sample_type",
+        "qmatrix_type",
+    ];
+    fn copy_fields(&self) -> Vec<&dyn CopyField> {
+        vec![
+            &self.measurement,
+            &self.display_name,
+            &self.permid,
+            &self.global_grp,
+            &self.sample_grp,
+            &self.sample_type,
+            &self.qmatrix_type,
+        ]
+    }
+}
+
 impl AnnBmc {
     pub fn parse(input_path: &Utf8PathBuf) -> Result<Vec<AnnForCreate>> {
         let mut ret = vec![];
@@ -33,34 +58,18 @@ impl AnnBmc {
         Ok(ret)
     }
     pub async fn bulk_import(mm: &ModelManager, entries: Vec<AnnForCreate>) -> Result<()> {
-        let mut tx = mm.db().begin().await?;
-        for entry in entries {
-            let _result = sqlx::query(&format!(
-                "INSERT INTO {} (measurement,display_name,permid,global_grp,sample_grp,sample_type,qmatrix_type)
-
-        VALUES ($1, $2, $3, $4, $5, $6, $7)",
-                Self::TABLE
-            ))
-            .bind(entry.measurement)
-            .bind(entry.display_name)
-            .bind(entry.permid)
-            .bind(entry.global_grp)
-            .bind(entry.sample_grp)
-            .bind(entry.sample_type)
-            .bind(entry.qmatrix_type)
-            .execute(&mut *tx)
-            .await;
-        }
-        tx.commit().await?;
+        bulk::copy_in(mm, Self::TABLE, &entries).await?;
         Ok(())
     }
     #[must_use]
-    pub fn get_create_sql(drop_table: bool) -> String {
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
         let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
         format!(
             r##"{}
 create table if not exists {table} (
-  id serial primary key,
+  id {pk},
   measurement character varying not null,
   display_name character varying not null,
   permid character varying not null,
@@ -69,8 +78,8 @@ create table if not exists {table} (
   sample_type character varying not null,
   qmatrix_type character varying not null
 );
-create index if not exists "IDX_{table}_measurement" ON {table} {BTREE} (measurement);
-create index if not exists "IDX_{table}_global_grp" ON {table} {BTREE} (global_grp);
+create index if not exists "IDX_{table}_measurement" ON {table} {btree} (measurement);
+create index if not exists "IDX_{table}_global_grp" ON {table} {btree} (global_grp);
         "##,
             match drop_table {
                 true => format!("drop table if exists {table};"),
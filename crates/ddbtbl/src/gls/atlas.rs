@@ -0,0 +1,286 @@
+use crate::bulk::{self, CopyField, CopyRow};
+use crate::dialect::Dialect;
+use iwf::ctx::Ctx;
+use iwf::sql::base::{self, DbBmc};
+use iwf::sql::ModelManager;
+use iwf::sql::{Error, Result};
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, OpValsInt64, OpValsString};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::fmt;
+use tabled::Tabled;
+
+/// Whether an atlas labels proteins with tissues or with cell types.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AtlasKind {
+    Tissue,
+    Cell,
+}
+
+impl fmt::Display for AtlasKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Tissue => write!(f, "tissue"),
+            Self::Cell => write!(f, "cell"),
+        }
+    }
+}
+
+impl AtlasKind {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "tissue" => Ok(Self::Tissue),
+            "cell" => Ok(Self::Cell),
+            _ => Err(Error::Specified(format!("Unknown atlas kind: {name}"))),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Modality {
+    Protein,
+    Rna,
+}
+
+impl fmt::Display for Modality {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Protein => write!(f, "protein"),
+            Self::Rna => write!(f, "rna"),
+        }
+    }
+}
+
+impl Modality {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "protein" => Ok(Self::Protein),
+            "rna" => Ok(Self::Rna),
+            _ => Err(Error::Specified(format!("Unknown atlas modality: {name}"))),
+        }
+    }
+}
+
+/// An atlas as configured in the workflow JSON. `source` names the raw
+/// qmatrix that is averaged per `global_grp` into the `name` qmatrix; atlases
+/// without a source are loaded as they are.
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+pub struct AtlasDef {
+    pub name: String,
+    pub kind: AtlasKind,
+    pub modality: Modality,
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+/// Checks that `name` matches `^[a-z][a-z0-9_]*$`; atlas names end up in
+/// the columns and string literals of the `labels_pivot` view.
+pub fn check_name(name: &str) -> Result<()> {
+    let mut chars = name.chars();
+    let valid = chars.next().is_some_and(|e| e.is_ascii_lowercase())
+        && chars.all(|e| e.is_ascii_lowercase() || e.is_ascii_digit() || e == '_');
+    match valid {
+        true => Ok(()),
+        false => Err(Error::Specified(format!(
+            "Invalid atlas name {name:?}: expected lower case letters, digits and '_', starting with a letter"
+        ))),
+    }
+}
+
+impl AtlasDef {
+    fn new(name: &str, kind: AtlasKind, modality: Modality, source: Option<&str>) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            modality,
+            source: source.map(String::from),
+        }
+    }
+
+    /// The atlases of the GLS publication, used when none are configured.
+    #[must_use]
+    pub fn builtin() -> Vec<Self> {
+        vec![
+            Self::new(
+                "haatlas",
+                AtlasKind::Tissue,
+                Modality::Protein,
+                Some("haatlasraw"),
+            ),
+            Self::new("emblatlas", AtlasKind::Tissue, Modality::Rna, None),
+            Self::new(
+                "msratlas",
+                AtlasKind::Tissue,
+                Modality::Rna,
+                Some("msratlasraw"),
+            ),
+            Self::new(
+                "mspatlas",
+                AtlasKind::Tissue,
+                Modality::Protein,
+                Some("mspatlasraw"),
+            ),
+            Self::new(
+                "hacells",
+                AtlasKind::Cell,
+                Modality::Protein,
+                Some("hacellsraw"),
+            ),
+            Self::new(
+                "emblcells",
+                AtlasKind::Cell,
+                Modality::Rna,
+                Some("emblcellsraw"),
+            ),
+        ]
+    }
+}
+
+impl TryFrom<Atlas> for AtlasDef {
+    type Error = Error;
+
+    fn try_from(item: Atlas) -> Result<Self> {
+        check_name(&item.name)?;
+        Ok(Self {
+            kind: AtlasKind::from_name(&item.kind)?,
+            modality: Modality::from_name(&item.modality)?,
+            source: match item.source.is_empty() {
+                true => None,
+                false => Some(item.source),
+            },
+            name: item.name,
+        })
+    }
+}
+
+#[derive(Fields, Deserialize, Clone, Debug)]
+pub struct AtlasForCreate {
+    pub name: String,
+    pub kind: String,
+    pub modality: String,
+    pub source: String,
+}
+
+impl CopyRow for AtlasForCreate {
+    const COLUMNS: &'static [&'static str] = &["name", "kind", "modality", "source"];
+    fn copy_fields(&self) -> Vec<&dyn CopyField> {
+        vec![&self.name, &self.kind, &self.modality, &self.source]
+    }
+}
+
+impl From<&AtlasDef> for AtlasForCreate {
+    fn from(item: &AtlasDef) -> Self {
+        Self {
+            name: item.name.clone(),
+            kind: item.kind.to_string(),
+            modality: item.modality.to_string(),
+            source: item.source.clone().unwrap_or_default(),
+        }
+    }
+}
+
+impl AtlasBmc {
+    pub async fn bulk_import(mm: &ModelManager, entries: Vec<AtlasForCreate>) -> Result<()> {
+        for entry in &entries {
+            check_name(&entry.name)?;
+        }
+        bulk::copy_in(mm, Self::TABLE, &entries).await?;
+        Ok(())
+    }
+
+    /// Returns the registered atlases in insertion order.
+    pub async fn load(mm: &ModelManager) -> Result<Vec<AtlasDef>> {
+        let items: Vec<Atlas> =
+            sqlx::query_as(&format!("select * from {} order by id", Self::TABLE))
+                .fetch_all(mm.db())
+                .await?;
+        items.into_iter().map(AtlasDef::try_from).collect()
+    }
+
+    #[must_use]
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
+        let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        format!(
+            r##"{}
+create table if not exists {table} (
+  id {pk},
+  name character varying not null unique,
+  kind character varying not null,
+  modality character varying not null,
+  source character varying not null
+);
+        "##,
+            match drop_table {
+                true => format!("drop table if exists {table};"),
+                false => String::new(),
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Fields, FromRow, Serialize, Deserialize, Default, Tabled)]
+pub struct Atlas {
+    pub id: i32,
+    pub name: String,
+    pub kind: String,
+    pub modality: String,
+    pub source: String,
+}
+
+#[derive(Fields, Default, Deserialize, Debug)]
+pub struct AtlasForUpdate {
+    pub name: Option<String>,
+    pub kind: Option<String>,
+    pub modality: Option<String>,
+    pub source: Option<String>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct AtlasFilter {
+    id: Option<OpValsInt64>,
+    name: Option<OpValsString>,
+    kind: Option<OpValsString>,
+    modality: Option<OpValsString>,
+    source: Option<OpValsString>,
+}
+
+pub struct AtlasBmc;
+
+impl DbBmc for AtlasBmc {
+    const TABLE: &'static str = "atlas";
+}
+
+impl AtlasBmc {
+    pub async fn create(ctx: &Ctx, mm: &ModelManager, clone_c: AtlasForCreate) -> Result<i32> {
+        base::create::<Self, _>(ctx, mm, clone_c).await
+    }
+    pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<Atlas> {
+        base::get::<Self, _>(ctx, mm, id).await
+    }
+
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<AtlasFilter>>,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<Atlas>> {
+        base::list::<Self, _, _>(ctx, mm, filters, list_options).await
+    }
+
+    pub async fn update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i32,
+        clone_u: AtlasForUpdate,
+    ) -> Result<()> {
+        base::update::<Self, _>(ctx, mm, id, clone_u).await
+    }
+
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<()> {
+        base::delete::<Self>(ctx, mm, id).await
+    }
+}
@@ -0,0 +1,166 @@
+use crate::bulk::{self, CopyField, CopyRow};
+use crate::dialect::Dialect;
+use iwf::ctx::Ctx;
+use iwf::sql::base::{self, DbBmc};
+use iwf::sql::ModelManager;
+use iwf::sql::Result;
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, OpValsFloat64, OpValsInt64, OpValsString};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tabled::Tabled;
+
+/// Per tissue agreement of `label_column` (`global` or `tissue`) of `labeldata` with
+/// the reference list.
+#[derive(Fields, Deserialize, Clone, Debug)]
+pub struct BenchmarkForCreate {
+    pub score_setting_id: i32,
+    pub label_column: String,
+    pub tissue: String,
+    pub n_tp: i32,
+    pub n_fp: i32,
+    pub n_fn: i32,
+    pub precision: f64,
+    pub recall: f64,
+    pub f1: f64,
+}
+
+impl CopyRow for BenchmarkForCreate {
+    const COLUMNS: &'static [&'static str] = &[
+        "score_setting_id",
+        "label_column",
+        "tissue",
+        "n_tp",
+        "n_fp",
+        "n_fn",
+        "precision",
+        "recall",
+        "f1",
+    ];
+    fn copy_fields(&self) -> Vec<&dyn CopyField> {
+        vec![
+            &self.score_setting_id,
+            &self.label_column,
+            &self.tissue,
+            &self.n_tp,
+            &self.n_fp,
+            &self.n_fn,
+            &self.precision,
+            &self.recall,
+            &self.f1,
+        ]
+    }
+}
+
+impl BenchmarkBmc {
+    pub async fn bulk_import(mm: &ModelManager, entries: Vec<BenchmarkForCreate>) -> Result<()> {
+        bulk::copy_in(mm, Self::TABLE, &entries).await?;
+        Ok(())
+    }
+    #[must_use]
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
+        let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
+        format!(
+            r##"{}
+create table if not exists {table} (
+  id {pk},
+  score_setting_id integer not null,
+  label_column character varying not null,
+  tissue character varying not null,
+  n_tp integer not null,
+  n_fp integer not null,
+  n_fn integer not null,
+  precision float not null,
+  recall float not null,
+  f1 float not null
+);
+
+create index if not exists "IDX_{table}_score_setting_id" ON {table} {btree} (score_setting_id);
+        "##,
+            match drop_table {
+                true => format!("drop table if exists {table};"),
+                false => String::new(),
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Fields, FromRow, Serialize, Deserialize, Default, Tabled)]
+pub struct Benchmark {
+    pub id: i32,
+    pub score_setting_id: i32,
+    pub label_column: String,
+    pub tissue: String,
+    pub n_tp: i32,
+    pub n_fp: i32,
+    pub n_fn: i32,
+    pub precision: f64,
+    pub recall: f64,
+    pub f1: f64,
+}
+
+#[derive(Fields, Default, Deserialize, Debug)]
+pub struct BenchmarkForUpdate {
+    pub score_setting_id: Option<i32>,
+    pub label_column: Option<String>,
+    pub tissue: Option<String>,
+    pub n_tp: Option<i32>,
+    pub n_fp: Option<i32>,
+    pub n_fn: Option<i32>,
+    pub precision: Option<f64>,
+    pub recall: Option<f64>,
+    pub f1: Option<f64>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct BenchmarkFilter {
+    id: Option<OpValsInt64>,
+    score_setting_id: Option<OpValsInt64>,
+    label_column: Option<OpValsString>,
+    tissue: Option<OpValsString>,
+    n_tp: Option<OpValsInt64>,
+    n_fp: Option<OpValsInt64>,
+    n_fn: Option<OpValsInt64>,
+    precision: Option<OpValsFloat64>,
+    recall: Option<OpValsFloat64>,
+    f1: Option<OpValsFloat64>,
+}
+
+pub struct BenchmarkBmc;
+
+impl DbBmc for BenchmarkBmc {
+    const TABLE: &'static str = "benchmark";
+}
+
+impl BenchmarkBmc {
+    pub async fn create(ctx: &Ctx, mm: &ModelManager, clone_c: BenchmarkForCreate) -> Result<i32> {
+        base::create::<Self, _>(ctx, mm, clone_c).await
+    }
+    pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<Benchmark> {
+        base::get::<Self, _>(ctx, mm, id).await
+    }
+
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<BenchmarkFilter>>,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<Benchmark>> {
+        base::list::<Self, _, _>(ctx, mm, filters, list_options).await
+    }
+
+    pub async fn update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i32,
+        clone_u: BenchmarkForUpdate,
+    ) -> Result<()> {
+        base::update::<Self, _>(ctx, mm, id, clone_u).await
+    }
+
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<()> {
+        base::delete::<Self>(ctx, mm, id).await
+    }
+}
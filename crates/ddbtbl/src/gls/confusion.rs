@@ -0,0 +1,137 @@
+use crate::bulk::{self, CopyField, CopyRow};
+use crate::dialect::Dialect;
+use iwf::ctx::Ctx;
+use iwf::sql::base::{self, DbBmc};
+use iwf::sql::ModelManager;
+use iwf::sql::Result;
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, OpValsInt64, OpValsString};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tabled::Tabled;
+
+/// Number of proteins with reference tissue `expected` labelled `predicted`.
+#[derive(Fields, Deserialize, Clone, Debug)]
+pub struct ConfusionForCreate {
+    pub score_setting_id: i32,
+    pub label_column: String,
+    pub expected: String,
+    pub predicted: String,
+    pub n: i32,
+}
+
+impl CopyRow for ConfusionForCreate {
+    const COLUMNS: &'static [&'static str] = &[
+        "score_setting_id",
+        "label_column",
+        "expected",
+        "predicted",
+        "n",
+    ];
+    fn copy_fields(&self) -> Vec<&dyn CopyField> {
+        vec![
+            &self.score_setting_id,
+            &self.label_column,
+            &self.expected,
+            &self.predicted,
+            &self.n,
+        ]
+    }
+}
+
+impl ConfusionBmc {
+    pub async fn bulk_import(mm: &ModelManager, entries: Vec<ConfusionForCreate>) -> Result<()> {
+        bulk::copy_in(mm, Self::TABLE, &entries).await?;
+        Ok(())
+    }
+    #[must_use]
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
+        let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
+        format!(
+            r##"{}
+create table if not exists {table} (
+  id {pk},
+  score_setting_id integer not null,
+  label_column character varying not null,
+  expected character varying not null,
+  predicted character varying not null,
+  n integer not null
+);
+
+create index if not exists "IDX_{table}_score_setting_id" ON {table} {btree} (score_setting_id);
+        "##,
+            match drop_table {
+                true => format!("drop table if exists {table};"),
+                false => String::new(),
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Fields, FromRow, Serialize, Deserialize, Default, Tabled)]
+pub struct Confusion {
+    pub id: i32,
+    pub score_setting_id: i32,
+    pub label_column: String,
+    pub expected: String,
+    pub predicted: String,
+    pub n: i32,
+}
+
+#[derive(Fields, Default, Deserialize, Debug)]
+pub struct ConfusionForUpdate {
+    pub score_setting_id: Option<i32>,
+    pub label_column: Option<String>,
+    pub expected: Option<String>,
+    pub predicted: Option<String>,
+    pub n: Option<i32>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct ConfusionFilter {
+    id: Option<OpValsInt64>,
+    score_setting_id: Option<OpValsInt64>,
+    label_column: Option<OpValsString>,
+    expected: Option<OpValsString>,
+    predicted: Option<OpValsString>,
+    n: Option<OpValsInt64>,
+}
+
+pub struct ConfusionBmc;
+
+impl DbBmc for ConfusionBmc {
+    const TABLE: &'static str = "confusion";
+}
+
+impl ConfusionBmc {
+    pub async fn create(ctx: &Ctx, mm: &ModelManager, clone_c: ConfusionForCreate) -> Result<i32> {
+        base::create::<Self, _>(ctx, mm, clone_c).await
+    }
+    pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<Confusion> {
+        base::get::<Self, _>(ctx, mm, id).await
+    }
+
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<ConfusionFilter>>,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<Confusion>> {
+        base::list::<Self, _, _>(ctx, mm, filters, list_options).await
+    }
+
+    pub async fn update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i32,
+        clone_u: ConfusionForUpdate,
+    ) -> Result<()> {
+        base::update::<Self, _>(ctx, mm, id, clone_u).await
+    }
+
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<()> {
+        base::delete::<Self>(ctx, mm, id).await
+    }
+}
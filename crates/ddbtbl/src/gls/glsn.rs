@@ -1,5 +1,7 @@
+use crate::bulk::{self, CopyField, CopyRow};
+use crate::dialect::Dialect;
 use iwf::ctx::Ctx;
-use iwf::sql::base::{self, DbBmc, BTREE};
+use iwf::sql::base::{self, DbBmc};
 use iwf::sql::ModelManager;
 use iwf::sql::Result;
 use modql::field::Fields;
@@ -16,40 +18,35 @@ pub struct GlsnForCreate {
     pub tissue: String,
 }
 
+impl CopyRow for GlsnForCreate {
+    const COLUMNS: &'static [&'static str] = &["qmatrix_id", "ac_id", "score", "tissue"];
+    fn copy_fields(&self) -> Vec<&dyn CopyField> {
+        vec![&self.qmatrix_id, &self.ac_id, &self.score, &self.tissue]
+    }
+}
+
 impl GlsnBmc {
     pub async fn bulk_import(mm: &ModelManager, entries: Vec<GlsnForCreate>) -> Result<()> {
-        let mut tx = mm.db().begin().await?;
-        for entry in entries {
-            let _result = sqlx::query(&format!(
-                "INSERT INTO {} (qmatrix_id,ac_id,score,tissue)
-    VALUES ($1,$2,$3,$4)",
-                Self::TABLE
-            ))
-            .bind(entry.qmatrix_id)
-            .bind(entry.ac_id)
-            .bind(entry.score)
-            .bind(entry.tissue)
-            .execute(&mut *tx)
-            .await;
-        }
-        tx.commit().await?;
+        bulk::copy_in(mm, Self::TABLE, &entries).await?;
         Ok(())
     }
     #[must_use]
-    pub fn get_create_sql(drop_table: bool) -> String {
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
         let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
         format!(
             r##"{}
 create table if not exists {table} (
-  id serial primary key,
+  id {pk},
   qmatrix_id integer not null,
   ac_id integer not null,
   score float not null,
   tissue character varying not null
 );
-create index if not exists "IDX_{table}_qmatrix_id" ON {table} {BTREE} (qmatrix_id);
-create index if not exists "IDX_{table}_ac_id" ON {table} {BTREE} (ac_id);
-create index if not exists "IDX_{table}_tissue" ON {table} {BTREE} (tissue);
+create index if not exists "IDX_{table}_qmatrix_id" ON {table} {btree} (qmatrix_id);
+create index if not exists "IDX_{table}_ac_id" ON {table} {btree} (ac_id);
+create index if not exists "IDX_{table}_tissue" ON {table} {btree} (tissue);
         "##,
             match drop_table {
                 true => format!("drop table if exists {table};"),
@@ -0,0 +1,327 @@
+//! Imputation of the (protein, sample) cells missing from a quantitative
+//! matrix. Every strategy draws from an RNG seeded by the configuration, and
+//! proteins and samples are visited in id order, so a configuration always
+//! imputes the same values.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+/// Measured values of a matrix on the input scale.
+#[derive(Default, Debug)]
+pub struct Observed {
+    values: HashMap<(i32, i32), f64>,
+    acs: BTreeSet<i32>,
+    anns: BTreeSet<i32>,
+}
+
+impl Observed {
+    pub fn insert(&mut self, ac_id: i32, ann_id: i32, value: f64) {
+        self.values.insert((ac_id, ann_id), value);
+        self.acs.insert(ac_id);
+        self.anns.insert(ann_id);
+    }
+
+    #[must_use]
+    pub fn get(&self, ac_id: i32, ann_id: i32) -> Option<f64> {
+        self.values.get(&(ac_id, ann_id)).copied()
+    }
+
+    #[must_use]
+    pub fn n_missing(&self) -> usize {
+        self.acs.len() * self.anns.len() - self.values.len()
+    }
+
+    /// Cells of the measured proteins and samples without a value, sample by
+    /// sample.
+    pub fn missing(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.anns.iter().flat_map(move |ann| {
+            self.acs
+                .iter()
+                .filter(move |ac| !self.values.contains_key(&(**ac, *ann)))
+                .map(move |ac| (*ac, *ann))
+        })
+    }
+
+    fn sorted_values(&self) -> Vec<f64> {
+        let mut values: Vec<f64> = self.values.values().copied().collect();
+        values.sort_by(f64::total_cmp);
+        values
+    }
+
+    fn protein_values(&self, ac_id: i32) -> impl Iterator<Item = f64> + '_ {
+        self.anns
+            .iter()
+            .filter_map(move |ann| self.get(ac_id, *ann))
+    }
+
+    fn protein_min(&self, ac_id: i32) -> f64 {
+        self.protein_values(ac_id).fold(f64::INFINITY, f64::min)
+    }
+}
+
+/// Fills the missing cells of a matrix, returning `(ac_id, ann_id, value)`.
+pub trait Imputer {
+    fn impute(&self, observed: &Observed, rng: &mut StdRng) -> Vec<(i32, i32, f64)>;
+}
+
+/// Leaves the missing cells empty.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default)]
+pub struct NoImputation;
+
+impl Imputer for NoImputation {
+    fn impute(&self, _observed: &Observed, _rng: &mut StdRng) -> Vec<(i32, i32, f64)> {
+        vec![]
+    }
+}
+
+/// Draws uniformly from the lowest `fraction` of all measured values.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug)]
+pub struct LowPercentile {
+    pub fraction: f64,
+}
+
+impl Default for LowPercentile {
+    fn default() -> Self {
+        Self { fraction: 0.01 }
+    }
+}
+
+impl Imputer for LowPercentile {
+    fn impute(&self, observed: &Observed, rng: &mut StdRng) -> Vec<(i32, i32, f64)> {
+        let values = observed.sorted_values();
+        let n = ((values.len() as f64 * self.fraction).ceil() as usize).min(values.len());
+        if n == 0 {
+            return vec![];
+        }
+        observed
+            .missing()
+            .map(|(ac, ann)| (ac, ann, values[rng.gen_range(0..n)]))
+            .collect()
+    }
+}
+
+/// Left-censored Gaussian (MinProb): draws per sample from a normal
+/// distribution centred on the sample's `quantile`, with the median
+/// per-protein standard deviation times `sd_scale` as width. Draws are
+/// truncated at the smallest measured value.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug)]
+pub struct MinProb {
+    pub quantile: f64,
+    pub sd_scale: f64,
+}
+
+impl Default for MinProb {
+    fn default() -> Self {
+        Self {
+            quantile: 0.01,
+            sd_scale: 1.0,
+        }
+    }
+}
+
+/// Standard normal draw (Box-Muller).
+fn standard_normal(rng: &mut StdRng) -> f64 {
+    let u1 = 1.0 - rng.gen::<f64>();
+    let u2 = rng.gen::<f64>();
+    (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
+}
+
+fn median(values: &mut [f64]) -> Option<f64> {
+    values.sort_by(f64::total_cmp);
+    let n = values.len();
+    match n {
+        0 => None,
+        _ if n % 2 == 1 => Some(values[n / 2]),
+        _ => Some((values[n / 2 - 1] + values[n / 2]) / 2.0),
+    }
+}
+
+impl Imputer for MinProb {
+    fn impute(&self, observed: &Observed, rng: &mut StdRng) -> Vec<(i32, i32, f64)> {
+        let floor = observed.sorted_values().first().copied().unwrap_or(0.0);
+        let mut sds: Vec<f64> = observed
+            .acs
+            .iter()
+            .filter_map(|ac| {
+                let values: Vec<f64> = observed.protein_values(*ac).collect();
+                let n = values.len() as f64;
+                if values.len() < 2 {
+                    return None;
+                }
+                let mean = values.iter().sum::<f64>() / n;
+                let var = values.iter().map(|e| (e - mean).powi(2)).sum::<f64>() / (n - 1.0);
+                Some(var.sqrt())
+            })
+            .collect();
+        let sd = median(&mut sds).unwrap_or(0.0) * self.sd_scale;
+        let centres: HashMap<i32, f64> = observed
+            .anns
+            .iter()
+            .filter_map(|ann| {
+                let mut values: Vec<f64> = observed
+                    .acs
+                    .iter()
+                    .filter_map(|ac| observed.get(*ac, *ann))
+                    .collect();
+                values.sort_by(f64::total_cmp);
+                let last = values.len().checked_sub(1)?;
+                let i = (self.quantile * last as f64).floor() as usize;
+                Some((*ann, values[i.min(last)]))
+            })
+            .collect();
+        observed
+            .missing()
+            .map(|(ac, ann)| {
+                let centre = centres.get(&ann).copied().unwrap_or(floor);
+                (ac, ann, (centre + sd * standard_normal(rng)).max(floor))
+            })
+            .collect()
+    }
+}
+
+/// Smallest measured value of the protein.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default)]
+pub struct ProteinMin;
+
+impl Imputer for ProteinMin {
+    fn impute(&self, observed: &Observed, _rng: &mut StdRng) -> Vec<(i32, i32, f64)> {
+        let mins: HashMap<i32, f64> = observed
+            .acs
+            .iter()
+            .map(|ac| (*ac, observed.protein_min(*ac)))
+            .collect();
+        observed
+            .missing()
+            .map(|(ac, ann)| (ac, ann, mins[&ac]))
+            .collect()
+    }
+}
+
+/// Mean of the `k` proteins closest to the protein (root mean squared
+/// difference over the shared samples) that were measured in the sample;
+/// the protein minimum when there are none. Quadratic in the number of
+/// proteins.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug)]
+pub struct Knn {
+    pub k: usize,
+}
+
+impl Default for Knn {
+    fn default() -> Self {
+        Self { k: 10 }
+    }
+}
+
+impl Knn {
+    /// Other proteins ordered by their distance to `ac_id`.
+    fn neighbours(observed: &Observed, ac_id: i32) -> Vec<i32> {
+        let mut distances: Vec<(f64, i32)> = observed
+            .acs
+            .iter()
+            .filter(|other| **other != ac_id)
+            .filter_map(|other| {
+                let (sum, n) = observed
+                    .anns
+                    .iter()
+                    .filter_map(|ann| {
+                        Some((observed.get(ac_id, *ann)?, observed.get(*other, *ann)?))
+                    })
+                    .fold((0.0, 0usize), |(sum, n), (a, b)| {
+                        (sum + (a - b).powi(2), n + 1)
+                    });
+                (n > 0).then(|| ((sum / n as f64).sqrt(), *other))
+            })
+            .collect();
+        distances.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        distances.into_iter().map(|e| e.1).collect()
+    }
+}
+
+impl Imputer for Knn {
+    /// Walks the matrix protein by protein, so that only the neighbour order
+    /// of the current protein is held in memory.
+    fn impute(&self, observed: &Observed, _rng: &mut StdRng) -> Vec<(i32, i32, f64)> {
+        let mut ret = vec![];
+        for ac in &observed.acs {
+            let missing: Vec<i32> = observed
+                .anns
+                .iter()
+                .filter(|ann| observed.get(*ac, **ann).is_none())
+                .copied()
+                .collect();
+            if missing.is_empty() {
+                continue;
+            }
+            let near = Self::neighbours(observed, *ac);
+            for ann in missing {
+                let values: Vec<f64> = near
+                    .iter()
+                    .filter_map(|other| observed.get(*other, ann))
+                    .take(self.k)
+                    .collect();
+                let value = match values.is_empty() {
+                    true => observed.protein_min(*ac),
+                    false => values.iter().sum::<f64>() / values.len() as f64,
+                };
+                ret.push((*ac, ann, value));
+            }
+        }
+        ret
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum ImputeMethod {
+    None,
+    LowPercentile(LowPercentile),
+    MinProb(MinProb),
+    ProteinMin,
+    Knn(Knn),
+}
+
+impl Default for ImputeMethod {
+    fn default() -> Self {
+        Self::LowPercentile(LowPercentile::default())
+    }
+}
+
+impl ImputeMethod {
+    #[must_use]
+    pub fn imputer(&self) -> &dyn Imputer {
+        match self {
+            Self::None => &NoImputation,
+            Self::LowPercentile(e) => e,
+            Self::MinProb(e) => e,
+            Self::ProteinMin => &ProteinMin,
+            Self::Knn(e) => e,
+        }
+    }
+}
+
+/// Imputation of one matrix. `scale` counts the imputed cells when the
+/// values are scaled per protein; otherwise they get a scaled value of 0.
+/// `legacy` reproduces the original imputation, which stores the drawn values
+/// of `ex` matrices without exponentiating them.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default)]
+pub struct Imputation {
+    #[serde(flatten)]
+    pub method: ImputeMethod,
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(default)]
+    pub scale: bool,
+    #[serde(default)]
+    pub legacy: bool,
+}
+
+impl Imputation {
+    /// Values of the missing cells of `observed`.
+    #[must_use]
+    pub fn run(&self, observed: &Observed) -> Vec<(i32, i32, f64)> {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        self.method.imputer().impute(observed, &mut rng)
+    }
+}
@@ -0,0 +1,149 @@
+use crate::bulk::{self, CopyField, CopyRow};
+use crate::dialect::Dialect;
+use iwf::ctx::Ctx;
+use iwf::sql::base::{self, DbBmc};
+use iwf::sql::ModelManager;
+use iwf::sql::Result;
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, OpValsInt64};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tabled::Tabled;
+
+/// Number of proteins whose labels under `score_setting_id` differ from the
+/// labels under the reference setting.
+#[derive(Fields, Deserialize, Clone, Debug)]
+pub struct LabelChangeForCreate {
+    pub score_setting_id: i32,
+    pub reference_id: i32,
+    pub n_proteins: i32,
+    pub n_global_changed: i32,
+    pub n_tissue_changed: i32,
+    pub n_cell_changed: i32,
+}
+
+impl CopyRow for LabelChangeForCreate {
+    const COLUMNS: &'static [&'static str] = &[
+        "score_setting_id",
+        "reference_id",
+        "n_proteins",
+        "n_global_changed",
+        "n_tissue_changed",
+        "n_cell_changed",
+    ];
+    fn copy_fields(&self) -> Vec<&dyn CopyField> {
+        vec![
+            &self.score_setting_id,
+            &self.reference_id,
+            &self.n_proteins,
+            &self.n_global_changed,
+            &self.n_tissue_changed,
+            &self.n_cell_changed,
+        ]
+    }
+}
+
+impl LabelChangeBmc {
+    pub async fn bulk_import(mm: &ModelManager, entries: Vec<LabelChangeForCreate>) -> Result<()> {
+        bulk::copy_in(mm, Self::TABLE, &entries).await?;
+        Ok(())
+    }
+    #[must_use]
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
+        let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
+        format!(
+            r##"{}
+create table if not exists {table} (
+  id {pk},
+  score_setting_id integer not null,
+  reference_id integer not null,
+  n_proteins integer not null,
+  n_global_changed integer not null,
+  n_tissue_changed integer not null,
+  n_cell_changed integer not null
+);
+
+create index if not exists "IDX_{table}_score_setting_id" ON {table} {btree} (score_setting_id);
+        "##,
+            match drop_table {
+                true => format!("drop table if exists {table};"),
+                false => String::new(),
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Fields, FromRow, Serialize, Deserialize, Default, Tabled)]
+pub struct LabelChange {
+    pub id: i32,
+    pub score_setting_id: i32,
+    pub reference_id: i32,
+    pub n_proteins: i32,
+    pub n_global_changed: i32,
+    pub n_tissue_changed: i32,
+    pub n_cell_changed: i32,
+}
+
+#[derive(Fields, Default, Deserialize, Debug)]
+pub struct LabelChangeForUpdate {
+    pub score_setting_id: Option<i32>,
+    pub reference_id: Option<i32>,
+    pub n_proteins: Option<i32>,
+    pub n_global_changed: Option<i32>,
+    pub n_tissue_changed: Option<i32>,
+    pub n_cell_changed: Option<i32>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct LabelChangeFilter {
+    id: Option<OpValsInt64>,
+    score_setting_id: Option<OpValsInt64>,
+    reference_id: Option<OpValsInt64>,
+    n_proteins: Option<OpValsInt64>,
+    n_global_changed: Option<OpValsInt64>,
+    n_tissue_changed: Option<OpValsInt64>,
+    n_cell_changed: Option<OpValsInt64>,
+}
+
+pub struct LabelChangeBmc;
+
+impl DbBmc for LabelChangeBmc {
+    const TABLE: &'static str = "labelchange";
+}
+
+impl LabelChangeBmc {
+    pub async fn create(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        clone_c: LabelChangeForCreate,
+    ) -> Result<i32> {
+        base::create::<Self, _>(ctx, mm, clone_c).await
+    }
+    pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<LabelChange> {
+        base::get::<Self, _>(ctx, mm, id).await
+    }
+
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<LabelChangeFilter>>,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<LabelChange>> {
+        base::list::<Self, _, _>(ctx, mm, filters, list_options).await
+    }
+
+    pub async fn update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i32,
+        clone_u: LabelChangeForUpdate,
+    ) -> Result<()> {
+        base::update::<Self, _>(ctx, mm, id, clone_u).await
+    }
+
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<()> {
+        base::delete::<Self>(ctx, mm, id).await
+    }
+}
@@ -0,0 +1,157 @@
+use crate::bulk::{self, CopyField, CopyRow};
+use crate::dialect::Dialect;
+use iwf::ctx::Ctx;
+use iwf::sql::base::{self, DbBmc};
+use iwf::sql::ModelManager;
+use iwf::sql::Result;
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, OpValsFloat64, OpValsInt64, OpValsString};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::collections::HashMap;
+use tabled::Tabled;
+
+/// Share of bootstrap replicates of atlas `kind` that label `ac_id` with `tissue`.
+#[derive(Fields, Deserialize, Clone, Debug)]
+pub struct LabelConfidenceForCreate {
+    pub ac_id: i32,
+    pub kind: String,
+    pub tissue: String,
+    pub n_replicates: i32,
+    pub confidence: f64,
+}
+
+impl CopyRow for LabelConfidenceForCreate {
+    const COLUMNS: &'static [&'static str] =
+        &["ac_id", "kind", "tissue", "n_replicates", "confidence"];
+    fn copy_fields(&self) -> Vec<&dyn CopyField> {
+        vec![
+            &self.ac_id,
+            &self.kind,
+            &self.tissue,
+            &self.n_replicates,
+            &self.confidence,
+        ]
+    }
+}
+
+impl LabelConfidenceBmc {
+    pub async fn bulk_import(
+        mm: &ModelManager,
+        entries: Vec<LabelConfidenceForCreate>,
+    ) -> Result<()> {
+        bulk::copy_in(mm, Self::TABLE, &entries).await?;
+        Ok(())
+    }
+
+    /// Highest confidence over all atlases per protein and tissue.
+    pub async fn get_confidence(mm: &ModelManager) -> Result<HashMap<i32, HashMap<String, f64>>> {
+        let items: Vec<(i32, String, f64)> = sqlx::query_as(&format!(
+            "select ac_id,tissue,max(confidence) from {} group by ac_id,tissue",
+            Self::TABLE
+        ))
+        .fetch_all(mm.db())
+        .await?;
+        let mut ret: HashMap<i32, HashMap<String, f64>> = HashMap::new();
+        for (ac_id, tissue, confidence) in items {
+            ret.entry(ac_id).or_default().insert(tissue, confidence);
+        }
+        Ok(ret)
+    }
+
+    #[must_use]
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
+        let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
+        format!(
+            r##"{}
+create table if not exists {table} (
+  id {pk},
+  ac_id integer not null,
+  kind character varying not null,
+  tissue character varying not null,
+  n_replicates integer not null,
+  confidence float not null
+);
+
+create index if not exists "IDX_{table}_ac_id" ON {table} {btree} (ac_id);
+create index if not exists "IDX_{table}_kind" ON {table} {btree} (kind);
+        "##,
+            match drop_table {
+                true => format!("drop table if exists {table};"),
+                false => String::new(),
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Fields, FromRow, Serialize, Deserialize, Default, Tabled)]
+pub struct LabelConfidence {
+    pub id: i32,
+    pub ac_id: i32,
+    pub kind: String,
+    pub tissue: String,
+    pub n_replicates: i32,
+    pub confidence: f64,
+}
+
+#[derive(Fields, Default, Deserialize, Debug)]
+pub struct LabelConfidenceForUpdate {
+    pub ac_id: Option<i32>,
+    pub kind: Option<String>,
+    pub tissue: Option<String>,
+    pub n_replicates: Option<i32>,
+    pub confidence: Option<f64>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct LabelConfidenceFilter {
+    id: Option<OpValsInt64>,
+    ac_id: Option<OpValsInt64>,
+    kind: Option<OpValsString>,
+    tissue: Option<OpValsString>,
+    n_replicates: Option<OpValsInt64>,
+    confidence: Option<OpValsFloat64>,
+}
+
+pub struct LabelConfidenceBmc;
+
+impl DbBmc for LabelConfidenceBmc {
+    const TABLE: &'static str = "labelconfidence";
+}
+
+impl LabelConfidenceBmc {
+    pub async fn create(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        clone_c: LabelConfidenceForCreate,
+    ) -> Result<i32> {
+        base::create::<Self, _>(ctx, mm, clone_c).await
+    }
+    pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<LabelConfidence> {
+        base::get::<Self, _>(ctx, mm, id).await
+    }
+
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<LabelConfidenceFilter>>,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<LabelConfidence>> {
+        base::list::<Self, _, _>(ctx, mm, filters, list_options).await
+    }
+
+    pub async fn update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i32,
+        clone_u: LabelConfidenceForUpdate,
+    ) -> Result<()> {
+        base::update::<Self, _>(ctx, mm, id, clone_u).await
+    }
+
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<()> {
+        base::delete::<Self>(ctx, mm, id).await
+    }
+}
@@ -1,6 +1,10 @@
+use crate::bulk::{self, CopyField, CopyRow};
+use crate::dialect::Dialect;
+use crate::gls::atlas::{AtlasDef, AtlasKind, Modality};
 use crate::gls::scoresetting::ScoreSettingForCreate;
+use crate::gls::tissue::Tissue;
 use iwf::ctx::Ctx;
-use iwf::sql::base::{self, DbBmc, BTREE};
+use iwf::sql::base::{self, DbBmc};
 use iwf::sql::ModelManager;
 use iwf::sql::Result;
 use modql::field::Fields;
@@ -11,273 +15,6 @@ use std::collections::{hash_map::Entry, HashMap, HashSet};
 use std::fmt;
 use tabled::Tabled;
 
-#[derive(Serialize, Deserialize, Debug, Hash, PartialEq, Eq)]
-pub enum DataSet {
-    #[serde(rename = "haatlas_clusters")]
-    HaAtlas,
-    #[serde(rename = "msratlas_clusters")]
-    MsrAtlas,
-    #[serde(rename = "mspatlas_clusters")]
-    MspAtlas,
-    #[serde(rename = "emblatlas_clusters")]
-    EmblAtlas,
-    #[serde(rename = "emblcells_clusters")]
-    EmblCells,
-    #[serde(rename = "hacells_clusters")]
-    HaCells,
-}
-
-impl fmt::Display for DataSet {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Self::HaAtlas => write!(f, "HaAtlas"),
-            Self::HaCells => write!(f, "HaCells"),
-            Self::EmblAtlas => write!(f, "EmblAtlas"),
-            Self::MsrAtlas => write!(f, "MsrAtlas"),
-            Self::MspAtlas => write!(f, "MspAtlas"),
-            Self::EmblCells => write!(f, "EmblCells"),
-        }
-    }
-}
-
-impl DataSet {
-    #[must_use]
-    pub fn from_str(name: &str) -> Self {
-        match name {
-            "msratlas" => Self::MsrAtlas,
-            "emblatlas" => Self::EmblAtlas,
-            "mspatlas" => Self::MspAtlas,
-            "haatlas" => Self::HaAtlas,
-            "hacells" => Self::HaCells,
-            "emblcells" => Self::EmblCells,
-            _ => panic!("Unknown dataset: {name}"),
-        }
-    }
-}
-
-#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone, Serialize, Deserialize)]
-pub enum Tissue {
-    Brain,
-    Muscle,
-    Nerve,
-    AdiposeTissue,
-    AdrenalGland,
-    Artery,
-    Bcell,
-    Bladder,
-    Bonemarrow,
-    Colon,
-    Erythrocytes,
-    Esophagus,
-    Heart,
-    Kidney,
-    Liver,
-    Lung,
-    Macrophages,
-    Monocytes,
-    Neutrophils,
-    Ovary,
-    Pancreas,
-    Platelets,
-    Prostate,
-    Skin,
-    Spleen,
-    Stomach,
-    TcellCd4,
-    TcellCd8,
-    Thyroid,
-    Common,
-    NoTissue,
-    Plasma,
-    Cmi,
-    Mi,
-    Nvar,
-    Cpancr,
-    Pancr,
-    Bsep,
-    Vsep,
-}
-
-impl fmt::Display for Tissue {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Self::Brain => write!(f, "brain"),
-            Self::Muscle => write!(f, "muscle"),
-            Self::Nerve => write!(f, "nerve"),
-            Self::AdiposeTissue => write!(f, "adiposetissue"),
-            Self::AdrenalGland => write!(f, "adrenalgland"),
-            Self::Artery => write!(f, "artery"),
-            Self::Bcell => write!(f, "bcell"),
-            Self::Bladder => write!(f, "bladder"),
-            Self::Bonemarrow => write!(f, "bonemarrow"),
-            Self::Colon => write!(f, "colon"),
-            Self::Common => write!(f, "common"),
-            Self::Erythrocytes => write!(f, "erythrocytes"),
-            Self::Esophagus => write!(f, "esophagus"),
-            Self::Heart => write!(f, "heart"),
-            Self::Kidney => write!(f, "kidney"),
-            Self::Liver => write!(f, "liver"),
-            Self::Lung => write!(f, "lung"),
-            Self::Macrophages => write!(f, "macrophages"),
-            Self::Monocytes => write!(f, "monocytes"),
-            Self::Neutrophils => write!(f, "neutrophils"),
-            Self::NoTissue => write!(f, "no_tissue"),
-            Self::Ovary => write!(f, "ovary"),
-            Self::Pancreas => write!(f, "pancreas"),
-            Self::Platelets => write!(f, "platelets"),
-            Self::Prostate => write!(f, "prostate"),
-            Self::Skin => write!(f, "skin"),
-            Self::Spleen => write!(f, "spleen"),
-            Self::Stomach => write!(f, "stomach"),
-            Self::TcellCd4 => write!(f, "tcellcd4"),
-            Self::TcellCd8 => write!(f, "tcellcd8"),
-            Self::Thyroid => write!(f, "thyroid"),
-            Self::Plasma => write!(f, "plasma"),
-            Self::Cmi => write!(f, "cmi"),
-            Self::Mi => write!(f, "mi"),
-            Self::Nvar => write!(f, "nvar"),
-            Self::Pancr => write!(f, "pancr"),
-            Self::Cpancr => write!(f, "cpancr"),
-            Self::Bsep => write!(f, "bsep"),
-            Self::Vsep => write!(f, "vsep"),
-        }
-    }
-}
-
-impl Tissue {
-    pub fn from_str(input: &str) -> Result<Self> {
-        match input {
-            "brain" => Ok(Self::Brain),
-            "muscle" => Ok(Self::Muscle),
-            "nerve" => Ok(Self::Nerve),
-            "adiposetissue" => Ok(Self::AdiposeTissue),
-            "adrenalgland" => Ok(Self::AdrenalGland),
-            "artery" => Ok(Self::Artery),
-            "bcell" => Ok(Self::Bcell),
-            "bladder" => Ok(Self::Bladder),
-            "bonemarrow" => Ok(Self::Bonemarrow),
-            "colon" => Ok(Self::Colon),
-            "common" => Ok(Self::Common),
-            "erythrocytes" => Ok(Self::Erythrocytes),
-            "esophagus" => Ok(Self::Esophagus),
-            "heart" => Ok(Self::Heart),
-            "kidney" => Ok(Self::Kidney),
-            "liver" => Ok(Self::Liver),
-            "lung" => Ok(Self::Lung),
-            "macrophage" => Ok(Self::Macrophages),
-            "macrophages" => Ok(Self::Macrophages),
-            "monocytes" => Ok(Self::Monocytes),
-            "neutrophils" => Ok(Self::Neutrophils),
-            "none" => Ok(Self::NoTissue),
-            "ovary" => Ok(Self::Ovary),
-            "pancreas" => Ok(Self::Pancreas),
-            "platelets" => Ok(Self::Platelets),
-            "prostate" => Ok(Self::Prostate),
-            "skin" => Ok(Self::Skin),
-            "spleen" => Ok(Self::Spleen),
-            "stomach" => Ok(Self::Stomach),
-            "tcellcd4" => Ok(Self::TcellCd4),
-            "tcellcd8" => Ok(Self::TcellCd8),
-            "thyroid" => Ok(Self::Thyroid),
-            "cmi" => Ok(Self::Cmi),
-            "mi" => Ok(Self::Mi),
-            "nvar" => Ok(Self::Nvar),
-            "pancr" => Ok(Self::Pancr),
-            "cpancr" => Ok(Self::Cpancr),
-            "vsep" => Ok(Self::Bsep),
-            "bsep" => Ok(Self::Vsep),
-            _ => panic!("Cannot match (tissue) {input}"),
-        }
-    }
-}
-
-impl Tissue {
-    #[must_use]
-    pub fn from_tag(grp: &str) -> Self {
-        match grp {
-            "adipo" => Self::AdiposeTissue,
-            "aorta" => Self::Artery,
-            "bcell" => Self::Bcell,
-            "bladd" => Self::Bladder,
-            "bonem" => Self::Bonemarrow,
-            "brain" => Self::Brain,
-            "endon" => Self::NoTissue,
-            "eryth" => Self::Erythrocytes,
-            "fasci" => Self::NoTissue,
-            "heart" => Self::Heart,
-            "kidne" => Self::Kidney,
-            "liver" => Self::Liver,
-            "lung" => Self::Lung,
-            "macro" => Self::Macrophages,
-            "monoc" => Self::Monocytes,
-            "muscl" => Self::Muscle,
-            "nerve" => Self::Nerve,
-            "neutr" => Self::Neutrophils,
-            "ovary" => Self::Ovary,
-            "pancr" => Self::Pancreas,
-            "plate" => Self::Platelets,
-            "prost" => Self::Prostate,
-            "sigmo" => Self::Colon,
-            "skin" => Self::Skin,
-            "splee" => Self::Spleen,
-            "stoma" => Self::Stomach,
-            "tcell4" => Self::TcellCd4,
-            "tcell8" => Self::TcellCd8,
-            "cmi" => Self::Cmi,
-            "mi" => Self::Mi,
-            "nvar" => Self::Nvar,
-            "cpancr" => Self::Cpancr,
-            "vsep" => Self::Vsep,
-            "bsep" => Self::Bsep,
-            _ => panic!("Unknown group"),
-        }
-    }
-    #[must_use]
-    pub fn get_color(&self) -> String {
-        match self {
-            Self::AdrenalGland => String::from("grey"),
-            Self::Artery => String::from("grey"),
-            Self::Colon => String::from("grey"),
-            Self::Esophagus => String::from("grey"),
-            Self::Thyroid => String::from("grey"),
-            Self::Common => String::from("grey"),
-            Self::NoTissue => String::from("grey"),
-            Self::Plasma => String::from("grey"),
-            Self::AdiposeTissue => String::from("grey"),
-            Self::Bcell => String::from("blue"),
-            Self::Bladder => String::from("grey"),
-            Self::Bonemarrow => String::from("grey"),
-            Self::Brain => String::from("blue"),
-            Self::Erythrocytes => String::from("grey"),
-            Self::Heart => String::from("red"),
-            Self::Kidney => String::from("grey"),
-            Self::Liver => String::from("green"),
-            Self::Lung => String::from("grey"),
-            Self::Macrophages => String::from("grey"),
-            Self::Monocytes => String::from("grey"),
-            Self::Muscle => String::from("yellow"),
-            Self::Nerve => String::from("cyan"),
-            Self::Neutrophils => String::from("grey"),
-            Self::Ovary => String::from("grey"),
-            Self::Pancreas => String::from("black"),
-            Self::Platelets => String::from("grey"),
-            Self::Prostate => String::from("grey"),
-            Self::Skin => String::from("grey"),
-            Self::Spleen => String::from("grey"),
-            Self::Stomach => String::from("grey"),
-            Self::TcellCd4 => String::from("grey"),
-            Self::TcellCd8 => String::from("grey"),
-            Self::Cmi => String::from("grey"),
-            Self::Mi => String::from("blue"),
-            Self::Nvar => String::from("grey"),
-            Self::Pancr => String::from("blue"),
-            Self::Cpancr => String::from("grey"),
-            Self::Bsep => String::from("orange"),
-            Self::Vsep => String::from("cyan"),
-        }
-    }
-}
-
 #[derive(Debug, Clone, Serialize)]
 pub struct ScoreCount {
     pub n: i32,
@@ -293,10 +30,39 @@ fn format_label(labels: &HashSet<Tissue>) -> Result<String> {
     Ok(v.join("."))
 }
 
-#[derive(Debug, Serialize)]
+/// Returns the entropy of the measured `labels` and its most frequent label
+/// (ties joined by '.'). "missing" padding of atlases that did not measure
+/// the protein is left out, so the entropy does not depend on coverage.
+/// Vectors without any measured label get 100 and "missing".
+fn entropy(labels: &[String]) -> (f32, String) {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for label in labels.iter().filter(|l| *l != "missing") {
+        *counts.entry(label).or_insert(0) += 1;
+    }
+    let Some(max) = counts.values().copied().max() else {
+        return (100.0, String::from("missing"));
+    };
+    let total = counts.values().sum::<usize>() as f64;
+    let entropy: f64 = counts
+        .values()
+        .map(|n| {
+            let p = *n as f64 / total;
+            p * (1.0 / p).log2()
+        })
+        .sum();
+    let mut dominant: Vec<&str> = counts
+        .iter()
+        .filter(|(_, v)| **v == max)
+        .map(|(k, _)| *k)
+        .collect();
+    dominant.sort_unstable();
+    (entropy as f32, dominant.join("."))
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct FullLabelData {
     #[serde(skip)]
-    pub label: HashMap<DataSet, HashSet<Tissue>>,
+    pub label: HashMap<AtlasDef, HashSet<Tissue>>,
     pub score_setting_id: i32,
     pub ac: String,
     pub description: String,
@@ -321,6 +87,8 @@ pub struct FullLabelData {
     #[serde(skip)]
     pub global_label: HashSet<Tissue>,
     pub global_label_string: String,
+    /// Mean bootstrap confidence of the global label tissues, -1 without bootstrap.
+    pub label_confidence: f64,
     pub cell_entropy: f32,
     pub cell_entropy_label: String,
     #[serde(skip)]
@@ -333,7 +101,10 @@ pub struct FullLabelData {
 }
 
 impl FullLabelData {
-    pub fn count(&mut self, settings: &ScoreSettingForCreate) {
+    /// Scores the labels per tissue and cell; `atlases` is the registry the
+    /// entropy label vectors are padded to.
+    pub fn count(&mut self, settings: &ScoreSettingForCreate, atlases: &[AtlasDef]) {
+        let common = Tissue::common();
         self.n_measure = 0;
         self.n_tissue = 0;
         self.n_cell = 0;
@@ -341,13 +112,13 @@ impl FullLabelData {
         let mut atlas = HashMap::new();
         let mut cell = HashMap::new();
         for (a, b) in &self.label {
-            if a.to_string().ends_with("Atlas") {
+            if a.kind == AtlasKind::Tissue {
                 for tissue in b {
                     let n_per_label = 6 / b.len() as i32;
                     for _ in 0..n_per_label {
                         self.tissue_entropy_labels.push(tissue.to_string());
                     }
-                    if tissue != &Tissue::Common {
+                    if tissue != &common {
                         let tval = match atlas.entry(tissue) {
                             Entry::Occupied(o) => o.into_mut(),
                             Entry::Vacant(v) => v.insert(ScoreCount { n: 0, score: 0.0 }),
@@ -364,13 +135,13 @@ impl FullLabelData {
                     }
                 }
                 self.n_tissue += 1;
-            } else if a.to_string().ends_with("Cells") {
+            } else {
                 for vlue in b {
                     let n_per_label = 6 / b.len() as i32;
                     for _ in 0..n_per_label {
                         self.cell_entropy_labels.push(vlue.to_string());
                     }
-                    if vlue != &Tissue::Common {
+                    if vlue != &common {
                         let tval = match cell.entry(vlue) {
                             Entry::Occupied(o) => o.into_mut(),
                             Entry::Vacant(v) => v.insert(ScoreCount { n: 0, score: 0.0 }),
@@ -388,80 +159,65 @@ impl FullLabelData {
                 }
                 self.n_cell += 1;
             }
-            if b.contains(&Tissue::Common) {
+            if b.contains(&common) {
                 self.n_common += 1;
             }
             self.n_measure += 1;
         }
-        while self.cell_entropy_labels.len() < 12 {
+        let n_slots = |kind: AtlasKind| 6 * atlases.iter().filter(|e| e.kind == kind).count();
+        while self.cell_entropy_labels.len() < n_slots(AtlasKind::Cell) {
             self.cell_entropy_labels.push(String::from("missing"));
         }
-        while self.tissue_entropy_labels.len() < 24 {
+        while self.tissue_entropy_labels.len() < n_slots(AtlasKind::Tissue) {
             self.tissue_entropy_labels.push(String::from("missing"));
         }
         for (k, v) in atlas {
-            self.tissue.insert(*k, v);
+            self.tissue.insert(k.clone(), v);
         }
         for (k, v) in cell {
-            self.cell.insert(*k, v);
+            self.cell.insert(k.clone(), v);
         }
     }
     pub fn create_labels(&mut self, settings: &ScoreSettingForCreate) {
+        let common = Tissue::common();
         self.cummulative_cell_score = 0.0;
         self.cummulative_atlas_score = 0.0;
         let mut tissue_labels = HashSet::new();
         for (k, v) in &self.tissue {
-            if v.score > self.max_atlas_score * settings.delta_to_include && k != &Tissue::Common {
+            if v.score > self.max_atlas_score * settings.delta_to_include && k != &common {
                 tissue_labels.insert(k);
                 self.cummulative_atlas_score += v.score;
             }
         }
         if tissue_labels.len() > settings.max_labels as usize {
-            self.tissue_label = HashSet::from([Tissue::Common]);
+            self.tissue_label = HashSet::from([common.clone()]);
         } else {
             for k in tissue_labels {
-                self.tissue_label.insert(*k);
+                self.tissue_label.insert(k.clone());
             }
         }
         let mut cell_labels = HashSet::new();
         for (k, v) in &self.cell {
-            if v.score > self.max_cell_score * settings.delta_to_include && k != &Tissue::Common {
+            if v.score > self.max_cell_score * settings.delta_to_include && k != &common {
                 cell_labels.insert(k);
                 self.cummulative_cell_score += v.score;
             }
         }
         if cell_labels.len() > settings.max_labels as usize {
-            self.cell_label = HashSet::from([Tissue::Common]);
+            self.cell_label = HashSet::from([common.clone()]);
         } else {
             for k in cell_labels {
-                self.cell_label.insert(*k);
+                self.cell_label.insert(k.clone());
             }
         }
         self.tissue_label_string = format_label(&self.tissue_label).unwrap();
         self.cell_label_string = format_label(&self.cell_label).unwrap();
-        if self.cell_entropy_label.is_empty() || self.cell_entropy_label == "missing" {
-            self.cell_entropy = 100.0;
-        } else {
-            self.cell_entropy = 0.0;
-        }
-        if self.tissue_entropy_label.is_empty() || self.tissue_entropy_label == "missing" {
-            self.tissue_entropy = 100.0;
-        } else {
-            self.tissue_entropy = 0.0;
-        }
-        if self.global_label_string == "plasma" || self.global_label_string == "common" {
-            self.entropy_label = self.global_label_string.clone();
-        } else if self.tissue_entropy <= self.cell_entropy {
-            self.entropy_label = self.tissue_entropy_label.clone();
-        } else {
-            self.entropy_label = self.cell_entropy_label.clone();
-        }
     }
     pub fn create_global_label(&mut self, settings: &ScoreSettingForCreate) {
         if self.is_plasma() {
-            self.global_label = HashSet::from([Tissue::Plasma]);
+            self.global_label = HashSet::from([Tissue::plasma()]);
         } else if self.n_common > settings.common_threashold {
-            self.global_label = HashSet::from([Tissue::Common]);
+            self.global_label = HashSet::from([Tissue::common()]);
         } else if self.cummulative_atlas_score
             > self.cummulative_cell_score * settings.cell_atlas_weight
         {
@@ -471,19 +227,45 @@ impl FullLabelData {
         }
         self.global_label_string = format_label(&self.global_label).unwrap();
     }
+    /// Shannon entropy (bits) of the padded label vectors filled by `count`;
+    /// needs the global label, so it runs after `create_global_label`.
+    pub fn create_entropy_labels(&mut self) {
+        (self.tissue_entropy, self.tissue_entropy_label) = entropy(&self.tissue_entropy_labels);
+        (self.cell_entropy, self.cell_entropy_label) = entropy(&self.cell_entropy_labels);
+        if self.global_label_string == "plasma" || self.global_label_string == "common" {
+            self.entropy_label = self.global_label_string.clone();
+        } else if self.tissue_entropy <= self.cell_entropy {
+            self.entropy_label = self.tissue_entropy_label.clone();
+        } else {
+            self.entropy_label = self.cell_entropy_label.clone();
+        }
+    }
+
+    /// `confidence` maps tissue names to the share of bootstrap replicates
+    /// assigning them to this protein; tissues never assigned count as 0.
+    pub fn create_label_confidence(&mut self, confidence: Option<&HashMap<String, f64>>) {
+        let Some(confidence) = confidence else {
+            return;
+        };
+        let sum: f64 = self
+            .global_label
+            .iter()
+            .map(|e| confidence.get(e.name()).copied().unwrap_or(0.0))
+            .sum();
+        self.label_confidence = sum / self.global_label.len().max(1) as f64;
+    }
 
     fn is_plasma(&self) -> bool {
+        let liver = Tissue::new("liver");
         let mut n_protein_liver = 0;
         let mut n_rna_liver = 0;
         for (ds, lbl) in &self.label {
-            if (ds == &DataSet::HaAtlas || ds == &DataSet::MspAtlas) && lbl.contains(&Tissue::Liver)
-            {
-                n_protein_liver += 1;
+            if ds.kind != AtlasKind::Tissue || !lbl.contains(&liver) {
+                continue;
             }
-            if (ds == &DataSet::EmblAtlas || ds == &DataSet::MsrAtlas)
-                && lbl.contains(&Tissue::Liver)
-            {
-                n_rna_liver += 1;
+            match ds.modality {
+                Modality::Protein => n_protein_liver += 1,
+                Modality::Rna => n_rna_liver += 1,
             }
         }
         if n_protein_liver == 0 && n_rna_liver >= 2 {
@@ -531,6 +313,7 @@ impl Default for FullLabelData {
             cell_label_string: String::new(),
             tissue_label_string: String::new(),
             global_label_string: String::new(),
+            label_confidence: -1.0,
             cell_entropy: -1.0,
             cell_entropy_label: String::new(),
             cell_entropy_labels: vec![],
@@ -554,6 +337,7 @@ pub struct LabelDataForCreate {
     pub description: String,
     pub entropy_label: String,
     pub global_label_string: String,
+    pub label_confidence: f64,
     pub max_atlas_score: f64,
     pub max_cell_score: f64,
     pub n_cell: i32,
@@ -565,6 +349,55 @@ pub struct LabelDataForCreate {
     pub tissue_label_string: String,
 }
 
+impl CopyRow for LabelDataForCreate {
+    const COLUMNS: &'static [&'static str] = &[
+        "ac",
+        "cell_entropy",
+        "cell_entropy_label",
+        "cell_label_string",
+        "cummulative_atlas_score",
+        "cummulative_cell_score",
+        "description",
+        "entropy_label",
+        "global_label_string",
+        "label_confidence",
+        "max_atlas_score",
+        "max_cell_score",
+        "n_cell",
+        "n_common",
+        "n_measure",
+        "n_tissue",
+        "score_setting_id",
+        "tissue_entropy",
+        "tissue_entropy_label",
+        "tissue_label_string",
+    ];
+    fn copy_fields(&self) -> Vec<&dyn CopyField> {
+        vec![
+            &self.ac,
+            &self.cell_entropy,
+            &self.cell_entropy_label,
+            &self.cell_label_string,
+            &self.cummulative_atlas_score,
+            &self.cummulative_cell_score,
+            &self.description,
+            &self.entropy_label,
+            &self.global_label_string,
+            &self.label_confidence,
+            &self.max_atlas_score,
+            &self.max_cell_score,
+            &self.n_cell,
+            &self.n_common,
+            &self.n_measure,
+            &self.n_tissue,
+            &self.score_setting_id,
+            &self.tissue_entropy,
+            &self.tissue_entropy_label,
+            &self.tissue_label_string,
+        ]
+    }
+}
+
 impl From<FullLabelData> for LabelDataForCreate {
     fn from(item: FullLabelData) -> Self {
         Self {
@@ -585,6 +418,7 @@ impl From<FullLabelData> for LabelDataForCreate {
             tissue_entropy: f64::from(item.tissue_entropy),
             tissue_label_string: item.tissue_label_string,
             global_label_string: item.global_label_string,
+            label_confidence: item.label_confidence,
             description: item.description,
             tissue_entropy_label: item.tissue_entropy_label,
         }
@@ -593,46 +427,19 @@ impl From<FullLabelData> for LabelDataForCreate {
 
 impl LabelDataBmc {
     pub async fn bulk_import(mm: &ModelManager, entries: Vec<LabelDataForCreate>) -> Result<()> {
-        let mut tx = mm.db().begin().await?;
-        for entry in entries {
-            let _result = sqlx::query(&format!(
-            "INSERT INTO {} (ac,cell_entropy,cell_entropy_label,cell_label_string,cummulative_atlas_score,cummulative_cell_score,description,entropy_label,global_label_string,max_atlas_score,max_cell_score,n_cell,n_common,n_measure,n_tissue,score_setting_id,tissue_entropy,tissue_entropy_label,tissue_label_string)
-    VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13,$14,$15,$16,$17,$18,$19)",
-            Self::TABLE
-        ))
-        .bind(entry.ac)
-        .bind(entry.cell_entropy)
-        .bind(entry.cell_entropy_label)
-        .bind(entry.cell_label_string)
-        .bind(entry.cummulative_atlas_score)
-        .bind(entry.cummulative_cell_score)
-        .bind(entry.description)
-        .bind(entry.entropy_label)
-        .bind(entry.global_label_string)
-        .bind(entry.max_atlas_score)
-        .bind(entry.max_cell_score)
-        .bind(entry.n_cell)
-        .bind(entry.n_common)
-        .bind(entry.n_measure)
-        .bind(entry.n_tissue)
-        .bind(entry.score_setting_id)
-        .bind(entry.tissue_entropy)
-        .bind(entry.tissue_entropy_label)
-        .bind(entry.tissue_label_string)
-        .execute(&mut *tx)
-        .await;
-        }
-        tx.commit().await?;
+        bulk::copy_in(mm, Self::TABLE, &entries).await?;
         Ok(())
     }
 
     #[must_use]
-    pub fn get_create_sql(drop_table: bool) -> String {
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
         let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
         format!(
             r##"{}
 create table if not exists {table} (
-  id serial primary key,
+  id {pk},
   score_setting_id integer not null,
   ac character varying not null,
   cell_entropy float not null,
@@ -643,6 +450,7 @@ create table if not exists {table} (
   description character varying not null,
   entropy_label character varying not null,
   global_label_string character varying not null,
+  label_confidence float not null,
   max_atlas_score float not null,
   max_cell_score float not null,
   n_cell integer not null,
@@ -653,8 +461,8 @@ create table if not exists {table} (
   tissue_entropy_label character varying not null,
   tissue_label_string character varying not null
 );
-create index if not exists "IDX_{table}_ac" ON {table} {BTREE} (ac);
-create index if not exists "IDX_{table}_score_setting_id" ON {table} {BTREE} (score_setting_id);
+create index if not exists "IDX_{table}_ac" ON {table} {btree} (ac);
+create index if not exists "IDX_{table}_score_setting_id" ON {table} {btree} (score_setting_id);
         "##,
             match drop_table {
                 true => format!("drop table if exists {table};"),
@@ -677,6 +485,7 @@ pub struct LabelData {
     pub description: String,
     pub entropy_label: String,
     pub global_label_string: String,
+    pub label_confidence: f64,
     pub max_atlas_score: f64,
     pub max_cell_score: f64,
     pub n_cell: i32,
@@ -699,6 +508,7 @@ pub struct LabelDataForUpdate {
     pub description: Option<String>,
     pub entropy_label: Option<String>,
     pub global_label_string: Option<String>,
+    pub label_confidence: Option<f64>,
     pub max_atlas_score: Option<f64>,
     pub max_cell_score: Option<f64>,
     pub n_cell: Option<i32>,
@@ -722,6 +532,7 @@ pub struct LabelDataFilter {
     description: Option<OpValsString>,
     entropy_label: Option<OpValsString>,
     global_label_string: Option<OpValsString>,
+    label_confidence: Option<OpValsFloat64>,
     max_atlas_score: Option<OpValsFloat64>,
     max_cell_score: Option<OpValsFloat64>,
     n_cell: Option<OpValsInt64>,
@@ -1,10 +1,24 @@
 pub mod ac;
 pub mod ann;
+pub mod atlas;
+pub mod benchmark;
+pub mod confusion;
 pub mod glsn;
+pub mod impute;
+pub mod labelchange;
+pub mod labelconfidence;
 pub mod labeldata;
+pub mod normalize;
+pub mod normfactor;
 pub mod qm;
 pub mod qmatrix;
+pub mod reference;
+pub mod resolve;
+pub mod scaling;
 pub mod scoresetting;
+pub mod stepstate;
+pub mod tissue;
+pub mod unresolvedid;
 pub mod variance;
 pub mod wkdelabel;
 pub mod wkdetag;
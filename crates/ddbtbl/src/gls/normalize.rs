@@ -0,0 +1,244 @@
+//! Between-sample normalisation of a quantitative matrix. Every method yields
+//! a factor per sample that the values of the sample are multiplied with;
+//! quantile normalisation replaces the measured values and reports the ratio
+//! of the sample means as its factor.
+//!
+//! Every factor brings a sample statistic to its mean over the samples
+//! (`mean / stat`, `mean / sum` for total-sum), so the `normfactor` rows of
+//! all methods can be compared. Total-sum values are still divided by their
+//! factor, as in the original pipeline, which widens rather than evens out
+//! the differences between the sample sums; this keeps the default
+//! reproducing the published results (see [`Normalization::apply`]).
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Measured values by `ann_id` and `ac_id`.
+pub type Samples = BTreeMap<i32, BTreeMap<i32, f64>>;
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Normalization {
+    /// Brings the sample sums, on the input scale, to their mean; applied
+    /// inverted as in the original pipeline.
+    #[default]
+    TotalSum,
+    /// Brings the sample medians to their mean.
+    Median,
+    /// Brings the sample 75th percentiles to their mean.
+    UpperQuartile,
+    /// Gives every sample the mean sorted value distribution.
+    Quantile,
+    /// Trimmed mean of M-values against the sample closest to the mean upper
+    /// quartile.
+    Tmm,
+}
+
+impl Normalization {
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::TotalSum => "total_sum",
+            Self::Median => "median",
+            Self::UpperQuartile => "upper_quartile",
+            Self::Quantile => "quantile",
+            Self::Tmm => "tmm",
+        }
+    }
+
+    /// Normalised `value` of a sample with `factor`. Total-sum divides by the
+    /// factor, i.e. multiplies by `sum / mean` like the original pipeline;
+    /// the other methods multiply.
+    #[must_use]
+    pub fn apply(self, value: f64, factor: f64) -> f64 {
+        match self {
+            Self::TotalSum => value / factor,
+            _ => value * factor,
+        }
+    }
+
+    /// Factor per sample of `samples`; for quantile normalisation the ratio
+    /// of the normalised to the measured sample mean.
+    #[must_use]
+    pub fn factors(self, samples: &Samples) -> HashMap<i32, f64> {
+        match self {
+            Self::TotalSum => total_sum(
+                &samples
+                    .iter()
+                    .map(|(ann, values)| (*ann, values.values().sum()))
+                    .collect(),
+            ),
+            Self::Median => to_mean(samples, |values| percentile(values, 0.5)),
+            Self::UpperQuartile => to_mean(samples, |values| percentile(values, 0.75)),
+            Self::Quantile => mean_ratio(samples, &quantile(samples)),
+            Self::Tmm => tmm(samples),
+        }
+    }
+}
+
+/// Linear interpolation between the closest ranks of the sorted `values`.
+fn percentile(values: &[f64], p: f64) -> f64 {
+    let Some(last) = values.len().checked_sub(1) else {
+        return f64::NAN;
+    };
+    let h = last as f64 * p;
+    let lo = h.floor() as usize;
+    let hi = (lo + 1).min(last);
+    values[lo] + (h - lo as f64) * (values[hi] - values[lo])
+}
+
+fn sorted(values: &BTreeMap<i32, f64>) -> Vec<f64> {
+    let mut values: Vec<f64> = values.values().copied().collect();
+    values.sort_by(f64::total_cmp);
+    values
+}
+
+/// Factors `mean / sum` from the per-sample sums `sums`.
+#[must_use]
+pub fn total_sum(sums: &HashMap<i32, f64>) -> HashMap<i32, f64> {
+    let mean = sums.values().sum::<f64>() / sums.len() as f64;
+    sums.iter().map(|(ann, sum)| (*ann, mean / sum)).collect()
+}
+
+/// Factors bringing the statistic `stat` of every sample to its mean.
+fn to_mean(samples: &Samples, stat: impl Fn(&[f64]) -> f64) -> HashMap<i32, f64> {
+    let stats: Vec<(i32, f64)> = samples
+        .iter()
+        .map(|(ann, values)| (*ann, stat(&sorted(values))))
+        .filter(|e| e.1 > 0.0)
+        .collect();
+    let mean = stats.iter().map(|e| e.1).sum::<f64>() / stats.len() as f64;
+    stats.into_iter().map(|(ann, v)| (ann, mean / v)).collect()
+}
+
+/// Quantile normalisation. Samples with fewer values are mapped onto the
+/// interpolated mean distribution by relative rank.
+#[must_use]
+pub fn quantile(samples: &Samples) -> Samples {
+    let n = samples.values().map(BTreeMap::len).max().unwrap_or(0);
+    let at = |i: usize| match n {
+        0 | 1 => 0.0,
+        _ => i as f64 / (n - 1) as f64,
+    };
+    let sorted_samples: Vec<Vec<f64>> = samples.values().map(sorted).collect();
+    let reference: Vec<f64> = (0..n)
+        .map(|i| {
+            sorted_samples
+                .iter()
+                .map(|values| percentile(values, at(i)))
+                .sum::<f64>()
+                / sorted_samples.len() as f64
+        })
+        .collect();
+    samples
+        .iter()
+        .map(|(ann, values)| {
+            let mut ranked: Vec<(i32, f64)> = values.iter().map(|(ac, v)| (*ac, *v)).collect();
+            ranked.sort_by(|a, b| a.1.total_cmp(&b.1));
+            let last = ranked.len().saturating_sub(1).max(1) as f64;
+            let normalized = ranked
+                .iter()
+                .enumerate()
+                .map(|(i, (ac, _))| (*ac, percentile(&reference, i as f64 / last)))
+                .collect();
+            (*ann, normalized)
+        })
+        .collect()
+}
+
+/// Ratio of the mean of every sample in `normalized` to that in `samples`.
+#[must_use]
+pub fn mean_ratio(samples: &Samples, normalized: &Samples) -> HashMap<i32, f64> {
+    let mean = |values: &BTreeMap<i32, f64>| values.values().sum::<f64>() / values.len() as f64;
+    samples
+        .iter()
+        .filter_map(|(ann, values)| Some((*ann, mean(normalized.get(ann)?) / mean(values))))
+        .collect()
+}
+
+/// Keeps the entries whose rank in `values` lies within the trimmed range.
+fn trimmed(values: &[f64], trim: f64) -> Vec<bool> {
+    let n = values.len();
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|a, b| values[*a].total_cmp(&values[*b]));
+    let lo = (n as f64 * trim).floor() as usize;
+    let mut keep = vec![false; n];
+    for (rank, i) in order.into_iter().enumerate() {
+        keep[i] = rank >= lo && rank < n - lo;
+    }
+    keep
+}
+
+/// TMM factor of `sample` against `reference`, 1 without shared values.
+fn tmm_factor(sample: &BTreeMap<i32, f64>, reference: &BTreeMap<i32, f64>) -> f64 {
+    let n_s: f64 = sample.values().sum();
+    let n_r: f64 = reference.values().sum();
+    let mut m = vec![];
+    let mut a = vec![];
+    let mut w = vec![];
+    for (ac, y_s) in sample {
+        let Some(y_r) = reference.get(ac) else {
+            continue;
+        };
+        if *y_s <= 0.0 || *y_r <= 0.0 {
+            continue;
+        }
+        let (p_s, p_r) = (y_s / n_s, y_r / n_r);
+        m.push((p_s / p_r).log2());
+        a.push(0.5 * (p_s * p_r).log2());
+        w.push(1.0 / ((n_s - y_s) / (n_s * y_s) + (n_r - y_r) / (n_r * y_r)));
+    }
+    let (keep_m, keep_a) = (trimmed(&m, 0.3), trimmed(&a, 0.05));
+    let (mut sum, mut weight) = (0.0, 0.0);
+    for i in 0..m.len() {
+        if keep_m[i] && keep_a[i] && w[i].is_finite() {
+            sum += w[i] * m[i];
+            weight += w[i];
+        }
+    }
+    match weight > 0.0 {
+        true => (sum / weight).exp2(),
+        false => 1.0,
+    }
+}
+
+fn tmm(samples: &Samples) -> HashMap<i32, f64> {
+    let upper: Vec<(i32, f64)> = samples
+        .iter()
+        .map(|(ann, values)| {
+            let total: f64 = values.values().sum();
+            let mut scaled: Vec<f64> = values.values().map(|v| v / total).collect();
+            scaled.sort_by(f64::total_cmp);
+            (*ann, percentile(&scaled, 0.75))
+        })
+        .collect();
+    let mean_upper = upper.iter().map(|e| e.1).sum::<f64>() / upper.len() as f64;
+    let Some(reference) = upper
+        .iter()
+        .min_by(|a, b| {
+            (a.1 - mean_upper)
+                .abs()
+                .total_cmp(&(b.1 - mean_upper).abs())
+        })
+        .and_then(|e| samples.get(&e.0))
+    else {
+        return HashMap::new();
+    };
+    let tmm: Vec<(i32, f64)> = samples
+        .iter()
+        .map(|(ann, values)| (*ann, tmm_factor(values, reference)))
+        .collect();
+    let log_mean = tmm.iter().map(|e| e.1.log2()).sum::<f64>() / tmm.len() as f64;
+    let effective: Vec<(i32, f64)> = tmm
+        .into_iter()
+        .map(|(ann, f)| {
+            let total: f64 = samples[&ann].values().sum();
+            (ann, total * f / log_mean.exp2())
+        })
+        .collect();
+    let mean = effective.iter().map(|e| e.1).sum::<f64>() / effective.len() as f64;
+    effective
+        .into_iter()
+        .map(|(ann, size)| (ann, mean / size))
+        .collect()
+}
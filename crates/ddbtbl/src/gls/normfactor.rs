@@ -0,0 +1,121 @@
+use crate::bulk::{self, CopyField, CopyRow};
+use crate::dialect::Dialect;
+use iwf::ctx::Ctx;
+use iwf::sql::base::{self, DbBmc};
+use iwf::sql::ModelManager;
+use iwf::sql::Result;
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, OpValsFloat64, OpValsInt64, OpValsString};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tabled::Tabled;
+
+/// Normalisation factor of sample `ann_id` in qmatrix `qmatrix_id`.
+#[derive(Fields, Deserialize, Clone, Debug)]
+pub struct NormFactorForCreate {
+    pub qmatrix_id: i32,
+    pub ann_id: i32,
+    pub method: String,
+    pub factor: f64,
+}
+
+impl CopyRow for NormFactorForCreate {
+    const COLUMNS: &'static [&'static str] = &["qmatrix_id", "ann_id", "method", "factor"];
+    fn copy_fields(&self) -> Vec<&dyn CopyField> {
+        vec![&self.qmatrix_id, &self.ann_id, &self.method, &self.factor]
+    }
+}
+
+impl NormFactorBmc {
+    pub async fn bulk_import(mm: &ModelManager, entries: Vec<NormFactorForCreate>) -> Result<()> {
+        bulk::copy_in(mm, Self::TABLE, &entries).await?;
+        Ok(())
+    }
+    #[must_use]
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
+        let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
+        format!(
+            r##"{}
+create table if not exists {table} (
+  id {pk},
+  qmatrix_id integer not null,
+  ann_id integer not null,
+  method character varying not null,
+  factor float not null
+);
+
+create index if not exists "IDX_{table}_qmatrix_id" ON {table} {btree} (qmatrix_id);
+
+        "##,
+            match drop_table {
+                true => format!("drop table if exists {table};"),
+                false => String::new(),
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Fields, FromRow, Serialize, Deserialize, Default, Tabled)]
+pub struct NormFactor {
+    pub id: i32,
+    pub qmatrix_id: i32,
+    pub ann_id: i32,
+    pub method: String,
+    pub factor: f64,
+}
+
+#[derive(Fields, Default, Deserialize, Debug)]
+pub struct NormFactorForUpdate {
+    pub qmatrix_id: Option<i32>,
+    pub ann_id: Option<i32>,
+    pub method: Option<String>,
+    pub factor: Option<f64>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct NormFactorFilter {
+    id: Option<OpValsInt64>,
+    qmatrix_id: Option<OpValsInt64>,
+    ann_id: Option<OpValsInt64>,
+    method: Option<OpValsString>,
+    factor: Option<OpValsFloat64>,
+}
+
+pub struct NormFactorBmc;
+
+impl DbBmc for NormFactorBmc {
+    const TABLE: &'static str = "normfactor";
+}
+
+impl NormFactorBmc {
+    pub async fn create(ctx: &Ctx, mm: &ModelManager, clone_c: NormFactorForCreate) -> Result<i32> {
+        base::create::<Self, _>(ctx, mm, clone_c).await
+    }
+    pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<NormFactor> {
+        base::get::<Self, _>(ctx, mm, id).await
+    }
+
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<NormFactorFilter>>,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<NormFactor>> {
+        base::list::<Self, _, _>(ctx, mm, filters, list_options).await
+    }
+
+    pub async fn update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i32,
+        clone_u: NormFactorForUpdate,
+    ) -> Result<()> {
+        base::update::<Self, _>(ctx, mm, id, clone_u).await
+    }
+
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<()> {
+        base::delete::<Self>(ctx, mm, id).await
+    }
+}
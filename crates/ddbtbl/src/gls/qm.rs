@@ -1,16 +1,23 @@
-use crate::gls::ac::{Ac, AcBmc};
+use crate::bulk::{self, CopyField, CopyRow};
+use crate::dialect::Dialect;
 use crate::gls::ann::{Ann, AnnBmc, AnnFilter};
+use crate::gls::atlas::AtlasBmc;
+use crate::gls::impute::{Imputation, Observed};
+use crate::gls::normalize::{self, Normalization, Samples};
+use crate::gls::normfactor::{NormFactorBmc, NormFactorForCreate};
 use crate::gls::qmatrix::{QmatrixBmc, QmatrixForCreate};
+use crate::gls::resolve::{IdResolver, Unresolved};
+use crate::gls::scaling::Scaling;
+use crate::gls::unresolvedid::{UnresolvedIdBmc, UnresolvedIdForCreate, PROTEIN, SAMPLE};
+use crate::query;
 use camino::Utf8PathBuf;
 use iwf::ctx::Ctx;
-use iwf::sql::base::{self, DbBmc, BTREE};
+use iwf::sql::base::{self, DbBmc};
 use iwf::sql::ModelManager;
 use iwf::sql::Result;
 use log::{debug, warn};
 use modql::field::Fields;
 use modql::filter::{FilterNodes, ListOptions, OpValsInt64};
-use rand::seq::SliceRandom;
-use rand::thread_rng;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use sqlx::FromRow;
@@ -25,9 +32,44 @@ pub struct QmForCreate {
     pub value: f64,
     pub norm_value: f64,
     pub scaled_value: f64,
+    pub share_value: f64,
     pub measured: bool,
 }
 
+impl CopyRow for QmForCreate {
+    const COLUMNS: &'static [&'static str] = &[
+        "qmatrix_id",
+        "ac_id",
+        "ann_id",
+        "value",
+        "norm_value",
+        "scaled_value",
+        "share_value",
+        "measured",
+    ];
+    fn copy_fields(&self) -> Vec<&dyn CopyField> {
+        vec![
+            &self.qmatrix_id,
+            &self.ac_id,
+            &self.ann_id,
+            &self.value,
+            &self.norm_value,
+            &self.scaled_value,
+            &self.share_value,
+            &self.measured,
+        ]
+    }
+}
+
+/// How the cells of a qmatrix are completed, normalised between samples and
+/// scaled per protein.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct QmProcessing {
+    pub imputation: Imputation,
+    pub normalization: Normalization,
+    pub scaling: Scaling,
+}
+
 #[derive(Deserialize, Clone, Debug)]
 pub struct QmParse {
     #[serde(alias = "id", alias = "gene.id")]
@@ -36,27 +78,17 @@ pub struct QmParse {
     pub value: f64,
 }
 
-fn get_ac(protein: &str, acm: &HashMap<String, Ac>, acfm: &HashMap<String, Ac>) -> Option<Ac> {
-    match acm.get(protein) {
-        Some(s) => Some(s.clone()),
-        None => acfm.get(protein).cloned(),
-    }
-}
-
 fn read_and_normalize(
     input_path: &Utf8PathBuf,
     annm: &HashMap<String, Ann>,
-    acm: &HashMap<String, Ac>,
-    acfm: &HashMap<String, Ac>,
+    resolver: &IdResolver,
     not_found_samples: &mut HashSet<String>,
-    not_found_proteins: &mut HashSet<String>,
+    not_found_proteins: &mut HashMap<String, Unresolved>,
     qmatrix_id: i32,
     ex: bool,
-) -> Result<Vec<QmForCreate>> {
-    let mut all_values = vec![];
-    let mut present = HashSet::new();
-    let mut present_acs = HashSet::new();
-    let mut present_anns = HashSet::new();
+    processing: &QmProcessing,
+) -> Result<(Vec<QmForCreate>, HashMap<i32, f64>)> {
+    let mut observed = Observed::default();
     let mut norm_data: HashMap<i32, f64> = HashMap::new();
 
     let mut ret = vec![];
@@ -73,172 +105,206 @@ fn read_and_normalize(
             }
             Some(ann) => {
                 let ann_id = ann.id;
-                match get_ac(&parsed.protein, acm, acfm) {
-                    None => {
-                        not_found_proteins.insert(parsed.protein);
-                        ();
+                match resolver.resolve(&parsed.protein) {
+                    Err(reason) => {
+                        not_found_proteins.insert(parsed.protein, reason);
                     }
-                    Some(s) => {
-                        if parsed.value > 0.0 {
-                            let value = match ex {
-                                false => parsed.value,
-                                true => f64::exp2(parsed.value),
-                            };
-                            let record = QmForCreate {
-                                qmatrix_id,
-                                ac_id: s.id,
-                                ann_id,
-                                value,
-                                norm_value: 0.,
-                                scaled_value: 0.,
-                                measured: true,
-                            };
-                            ret.push(record);
-                            all_values.push(parsed.value);
-                            present_acs.insert(s.id);
-                            present_anns.insert(ann_id);
-                            present.insert((s.id, ann_id));
-                            norm_data
-                                .entry(ann_id)
-                                .and_modify(|curr| *curr += parsed.value)
-                                .or_insert(parsed.value);
-                        }
+                    Ok(_) if parsed.value <= 0.0 => {}
+                    Ok(s) if observed.get(s.id, ann_id).is_some() => {
+                        not_found_proteins.insert(parsed.protein, Unresolved::Duplicate);
+                    }
+                    Ok(s) => {
+                        let value = match ex {
+                            false => parsed.value,
+                            true => f64::exp2(parsed.value),
+                        };
+                        let record = QmForCreate {
+                            qmatrix_id,
+                            ac_id: s.id,
+                            ann_id,
+                            value,
+                            norm_value: 0.,
+                            scaled_value: 0.,
+                            share_value: 0.,
+                            measured: true,
+                        };
+                        ret.push(record);
+                        observed.insert(s.id, ann_id, parsed.value);
+                        norm_data
+                            .entry(ann_id)
+                            .and_modify(|curr| *curr += parsed.value)
+                            .or_insert(parsed.value);
                     }
                 }
             }
         }
     }
-    normalize_reads(
+    impute(
         &mut ret,
-        &present,
-        &present_acs,
-        &present_anns,
-        &mut all_values,
+        &observed,
+        &processing.imputation,
         qmatrix_id,
+        ex,
         &mut norm_data,
-    )?;
+    );
+    let factors = normalize_reads(&mut ret, &norm_data, processing.normalization);
 
-    Ok(ret)
+    Ok((ret, factors))
 }
 
-fn normalize_reads(
+/// Adds the imputed cells of `observed` to `ret` and to the per-sample sums.
+/// Imputation works on the input scale like `observed`; `ex` exponentiates
+/// the stored value as for the measured cells, except for the legacy
+/// imputation, which keeps the drawn value.
+fn impute(
     ret: &mut Vec<QmForCreate>,
-    present: &HashSet<(i32, i32)>,
-    present_acs: &HashSet<i32>,
-    present_anns: &HashSet<i32>,
-    all_values: &mut Vec<f64>,
+    observed: &Observed,
+    imputation: &Imputation,
     qmatrix_id: i32,
+    ex: bool,
     norm_data: &mut HashMap<i32, f64>,
-) -> Result<()> {
-    all_values.sort_by(|a, b| a.partial_cmp(b).unwrap());
-    let one_percent_count = (all_values.len() as f64 * 0.01).ceil() as usize;
-    let first_one_percent = &all_values[..one_percent_count];
-    let mut n_have = 0;
-    let mut n_missing = 0;
-    let mut rng = thread_rng();
-    for ann in present_anns {
-        for ac in present_acs {
-            if present.contains(&(*ac, *ann)) {
-                n_have += 1;
-                continue;
-            }
-            n_missing += 1;
-            let value = *first_one_percent.choose(&mut rng).unwrap();
-            let record = QmForCreate {
-                qmatrix_id,
-                ac_id: *ac,
-                ann_id: *ann,
-                value,
-                norm_value: 0.,
-                scaled_value: 0.,
-                measured: false,
-            };
-            norm_data
-                .entry(*ann)
-                .and_modify(|curr| *curr += value)
-                .or_insert(value);
-            ret.push(record);
+) {
+    let imputed = imputation.run(observed);
+    debug!(
+        "distr {} {} {}",
+        ret.len(),
+        observed.n_missing(),
+        imputed.len()
+    );
+    for (ac_id, ann_id, value) in imputed {
+        norm_data
+            .entry(ann_id)
+            .and_modify(|curr| *curr += value)
+            .or_insert(value);
+        ret.push(QmForCreate {
+            qmatrix_id,
+            ac_id,
+            ann_id,
+            value: match ex && !imputation.legacy {
+                false => value,
+                true => f64::exp2(value),
+            },
+            norm_value: 0.,
+            scaled_value: 0.,
+            share_value: 0.,
+            measured: false,
+        });
+    }
+}
+
+/// Sets the normalised values of `ret` and returns the factor per sample.
+/// Total-sum factors come from the input-scale sums in `norm_data`; the
+/// other methods look at the stored values of the measured cells only.
+fn normalize_reads(
+    ret: &mut [QmForCreate],
+    norm_data: &HashMap<i32, f64>,
+    normalization: Normalization,
+) -> HashMap<i32, f64> {
+    let mut samples = Samples::new();
+    if normalization != Normalization::TotalSum {
+        for r in ret.iter().filter(|e| e.measured) {
+            samples
+                .entry(r.ann_id)
+                .or_default()
+                .insert(r.ac_id, r.value);
         }
     }
-    debug!("distr {} {}", n_have, n_missing);
-    let norm_sum = norm_data.values().sum::<f64>() / (norm_data.len() as f64);
-    let mut norm_factors = HashMap::new();
-    for (k, v) in norm_data {
-        let factor = *v / norm_sum;
-        debug!("Factor: {k} {v} {factor}");
-        norm_factors.insert(k, factor);
+    let (norm_factors, normalized) = match normalization {
+        Normalization::TotalSum => (normalize::total_sum(norm_data), None),
+        Normalization::Quantile => {
+            let normalized = normalize::quantile(&samples);
+            (
+                normalize::mean_ratio(&samples, &normalized),
+                Some(normalized),
+            )
+        }
+        _ => (normalization.factors(&samples), None),
+    };
+    for (k, factor) in &norm_factors {
+        debug!("Factor: {} {k} {factor}", normalization.name());
     }
     for r in ret.iter_mut() {
-        let nvalue = r.value * *norm_factors.get(&r.ann_id).unwrap();
-        r.norm_value = nvalue;
+        let factor = norm_factors.get(&r.ann_id).copied().unwrap_or(1.0);
+        r.norm_value = match (&normalized, r.measured) {
+            (Some(normalized), true) => normalized[&r.ann_id][&r.ac_id],
+            _ => normalization.apply(r.value, factor),
+        };
     }
+    norm_factors
+}
 
-    Ok(())
+/// Records the normalisation factors of qmatrix `qmatrix_id`.
+async fn store_factors(
+    mm: &ModelManager,
+    qmatrix_id: i32,
+    normalization: Normalization,
+    factors: HashMap<i32, f64>,
+) -> Result<()> {
+    let entries = factors
+        .into_iter()
+        .map(|(ann_id, factor)| NormFactorForCreate {
+            qmatrix_id,
+            ann_id,
+            method: normalization.name().into(),
+            factor,
+        })
+        .collect();
+    NormFactorBmc::bulk_import(mm, entries).await
 }
 
-fn scale(ret: &mut Vec<QmForCreate>, impute: bool) -> Result<()> {
-    let mut max_scale_data: HashMap<i32, f64> = HashMap::new();
-    let mut min_scale_data: HashMap<i32, f64> = HashMap::new();
-    let mut sum_data: HashMap<i32, f64> = HashMap::new();
-    for r in ret.iter() {
-        let include = match impute {
-            true => true,
-            false => r.measured,
-        };
-        if include {
-            sum_data
-                .entry(r.ac_id)
-                .and_modify(|current_sum| *current_sum += r.norm_value)
-                .or_insert(r.norm_value);
-            max_scale_data
-                .entry(r.ac_id)
-                .and_modify(|current_max| {
-                    if r.norm_value > *current_max {
-                        *current_max = r.norm_value;
-                    }
-                })
-                .or_insert(r.norm_value);
-            min_scale_data
-                .entry(r.ac_id)
-                .and_modify(|current_min| {
-                    if r.norm_value < *current_min {
-                        *current_min = r.norm_value;
-                    }
-                })
-                .or_insert(r.norm_value);
-        };
+/// Sets the scaled values of `ret` per protein with `scaling`, and the
+/// shares of the protein sum that the WKDE weights and `glsn` read. Imputed
+/// cells take part when `impute` is set and keep 0 otherwise.
+fn scale(ret: &mut [QmForCreate], impute: bool, scaling: Scaling) -> Result<()> {
+    let mut rows: HashMap<i32, Vec<usize>> = HashMap::new();
+    for (i, r) in ret.iter().enumerate() {
+        if impute || r.measured {
+            rows.entry(r.ac_id).or_default().push(i);
+        }
     }
-    for r in ret.iter_mut() {
-        if let Some(sum) = sum_data.get(&r.ac_id) {
-            match r.measured {
-                true => r.scaled_value = r.norm_value / sum,
-                false => match impute {
-                    false => r.scaled_value = 0.0,
-                    true => r.scaled_value = r.norm_value / sum,
-                },
-            };
+    for idx in rows.values() {
+        let values: Vec<f64> = idx.iter().map(|i| ret[*i].norm_value).collect();
+        for (i, scaled) in idx.iter().zip(scaling.apply(&values)) {
+            ret[*i].scaled_value = scaled;
+        }
+        for (i, share) in idx.iter().zip(Scaling::Sum.apply(&values)) {
+            ret[*i].share_value = share;
         }
     }
     Ok(())
 }
 
-async fn get_ac_maps(
-    ctx: &Ctx,
+/// Records the samples and proteins of `input_path` that were not found.
+async fn store_unresolved(
     mm: &ModelManager,
-) -> Result<(HashMap<String, Ac>, HashMap<String, Ac>)> {
-    let op = ListOptions {
-        limit: Some(50_000),
-        ..Default::default()
+    input_path: &Utf8PathBuf,
+    not_found_samples: &HashSet<String>,
+    not_found_proteins: &HashMap<String, Unresolved>,
+) -> Result<()> {
+    let entry = |kind: &str, input_id: &str, reason: &str| UnresolvedIdForCreate {
+        source: input_path.to_string(),
+        kind: kind.into(),
+        input_id: input_id.into(),
+        reason: reason.into(),
     };
-
-    let acs = AcBmc::list(ctx, mm, None, Some(op.clone())).await?;
-    let acm: HashMap<String, Ac> = acs.into_iter().map(|e| (e.entry.clone(), e)).collect();
-    let acs = AcBmc::list(ctx, mm, None, Some(op)).await?;
-    let acfm: HashMap<String, Ac> = acs.into_iter().map(|e| (e.frm.clone(), e)).collect();
-    Ok((acm, acfm))
+    let entries = not_found_samples
+        .iter()
+        .map(|e| entry(SAMPLE, e, "no_annotation"))
+        .chain(
+            not_found_proteins
+                .iter()
+                .map(|(id, reason)| entry(PROTEIN, id, reason.reason())),
+        )
+        .collect();
+    UnresolvedIdBmc::bulk_import(mm, entries).await
 }
 
-async fn create_qmatrix(ctx: &Ctx, mm: &ModelManager, matrix: &str) -> Result<i32> {
+async fn create_qmatrix(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    matrix: &str,
+    scaling: Scaling,
+) -> Result<i32> {
     let qmatrix_id = QmatrixBmc::create(
         ctx,
         mm,
@@ -247,6 +313,7 @@ async fn create_qmatrix(ctx: &Ctx, mm: &ModelManager, matrix: &str) -> Result<i3
             name: matrix.into(),
             idx_column: "qmatrix".into(),
             qmatrixdf_name: "qmatrix".into(),
+            scaling: scaling.name().into(),
         },
     )
     .await?;
@@ -276,63 +343,56 @@ impl QmBmc {
         input_path: &Utf8PathBuf,
         matrix: String,
         ex: bool,
-        impute: bool,
+        processing: &QmProcessing,
+        resolver: &IdResolver,
     ) -> Result<Vec<QmForCreate>> {
         debug!("Input Path: {}", input_path);
-        let qmatrix_id = create_qmatrix(ctx, mm, &matrix).await?;
-        let (acm, acfm) = get_ac_maps(ctx, mm).await?;
+        let qmatrix_id = create_qmatrix(ctx, mm, &matrix, processing.scaling).await?;
         let annm = get_ann_map(ctx, mm, &matrix).await?;
-        let mut not_found_proteins: HashSet<String> = HashSet::new();
+        let mut not_found_proteins: HashMap<String, Unresolved> = HashMap::new();
         let mut not_found_samples: HashSet<String> = HashSet::new();
-        let mut ret = read_and_normalize(
+        let (mut ret, factors) = read_and_normalize(
             input_path,
             &annm,
-            &acm,
-            &acfm,
+            resolver,
             &mut not_found_samples,
             &mut not_found_proteins,
             qmatrix_id,
             ex,
+            processing,
         )?;
-        scale(&mut ret, impute)?;
+        store_factors(mm, qmatrix_id, processing.normalization, factors).await?;
+        scale(&mut ret, processing.imputation.scale, processing.scaling)?;
         warn!("N proteins not found: {}", not_found_proteins.len(),);
         warn!("N samples not found: {}:", not_found_samples.len());
+        store_unresolved(mm, input_path, &not_found_samples, &not_found_proteins).await?;
         iwf::msg(
             json!({"src":file!(), "line":line!(), "file":input_path.to_string(), "samples_not_found":not_found_samples}),
         );
         iwf::msg(
-            json!({"src":file!(), "line":line!(), "file":input_path.to_string(), "proteins_not_found":not_found_proteins}),
+            json!({"src":file!(), "line":line!(), "file":input_path.to_string(), "proteins_not_found":not_found_proteins.keys().collect::<Vec<_>>()}),
         );
         Ok(ret)
     }
     pub async fn bulk_import(mm: &ModelManager, entries: Vec<QmForCreate>) -> Result<()> {
-        let mut tx = mm.db().begin().await?;
-        for entry in entries {
-            let _result = sqlx::query(&format!(
-                "INSERT INTO {} (qmatrix_id,ac_id,ann_id,value,norm_value, scaled_value, measured)
-        VALUES ($1, $2, $3, $4, $5, $6, $7)",
-                Self::TABLE
-            ))
-            .bind(entry.qmatrix_id)
-            .bind(entry.ac_id)
-            .bind(entry.ann_id)
-            .bind(entry.value)
-            .bind(entry.norm_value)
-            .bind(entry.scaled_value)
-            .bind(entry.measured)
-            .execute(&mut *tx)
-            .await;
-        }
-        tx.commit().await?;
+        bulk::copy_in(mm, Self::TABLE, &entries).await?;
         Ok(())
     }
+    /// Column holding the share of the protein sum next to `scaled_value`,
+    /// added after the initial schema.
     #[must_use]
-    pub fn get_create_sql(drop_table: bool) -> String {
+    pub fn get_add_share_sql(dialect: Dialect) -> String {
+        dialect.add_column(Self::TABLE, "share_value float not null default 0")
+    }
+    #[must_use]
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
         let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
         format!(
             r##"{}
 create table if not exists {table} (
-  id serial primary key,
+  id {pk},
   qmatrix_id integer not null,
   ac_id integer not null,
   ann_id integer not null,
@@ -341,9 +401,9 @@ create table if not exists {table} (
   scaled_value float not null,
   measured bool not null
 );
-create index if not exists "IDX_{table}_qmatrix_id" ON {table} {BTREE} (qmatrix_id);
-create index if not exists "IDX_{table}_ac_id" ON {table} {BTREE} (ac_id);
-create index if not exists "IDX_{table}_ann_id" ON {table} {BTREE} (ann_id);
+create index if not exists "IDX_{table}_qmatrix_id" ON {table} {btree} (qmatrix_id);
+create index if not exists "IDX_{table}_ac_id" ON {table} {btree} (ac_id);
+create index if not exists "IDX_{table}_ann_id" ON {table} {btree} (ann_id);
         "##,
             match drop_table {
                 true => format!("drop table if exists {table};"),
@@ -362,6 +422,7 @@ pub struct Qm {
     pub value: f64,
     pub norm_value: f64,
     pub scaled_value: f64,
+    pub share_value: f64,
     pub measured: bool,
 }
 
@@ -392,26 +453,29 @@ pub struct HaatlasQmatrix {
     n: i64,
 }
 
-pub async fn create_haatlas_qmatrix(ctx: &Ctx, mm: &ModelManager) -> Result<()> {
-    let qms = vec![
-        "haatlas".to_string(),
-        "hacells".to_string(),
-        "mspatlas".into(),
-        "msratlas".into(),
-        "emblcells".into(),
-    ];
-    for qm in qms {
-        let qmatrix_id = create_qmatrix(ctx, mm, &qm).await?;
+/// Averages the `source` qmatrix of every registered atlas per `global_grp`,
+/// processing the means with the entry of `processings` for the source
+/// qmatrix.
+pub async fn create_haatlas_qmatrix(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    processings: &HashMap<String, QmProcessing>,
+) -> Result<()> {
+    for atlas in AtlasBmc::load(mm).await? {
+        let Some(source) = atlas.source else {
+            continue;
+        };
+        let processing = processings.get(&source).copied().unwrap_or_default();
+        let qm = atlas.name;
+        let qmatrix_id = create_qmatrix(ctx, mm, &qm, processing.scaling).await?;
         let annm = get_ann_map(ctx, mm, &qm).await?;
-        let items: Vec<HaatlasQmatrix> = sqlx::query_as::<_, HaatlasQmatrix>(&format!("select ac_id,global_grp,avg(value) as value,count(*) as n from qm inner join ann on ann_id = ann.id inner join qmatrix on qmatrix.id=  qmatrix_id where qmatrix.name = '{qm}raw' and measured = 'true' group by ac_id,global_grp;"))
-        .fetch_all(&mm.db)
-        .await?;
+        let (stmt, values) = query::group_means(&source);
+        let items: Vec<HaatlasQmatrix> = sqlx::query_as_with::<_, HaatlasQmatrix, _>(&stmt, values)
+            .fetch_all(&mm.db)
+            .await?;
         debug!("Item: {}", items.len());
         let mut ret = vec![];
-        let mut all_values = vec![];
-        let mut present = HashSet::new();
-        let mut present_acs = HashSet::new();
-        let mut present_anns = HashSet::new();
+        let mut observed = Observed::default();
         let mut norm_data: HashMap<i32, f64> = HashMap::new();
         for item in items {
             let ann = annm.get(&item.global_grp).unwrap();
@@ -422,71 +486,27 @@ pub async fn create_haatlas_qmatrix(ctx: &Ctx, mm: &ModelManager) -> Result<()>
                 value: item.value,
                 norm_value: 0.,
                 scaled_value: 0.,
+                share_value: 0.,
                 measured: true,
             };
-            all_values.push(fc.value);
-            present_acs.insert(item.ac_id);
-            present_anns.insert(ann.id);
-            present.insert((item.ac_id, ann.id));
+            observed.insert(item.ac_id, ann.id, fc.value);
             norm_data
                 .entry(ann.id)
                 .and_modify(|curr| *curr += fc.value)
                 .or_insert(fc.value);
             ret.push(fc);
         }
-        all_values.sort_by(|a, b| a.partial_cmp(b).unwrap());
-        let one_percent_count = (all_values.len() as f64 * 0.01).ceil() as usize;
-        let first_one_percent = &all_values[..one_percent_count];
-        debug!(
-            "LENS: {} {} {} {} {} {} {}",
-            present_acs.len(),
-            present_anns.len(),
-            present.len(),
-            all_values.len(),
-            first_one_percent.len(),
-            all_values[all_values.len() - 1],
-            first_one_percent[first_one_percent.len() - 1],
+        impute(
+            &mut ret,
+            &observed,
+            &processing.imputation,
+            qmatrix_id,
+            false,
+            &mut norm_data,
         );
-        let mut n_have = 0;
-        let mut n_missing = 0;
-        let mut rng = thread_rng();
-        for ann in &present_anns {
-            for ac in &present_acs {
-                if present.contains(&(*ac, *ann)) {
-                    n_have += 1;
-                    continue;
-                }
-                n_missing += 1;
-                let value = *first_one_percent.choose(&mut rng).unwrap();
-                let record = QmForCreate {
-                    qmatrix_id,
-                    ac_id: *ac,
-                    ann_id: *ann,
-                    value,
-                    norm_value: 0.,
-                    scaled_value: 0.,
-                    measured: false,
-                };
-                norm_data
-                    .entry(*ann)
-                    .and_modify(|curr| *curr += value)
-                    .or_insert(value);
-                ret.push(record);
-            }
-        }
-        debug!("distr {} {}", n_have, n_missing);
-        let norm_sum = norm_data.values().sum::<f64>() / (norm_data.len() as f64);
-        let mut norm_factors = HashMap::new();
-        for (k, v) in norm_data {
-            let factor = v / norm_sum;
-            debug!("{k} {v} {factor}");
-            norm_factors.insert(k, factor);
-        }
-        for r in &mut ret {
-            let nvalue = r.value * *norm_factors.get(&r.ann_id).unwrap();
-            r.norm_value = nvalue;
-        }
-        scale(&mut ret, false)?;
+        let factors = normalize_reads(&mut ret, &norm_data, processing.normalization);
+        store_factors(mm, qmatrix_id, processing.normalization, factors).await?;
+        scale(&mut ret, processing.imputation.scale, processing.scaling)?;
         debug!("RET: {}", ret.len());
         QmBmc::bulk_import(mm, ret).await?;
     }
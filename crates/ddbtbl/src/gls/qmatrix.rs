@@ -1,6 +1,9 @@
+use crate::bulk::{self, CopyField, CopyRow};
+use crate::dialect::Dialect;
+use crate::gls::scaling::Scaling;
 use camino::Utf8PathBuf;
 use iwf::ctx::Ctx;
-use iwf::sql::base::{self, DbBmc, BTREE};
+use iwf::sql::base::{self, DbBmc};
 use iwf::sql::ModelManager;
 use iwf::sql::Result;
 use modql::field::Fields;
@@ -15,6 +18,11 @@ pub struct QmatrixForCreate {
     pub name: String,
     pub idx_column: String,
     pub qmatrixdf_name: String,
+    pub scaling: String,
+}
+
+fn default_scaling() -> String {
+    Scaling::default().name().into()
 }
 
 impl QmatrixBmc {
@@ -30,38 +38,36 @@ impl QmatrixBmc {
         Ok(ret)
     }
     pub async fn bulk_import(mm: &ModelManager, entries: Vec<Qmatrix>) -> Result<()> {
-        let mut tx = mm.db().begin().await?;
-        for entry in entries {
-            let _result = sqlx::query(&format!(
-                "INSERT INTO {} (id,typ,name,idx_column,qmatrixdf_name)
-
-        VALUES ($1, $2, $3, $4, $5)",
-                Self::TABLE
-            ))
-            .bind(entry.id)
-            .bind(entry.typ)
-            .bind(entry.name)
-            .bind(entry.idx_column)
-            .bind(entry.qmatrixdf_name)
-            .execute(&mut *tx)
-            .await;
-        }
-        tx.commit().await?;
+        bulk::copy_in(mm, Self::TABLE, &entries).await?;
         Ok(())
     }
+    /// Column recording the per-protein scaling of `scaled_value`, added
+    /// after the initial schema.
+    #[must_use]
+    pub fn get_add_scaling_sql(dialect: Dialect) -> String {
+        dialect.add_column(
+            Self::TABLE,
+            &format!(
+                "scaling character varying not null default '{}'",
+                default_scaling()
+            ),
+        )
+    }
     #[must_use]
-    pub fn get_create_sql(drop_table: bool) -> String {
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
         let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
         format!(
             r##"{}
 create table if not exists {table} (
-  id serial primary key,
+  id {pk},
   typ character varying not null,
   name character varying not null,
   idx_column character varying not null,
   qmatrixdf_name character varying not null
 );
-create index if not exists "IDX_{table}_name" ON {table} {BTREE} (name);
+create index if not exists "IDX_{table}_name" ON {table} {btree} (name);
         "##,
             match drop_table {
                 true => format!("drop table if exists {table};"),
@@ -79,6 +85,29 @@ pub struct Qmatrix {
     pub name: String,
     pub idx_column: String,
     pub qmatrixdf_name: String,
+    #[serde(default = "default_scaling")]
+    pub scaling: String,
+}
+
+impl CopyRow for Qmatrix {
+    const COLUMNS: &'static [&'static str] = &[
+        "id",
+        "typ",
+        "name",
+        "idx_column",
+        "qmatrixdf_name",
+        "scaling",
+    ];
+    fn copy_fields(&self) -> Vec<&dyn CopyField> {
+        vec![
+            &self.id,
+            &self.typ,
+            &self.name,
+            &self.idx_column,
+            &self.qmatrixdf_name,
+            &self.scaling,
+        ]
+    }
 }
 
 #[derive(Fields, Default, Deserialize, Debug)]
@@ -90,6 +119,7 @@ pub struct QmatrixForUpdate {
 pub struct QmatrixFilter {
     id: Option<OpValsInt64>,
     name: Option<OpValsString>,
+    scaling: Option<OpValsString>,
 }
 
 pub struct QmatrixBmc;
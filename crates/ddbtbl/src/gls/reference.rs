@@ -0,0 +1,146 @@
+use crate::bulk::{self, CopyField, CopyRow};
+use crate::dialect::Dialect;
+use camino::Utf8Path;
+use iwf::ctx::Ctx;
+use iwf::sql::base::{self, DbBmc};
+use iwf::sql::ModelManager;
+use iwf::sql::{Error, Result};
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, OpValsInt64, OpValsString};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tabled::Tabled;
+
+/// A line of the reference TSV before the protein is matched to `ac`.
+#[derive(Deserialize, Clone, Debug)]
+pub struct ReferenceRecord {
+    pub protein: String,
+    pub tissue: String,
+}
+
+/// Expected tissue of a protein from a reference list, one row per tissue.
+#[derive(Fields, Deserialize, Clone, Debug)]
+pub struct ReferenceForCreate {
+    pub ac_id: i32,
+    pub protein: String,
+    pub tissue: String,
+}
+
+impl CopyRow for ReferenceForCreate {
+    const COLUMNS: &'static [&'static str] = &["ac_id", "protein", "tissue"];
+    fn copy_fields(&self) -> Vec<&dyn CopyField> {
+        vec![&self.ac_id, &self.protein, &self.tissue]
+    }
+}
+
+impl ReferenceBmc {
+    pub async fn bulk_import(mm: &ModelManager, entries: Vec<ReferenceForCreate>) -> Result<()> {
+        bulk::copy_in(mm, Self::TABLE, &entries).await?;
+        Ok(())
+    }
+    /// Reads a TSV with the columns `protein` and `tissue`; several tissues of
+    /// one protein may be given in one field, separated by ',' or ';'.
+    pub fn parse(input_path: &Utf8Path) -> Result<Vec<ReferenceRecord>> {
+        let mut ret = vec![];
+        let mut rdr = csv::ReaderBuilder::new()
+            .delimiter(b'\t')
+            .from_path(input_path)
+            .map_err(|e| Error::Specified(format!("Cannot read {input_path}: {e}")))?;
+        for line in rdr.deserialize() {
+            let record: ReferenceRecord =
+                line.map_err(|e| Error::Specified(format!("Cannot parse {input_path}: {e}")))?;
+            for tissue in record.tissue.split([',', ';']) {
+                let tissue = tissue.trim();
+                if !tissue.is_empty() {
+                    ret.push(ReferenceRecord {
+                        protein: record.protein.trim().to_string(),
+                        tissue: tissue.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(ret)
+    }
+    #[must_use]
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
+        let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
+        format!(
+            r##"{}
+create table if not exists {table} (
+  id {pk},
+  ac_id integer not null,
+  protein character varying not null,
+  tissue character varying not null
+);
+
+create index if not exists "IDX_{table}_ac_id" ON {table} {btree} (ac_id);
+        "##,
+            match drop_table {
+                true => format!("drop table if exists {table};"),
+                false => String::new(),
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Fields, FromRow, Serialize, Deserialize, Default, Tabled)]
+pub struct Reference {
+    pub id: i32,
+    pub ac_id: i32,
+    pub protein: String,
+    pub tissue: String,
+}
+
+#[derive(Fields, Default, Deserialize, Debug)]
+pub struct ReferenceForUpdate {
+    pub ac_id: Option<i32>,
+    pub protein: Option<String>,
+    pub tissue: Option<String>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct ReferenceFilter {
+    id: Option<OpValsInt64>,
+    ac_id: Option<OpValsInt64>,
+    protein: Option<OpValsString>,
+    tissue: Option<OpValsString>,
+}
+
+pub struct ReferenceBmc;
+
+impl DbBmc for ReferenceBmc {
+    const TABLE: &'static str = "reference";
+}
+
+impl ReferenceBmc {
+    pub async fn create(ctx: &Ctx, mm: &ModelManager, clone_c: ReferenceForCreate) -> Result<i32> {
+        base::create::<Self, _>(ctx, mm, clone_c).await
+    }
+    pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<Reference> {
+        base::get::<Self, _>(ctx, mm, id).await
+    }
+
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<ReferenceFilter>>,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<Reference>> {
+        base::list::<Self, _, _>(ctx, mm, filters, list_options).await
+    }
+
+    pub async fn update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i32,
+        clone_u: ReferenceForUpdate,
+    ) -> Result<()> {
+        base::update::<Self, _>(ctx, mm, id, clone_u).await
+    }
+
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<()> {
+        base::delete::<Self>(ctx, mm, id).await
+    }
+}
@@ -0,0 +1,164 @@
+//! Mapping of the protein identifiers of input matrices to `ac` rows.
+//!
+//! An identifier may be a protein group (`P1;P2`), resolved by its first
+//! member that maps. A member is looked up as an accession (`ac.entry`) or
+//! mapped-from identifier (`ac.frm`), then without its isoform or version
+//! suffix, and finally through the Open Targets targets: an Ensembl gene id,
+//! approved symbol or symbol synonym leads to the UniProt `proteinIds` of
+//! the target.
+
+use crate::gls::ac::{Ac, AcBmc};
+use camino::Utf8PathBuf;
+use ddbot::model::target::Target;
+use iwf::ctx::Ctx;
+use iwf::sql::ModelManager;
+use iwf::sql::{Error, Result};
+use modql::filter::ListOptions;
+use std::collections::{BTreeMap, HashMap};
+
+/// Why an identifier could not be resolved, from the least to the most
+/// specific.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Unresolved {
+    Empty,
+    /// Neither an accession nor a known target identifier.
+    Unknown,
+    /// A target without any UniProt id in `ac`.
+    NoAccession,
+    /// Synonym of targets with different accessions.
+    Ambiguous,
+    /// Resolved to a protein that already has a value in the sample, e.g.
+    /// an isoform next to its canonical accession; the first value is kept.
+    Duplicate,
+}
+
+impl Unresolved {
+    #[must_use]
+    pub fn reason(self) -> &'static str {
+        match self {
+            Self::Empty => "empty",
+            Self::Unknown => "unknown",
+            Self::NoAccession => "no_accession",
+            Self::Ambiguous => "ambiguous",
+            Self::Duplicate => "duplicate",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct IdResolver {
+    acm: HashMap<String, Ac>,
+    acfm: HashMap<String, Ac>,
+    /// Upper-cased Ensembl id, symbol or synonym to target indices.
+    targets: HashMap<String, Vec<usize>>,
+    /// UniProt ids of every target, Swiss-Prot first.
+    proteins: Vec<Vec<String>>,
+}
+
+/// `id` without a trailing `-<n>` isoform or `.<n>` version.
+fn strip_suffix(id: &str) -> &str {
+    match id.rfind(['-', '.']) {
+        Some(pos)
+            if pos > 0
+                && pos + 1 < id.len()
+                && id[pos + 1..].bytes().all(|b| b.is_ascii_digit()) =>
+        {
+            &id[..pos]
+        }
+        _ => id,
+    }
+}
+
+impl IdResolver {
+    #[must_use]
+    pub fn new(acs: Vec<Ac>, targets: impl IntoIterator<Item = Target>) -> Self {
+        let mut ret = Self::default();
+        for ac in acs {
+            ret.acfm.insert(ac.frm.clone(), ac.clone());
+            ret.acm.insert(ac.entry.clone(), ac);
+        }
+        for target in targets {
+            let idx = ret.proteins.len();
+            let mut proteins: Vec<(bool, String)> = target
+                .protein_ids
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|e| e.source.starts_with("uniprot"))
+                .map(|e| (e.source != "uniprot_swissprot", e.id))
+                .collect();
+            proteins.sort();
+            ret.proteins
+                .push(proteins.into_iter().map(|e| e.1).collect());
+            let keys = [target.id, target.approved_symbol]
+                .into_iter()
+                .chain(target.synonyms.into_iter().map(|e| e.label));
+            for key in keys {
+                let indices = ret.targets.entry(key.to_uppercase()).or_default();
+                if !indices.contains(&idx) {
+                    indices.push(idx);
+                }
+            }
+        }
+        ret
+    }
+
+    /// Resolver over the `ac` table and the targets of `target_bincode_path`.
+    pub async fn load(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        target_bincode_path: &Utf8PathBuf,
+    ) -> Result<Self> {
+        let op = ListOptions {
+            limit: Some(50_000),
+            ..Default::default()
+        };
+        let acs = AcBmc::list(ctx, mm, None, Some(op)).await?;
+        let targets = ddbot::bincode2targets(target_bincode_path)
+            .map_err(|e| Error::Specified(format!("Cannot parse {target_bincode_path}: {e}")))?;
+        Ok(Self::new(acs, targets.into_values()))
+    }
+
+    fn direct(&self, id: &str) -> Option<&Ac> {
+        self.acm.get(id).or_else(|| self.acfm.get(id))
+    }
+
+    fn via_targets(&self, id: &str) -> std::result::Result<&Ac, Unresolved> {
+        let Some(targets) = self.targets.get(&id.to_uppercase()) else {
+            return Err(Unresolved::Unknown);
+        };
+        let acs: BTreeMap<i32, &Ac> = targets
+            .iter()
+            .filter_map(|idx| self.proteins[*idx].iter().find_map(|e| self.acm.get(e)))
+            .map(|ac| (ac.id, ac))
+            .collect();
+        let mut acs = acs.into_values();
+        match (acs.next(), acs.next()) {
+            (None, _) => Err(Unresolved::NoAccession),
+            (Some(ac), None) => Ok(ac),
+            (Some(_), Some(_)) => Err(Unresolved::Ambiguous),
+        }
+    }
+
+    fn resolve_member(&self, id: &str) -> std::result::Result<&Ac, Unresolved> {
+        let stripped = strip_suffix(id);
+        if let Some(ac) = self.direct(id).or_else(|| self.direct(stripped)) {
+            return Ok(ac);
+        }
+        match self.via_targets(id) {
+            Err(e) if stripped != id => self.via_targets(stripped).map_err(|f| e.max(f)),
+            ret => ret,
+        }
+    }
+
+    /// The `ac` row of the input identifier `input`.
+    pub fn resolve(&self, input: &str) -> std::result::Result<&Ac, Unresolved> {
+        let mut reason = Unresolved::Empty;
+        for member in input.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+            match self.resolve_member(member) {
+                Ok(ac) => return Ok(ac),
+                Err(e) => reason = reason.max(e),
+            }
+        }
+        Err(reason)
+    }
+}
@@ -0,0 +1,93 @@
+//! Per-protein scaling of the normalised values into `scaled_value`. Every
+//! mode maps the values of one protein across the samples; degenerate
+//! proteins (a single value or no spread) get 0.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Scaling {
+    /// Share of the protein sum, the values sum to one.
+    #[default]
+    Sum,
+    /// Maps the smallest value to 0 and the largest to 1.
+    MinMax,
+    /// Centred on the mean, in standard deviations.
+    Zscore,
+    /// Z-score of the log2 values.
+    Log2Zscore,
+    /// Rank over the number of values, ties get their mean rank.
+    Rank,
+}
+
+impl Scaling {
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Sum => "sum",
+            Self::MinMax => "min_max",
+            Self::Zscore => "zscore",
+            Self::Log2Zscore => "log2_zscore",
+            Self::Rank => "rank",
+        }
+    }
+
+    /// Scaled `values` of one protein, in the same order.
+    #[must_use]
+    pub fn apply(self, values: &[f64]) -> Vec<f64> {
+        match self {
+            Self::Sum => {
+                let sum: f64 = values.iter().sum();
+                values.iter().map(|v| v / sum).collect()
+            }
+            Self::MinMax => {
+                let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+                let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                values
+                    .iter()
+                    .map(|v| match max > min {
+                        true => (v - min) / (max - min),
+                        false => 0.0,
+                    })
+                    .collect()
+            }
+            Self::Zscore => zscore(values),
+            Self::Log2Zscore => zscore(&values.iter().map(|v| v.log2()).collect::<Vec<f64>>()),
+            Self::Rank => rank(values),
+        }
+    }
+}
+
+fn zscore(values: &[f64]) -> Vec<f64> {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let var = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    let sd = var.sqrt();
+    values
+        .iter()
+        .map(|v| match sd > 0.0 {
+            true => (v - mean) / sd,
+            false => 0.0,
+        })
+        .collect()
+}
+
+fn rank(values: &[f64]) -> Vec<f64> {
+    let n = values.len();
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|a, b| values[*a].total_cmp(&values[*b]));
+    let mut ret = vec![0.0; n];
+    let mut i = 0;
+    while i < n {
+        let mut j = i;
+        while j + 1 < n && values[order[j + 1]] == values[order[i]] {
+            j += 1;
+        }
+        let mean_rank = (i + j) as f64 / 2.0 + 1.0;
+        for k in &order[i..=j] {
+            ret[*k] = mean_rank / n as f64;
+        }
+        i = j + 1;
+    }
+    ret
+}
@@ -1,7 +1,8 @@
+use crate::dialect::Dialect;
 use iwf::ctx::Ctx;
 use iwf::sql::base::{self, DbBmc};
 use iwf::sql::ModelManager;
-use iwf::sql::Result;
+use iwf::sql::{Error, Result};
 use modql::field::Fields;
 use modql::filter::{FilterNodes, ListOptions, OpValsFloat64, OpValsInt64};
 use serde::{Deserialize, Serialize};
@@ -44,14 +45,70 @@ impl fmt::Display for ScoreSettingForCreate {
     }
 }
 
+/// Candidate values per score parameter; an empty list keeps the default.
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct ScoreSettingGrid {
+    #[serde(default)]
+    pub cell_atlas_weight: Vec<f64>,
+    #[serde(default)]
+    pub common_threashold: Vec<i32>,
+    #[serde(default)]
+    pub delta_to_include: Vec<f64>,
+    #[serde(default)]
+    pub max_labels: Vec<i32>,
+    #[serde(default)]
+    pub multi_label_weight: Vec<f64>,
+}
+
+impl ScoreSettingGrid {
+    /// Cartesian product of the grid; the first combination takes the first
+    /// value of every list and is the reference of the label change summary.
+    #[must_use]
+    pub fn combinations(&self) -> Vec<ScoreSettingForCreate> {
+        let default = ScoreSettingForCreate::default();
+        let or_default = |values: &[f64], value: f64| match values.is_empty() {
+            true => vec![value],
+            false => values.to_vec(),
+        };
+        let or_default_int = |values: &[i32], value: i32| match values.is_empty() {
+            true => vec![value],
+            false => values.to_vec(),
+        };
+        let mut ret = vec![];
+        for multi_label_weight in or_default(&self.multi_label_weight, default.multi_label_weight) {
+            for delta_to_include in or_default(&self.delta_to_include, default.delta_to_include) {
+                for max_labels in or_default_int(&self.max_labels, default.max_labels) {
+                    for common_threashold in
+                        or_default_int(&self.common_threashold, default.common_threashold)
+                    {
+                        for cell_atlas_weight in
+                            or_default(&self.cell_atlas_weight, default.cell_atlas_weight)
+                        {
+                            ret.push(ScoreSettingForCreate {
+                                cell_atlas_weight,
+                                common_threashold,
+                                delta_to_include,
+                                max_labels,
+                                multi_label_weight,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        ret
+    }
+}
+
 impl ScoreSettingBmc {
     #[must_use]
-    pub fn get_create_sql(drop_table: bool) -> String {
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
         let table = Self::TABLE;
+        let pk = dialect.primary_key();
         format!(
             r##"{}
 create table if not exists {table} (
-  id serial primary key,
+  id {pk},
   cell_atlas_weight float not null,
   common_threashold integer not null,
   delta_to_include float not null,
@@ -108,6 +165,17 @@ impl DbBmc for ScoreSettingBmc {
 }
 
 impl ScoreSettingBmc {
+    /// Id of the reference setting, the first one stored. Reports and views
+    /// showing a single setting select it.
+    pub const REFERENCE_ID_SQL: &'static str = "select min(id) from scoresetting";
+
+    pub async fn reference_id(mm: &ModelManager) -> Result<i32> {
+        let (id,): (Option<i32>,) = sqlx::query_as(Self::REFERENCE_ID_SQL)
+            .fetch_one(mm.db())
+            .await?;
+        id.ok_or_else(|| Error::Specified("No score setting stored".to_string()))
+    }
+
     pub async fn create(
         ctx: &Ctx,
         mm: &ModelManager,
@@ -0,0 +1,149 @@
+use crate::dialect::Dialect;
+use iwf::ctx::Ctx;
+use iwf::sql::base::{self, DbBmc};
+use iwf::sql::ModelManager;
+use iwf::sql::Result;
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, OpValsInt64, OpValsString};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::collections::HashSet;
+use tabled::Tabled;
+
+pub const RUNNING: &str = "running";
+pub const DONE: &str = "done";
+pub const FAILED: &str = "failed";
+
+/// Completion state of a pipeline step, one row per step.
+#[derive(Fields, Deserialize, Clone, Debug)]
+pub struct StepStateForCreate {
+    pub step: String,
+    pub status: String,
+    pub message: String,
+}
+
+impl StepStateBmc {
+    #[must_use]
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
+        let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        format!(
+            r##"{}
+create table if not exists {table} (
+  id {pk},
+  step character varying not null unique,
+  status character varying not null,
+  message character varying not null
+);
+        "##,
+            match drop_table {
+                true => format!("drop table if exists {table};"),
+                false => String::new(),
+            }
+        )
+    }
+
+    async fn set(mm: &ModelManager, step: &str, status: &str, message: &str) -> Result<()> {
+        sqlx::query(&format!(
+            "insert into {} (step, status, message) values ($1, $2, $3) on conflict (step) do update set status = excluded.status, message = excluded.message",
+            Self::TABLE
+        ))
+        .bind(step)
+        .bind(status)
+        .bind(message)
+        .execute(mm.db())
+        .await?;
+        Ok(())
+    }
+
+    pub async fn start(mm: &ModelManager, step: &str) -> Result<()> {
+        Self::set(mm, step, RUNNING, "").await
+    }
+
+    pub async fn finish(mm: &ModelManager, step: &str) -> Result<()> {
+        Self::set(mm, step, DONE, "").await
+    }
+
+    pub async fn fail(mm: &ModelManager, step: &str, message: &str) -> Result<()> {
+        Self::set(mm, step, FAILED, message).await
+    }
+
+    /// Steps whose last run completed.
+    pub async fn done(mm: &ModelManager) -> Result<HashSet<String>> {
+        let items: Vec<(String,)> = sqlx::query_as(&format!(
+            "select step from {} where status = $1",
+            Self::TABLE
+        ))
+        .bind(DONE)
+        .fetch_all(mm.db())
+        .await?;
+        Ok(items.into_iter().map(|e| e.0).collect())
+    }
+
+    /// Forgets the state of `steps`, whose outputs are about to be replaced.
+    pub async fn reset(mm: &ModelManager, steps: &[&str]) -> Result<()> {
+        sqlx::query(&format!("delete from {} where step = any($1)", Self::TABLE))
+            .bind(steps)
+            .execute(mm.db())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Fields, FromRow, Serialize, Deserialize, Default, Tabled)]
+pub struct StepState {
+    pub id: i32,
+    pub step: String,
+    pub status: String,
+    pub message: String,
+}
+
+#[derive(Fields, Default, Deserialize, Debug)]
+pub struct StepStateForUpdate {
+    pub status: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct StepStateFilter {
+    id: Option<OpValsInt64>,
+    step: Option<OpValsString>,
+    status: Option<OpValsString>,
+}
+
+pub struct StepStateBmc;
+
+impl DbBmc for StepStateBmc {
+    const TABLE: &'static str = "step_state";
+}
+
+impl StepStateBmc {
+    pub async fn create(ctx: &Ctx, mm: &ModelManager, clone_c: StepStateForCreate) -> Result<i32> {
+        base::create::<Self, _>(ctx, mm, clone_c).await
+    }
+    pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<StepState> {
+        base::get::<Self, _>(ctx, mm, id).await
+    }
+
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<StepStateFilter>>,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<StepState>> {
+        base::list::<Self, _, _>(ctx, mm, filters, list_options).await
+    }
+
+    pub async fn update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i32,
+        clone_u: StepStateForUpdate,
+    ) -> Result<()> {
+        base::update::<Self, _>(ctx, mm, id, clone_u).await
+    }
+
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<()> {
+        base::delete::<Self>(ctx, mm, id).await
+    }
+}
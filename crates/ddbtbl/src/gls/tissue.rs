@@ -0,0 +1,218 @@
+use camino::Utf8Path;
+use iwf::sql::ModelManager;
+use iwf::sql::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A tissue or cell type label, identified by its canonical registry name.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Tissue(String);
+
+impl Tissue {
+    pub fn new(name: &str) -> Self {
+        Self(name.to_lowercase())
+    }
+    #[must_use]
+    pub fn common() -> Self {
+        Self::new("common")
+    }
+    #[must_use]
+    pub fn plasma() -> Self {
+        Self::new("plasma")
+    }
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Tissue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TissueDef {
+    pub name: String,
+    /// Alternative spellings, e.g. the `global_grp` used by an atlas.
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Short sample group tags.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub display: Option<String>,
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct TissueConfig {
+    tissues: Vec<TissueDef>,
+}
+
+/// (name, aliases, tags, color) of the tissues known before any configuration.
+const BUILTIN: &[(&str, &[&str], &[&str], &str)] = &[
+    ("adiposetissue", &[], &["adipo"], "grey"),
+    ("adrenalgland", &[], &[], "grey"),
+    ("artery", &[], &["aorta"], "grey"),
+    ("bcell", &[], &["bcell"], "blue"),
+    ("bladder", &[], &["bladd"], "grey"),
+    ("bonemarrow", &[], &["bonem"], "grey"),
+    ("brain", &[], &["brain"], "blue"),
+    ("colon", &[], &["sigmo"], "grey"),
+    ("common", &[], &[], "grey"),
+    ("erythrocytes", &[], &["eryth"], "grey"),
+    ("esophagus", &[], &[], "grey"),
+    ("heart", &[], &["heart"], "red"),
+    ("kidney", &[], &["kidne"], "grey"),
+    ("liver", &[], &["liver"], "green"),
+    ("lung", &[], &["lung"], "grey"),
+    ("macrophages", &["macrophage"], &["macro"], "grey"),
+    ("monocytes", &[], &["monoc"], "grey"),
+    ("muscle", &[], &["muscl"], "yellow"),
+    ("nerve", &[], &["nerve"], "cyan"),
+    ("neutrophils", &[], &["neutr"], "grey"),
+    ("no_tissue", &["none"], &["endon", "fasci"], "grey"),
+    ("ovary", &[], &["ovary"], "grey"),
+    ("pancreas", &[], &["pancr"], "black"),
+    ("platelets", &[], &["plate"], "grey"),
+    ("prostate", &[], &["prost"], "grey"),
+    ("skin", &[], &["skin"], "grey"),
+    ("spleen", &[], &["splee"], "grey"),
+    ("stomach", &[], &["stoma"], "grey"),
+    ("tcellcd4", &[], &["tcell4"], "grey"),
+    ("tcellcd8", &[], &["tcell8"], "grey"),
+    ("thyroid", &[], &[], "grey"),
+    ("plasma", &[], &[], "grey"),
+    ("cmi", &[], &["cmi"], "grey"),
+    ("mi", &[], &["mi"], "blue"),
+    ("nvar", &[], &["nvar"], "grey"),
+    ("cpancr", &[], &["cpancr"], "grey"),
+    ("pancr", &[], &[], "blue"),
+    ("bsep", &[], &["bsep"], "orange"),
+    ("vsep", &[], &["vsep"], "cyan"),
+];
+
+/// Tissue vocabulary with aliases, short tags, display names and colors.
+/// Names, aliases and tags are matched case-insensitively.
+#[derive(Debug, Clone, Default)]
+pub struct TissueRegistry {
+    defs: Vec<TissueDef>,
+    names: HashMap<String, usize>,
+    tags: HashMap<String, usize>,
+}
+
+impl TissueRegistry {
+    #[must_use]
+    pub fn builtin() -> Self {
+        let mut ret = Self::default();
+        for (name, aliases, tags, color) in BUILTIN {
+            ret.insert(TissueDef {
+                name: (*name).into(),
+                aliases: aliases.iter().map(|e| (*e).into()).collect(),
+                tags: tags.iter().map(|e| (*e).into()).collect(),
+                display: None,
+                color: Some((*color).into()),
+            });
+        }
+        ret
+    }
+
+    /// Built-in tissues, extended by every `ann.global_grp` and then by the
+    /// optional YAML/JSON file (`tissues: [{name, aliases, tags, display, color}]`).
+    pub async fn load(mm: &ModelManager, path: Option<&Utf8Path>) -> Result<Self> {
+        let mut ret = Self::builtin();
+        let groups: Vec<(String,)> =
+            sqlx::query_as("select distinct global_grp from ann order by global_grp")
+                .fetch_all(mm.db())
+                .await?;
+        for (grp,) in groups {
+            if ret.resolve(&grp).is_err() {
+                ret.insert(TissueDef {
+                    name: grp,
+                    ..Default::default()
+                });
+            }
+        }
+        if let Some(path) = path {
+            ret.merge_file(path)?;
+        }
+        Ok(ret)
+    }
+
+    pub fn merge_file(&mut self, path: &Utf8Path) -> Result<()> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| Error::Specified(format!("Cannot read {path}: {e}")))?;
+        let config: TissueConfig = match path.extension() {
+            Some("json") => serde_json::from_str(&content)
+                .map_err(|e| Error::Specified(format!("Cannot parse {path}: {e}")))?,
+            _ => serde_yaml::from_str(&content)
+                .map_err(|e| Error::Specified(format!("Cannot parse {path}: {e}")))?,
+        };
+        for def in config.tissues {
+            self.insert(def);
+        }
+        Ok(())
+    }
+
+    /// Adds a tissue or replaces the definition with the same name.
+    pub fn insert(&mut self, mut def: TissueDef) {
+        def.name = def.name.to_lowercase();
+        let idx = match self.defs.iter().position(|e| e.name == def.name) {
+            Some(idx) => {
+                self.names.retain(|_, v| *v != idx);
+                self.tags.retain(|_, v| *v != idx);
+                self.defs[idx] = def;
+                idx
+            }
+            None => {
+                self.defs.push(def);
+                self.defs.len() - 1
+            }
+        };
+        let def = &self.defs[idx];
+        for name in std::iter::once(&def.name).chain(&def.aliases) {
+            self.names.insert(name.to_lowercase(), idx);
+        }
+        for tag in &def.tags {
+            self.tags.insert(tag.to_lowercase(), idx);
+        }
+    }
+
+    /// Maps a name or alias to its tissue.
+    pub fn resolve(&self, input: &str) -> Result<Tissue> {
+        self.names
+            .get(&input.to_lowercase())
+            .map(|idx| Tissue::new(&self.defs[*idx].name))
+            .ok_or_else(|| Error::Specified(format!("Unknown tissue: {input}")))
+    }
+
+    /// Maps a short sample group tag to its tissue.
+    pub fn from_tag(&self, tag: &str) -> Result<Tissue> {
+        self.tags
+            .get(&tag.to_lowercase())
+            .map(|idx| Tissue::new(&self.defs[*idx].name))
+            .ok_or_else(|| Error::Specified(format!("Unknown tissue tag: {tag}")))
+    }
+
+    fn get(&self, tissue: &Tissue) -> Option<&TissueDef> {
+        self.names.get(tissue.name()).map(|idx| &self.defs[*idx])
+    }
+
+    #[must_use]
+    pub fn display(&self, tissue: &Tissue) -> String {
+        self.get(tissue)
+            .and_then(|e| e.display.clone())
+            .unwrap_or_else(|| tissue.to_string())
+    }
+
+    #[must_use]
+    pub fn color(&self, tissue: &Tissue) -> String {
+        self.get(tissue)
+            .and_then(|e| e.color.clone())
+            .unwrap_or_else(|| String::from("grey"))
+    }
+}
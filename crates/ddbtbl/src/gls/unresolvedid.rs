@@ -0,0 +1,124 @@
+use crate::bulk::{self, CopyField, CopyRow};
+use crate::dialect::Dialect;
+use iwf::ctx::Ctx;
+use iwf::sql::base::{self, DbBmc};
+use iwf::sql::ModelManager;
+use iwf::sql::Result;
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, OpValsInt64, OpValsString};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tabled::Tabled;
+
+pub const PROTEIN: &str = "protein";
+pub const SAMPLE: &str = "sample";
+
+/// Identifier of input file `source` that could not be mapped; `kind` is
+/// [`PROTEIN`] or [`SAMPLE`].
+#[derive(Fields, Deserialize, Clone, Debug)]
+pub struct UnresolvedIdForCreate {
+    pub source: String,
+    pub kind: String,
+    pub input_id: String,
+    pub reason: String,
+}
+
+impl CopyRow for UnresolvedIdForCreate {
+    const COLUMNS: &'static [&'static str] = &["source", "kind", "input_id", "reason"];
+    fn copy_fields(&self) -> Vec<&dyn CopyField> {
+        vec![&self.source, &self.kind, &self.input_id, &self.reason]
+    }
+}
+
+impl UnresolvedIdBmc {
+    pub async fn bulk_import(mm: &ModelManager, entries: Vec<UnresolvedIdForCreate>) -> Result<()> {
+        bulk::copy_in(mm, Self::TABLE, &entries).await?;
+        Ok(())
+    }
+    #[must_use]
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
+        let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
+        format!(
+            r##"{}
+create table if not exists {table} (
+  id {pk},
+  source character varying not null,
+  kind character varying not null,
+  input_id character varying not null,
+  reason character varying not null
+);
+create index if not exists "IDX_{table}_source" ON {table} {btree} (source);
+        "##,
+            match drop_table {
+                true => format!("drop table if exists {table};"),
+                false => String::new(),
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Fields, FromRow, Serialize, Deserialize, Default, Tabled)]
+pub struct UnresolvedId {
+    pub id: i32,
+    pub source: String,
+    pub kind: String,
+    pub input_id: String,
+    pub reason: String,
+}
+
+#[derive(Fields, Default, Deserialize, Debug)]
+pub struct UnresolvedIdForUpdate {
+    pub reason: Option<String>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct UnresolvedIdFilter {
+    id: Option<OpValsInt64>,
+    source: Option<OpValsString>,
+    kind: Option<OpValsString>,
+    input_id: Option<OpValsString>,
+    reason: Option<OpValsString>,
+}
+
+pub struct UnresolvedIdBmc;
+
+impl DbBmc for UnresolvedIdBmc {
+    const TABLE: &'static str = "unresolved_id";
+}
+
+impl UnresolvedIdBmc {
+    pub async fn create(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        clone_c: UnresolvedIdForCreate,
+    ) -> Result<i32> {
+        base::create::<Self, _>(ctx, mm, clone_c).await
+    }
+    pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<UnresolvedId> {
+        base::get::<Self, _>(ctx, mm, id).await
+    }
+
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<UnresolvedIdFilter>>,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<UnresolvedId>> {
+        base::list::<Self, _, _>(ctx, mm, filters, list_options).await
+    }
+
+    pub async fn update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i32,
+        clone_u: UnresolvedIdForUpdate,
+    ) -> Result<()> {
+        base::update::<Self, _>(ctx, mm, id, clone_u).await
+    }
+
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<()> {
+        base::delete::<Self>(ctx, mm, id).await
+    }
+}
@@ -1,5 +1,7 @@
+use crate::bulk::{self, CopyField, CopyRow};
+use crate::dialect::Dialect;
 use iwf::ctx::Ctx;
-use iwf::sql::base::{self, DbBmc, BTREE};
+use iwf::sql::base::{self, DbBmc};
 use iwf::sql::ModelManager;
 use iwf::sql::Result;
 use modql::field::Fields;
@@ -15,37 +17,34 @@ pub struct WkdeLabelForCreate {
     pub labels: Vec<i32>,
 }
 
+impl CopyRow for WkdeLabelForCreate {
+    const COLUMNS: &'static [&'static str] = &["ac_id", "kind", "labels"];
+    fn copy_fields(&self) -> Vec<&dyn CopyField> {
+        vec![&self.ac_id, &self.kind, &self.labels]
+    }
+}
+
 impl WkdeLabelBmc {
     pub async fn bulk_import(mm: &ModelManager, entries: Vec<WkdeLabelForCreate>) -> Result<()> {
-        let mut tx = mm.db().begin().await?;
-        for entry in entries {
-            let _result = sqlx::query(&format!(
-                "INSERT INTO {} (ac_id,kind,labels)
-    VALUES ($1,$2,$3)",
-                Self::TABLE
-            ))
-            .bind(entry.ac_id)
-            .bind(entry.kind)
-            .bind(entry.labels)
-            .execute(&mut *tx)
-            .await;
-        }
-        tx.commit().await?;
+        bulk::copy_in(mm, Self::TABLE, &entries).await?;
         Ok(())
     }
     #[must_use]
-    pub fn get_create_sql(drop_table: bool) -> String {
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
         let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
+        let int_array = dialect.int_array();
         format!(
             r##"{}
 create table if not exists {table} (
-  id serial primary key,
+  id {pk},
   ac_id integer not null,
   kind character varying not null,
-  labels integer[] not null
+  labels {int_array} not null
 );
-create index if not exists "IDX_{table}_ac_id" ON {table} {BTREE} (ac_id);
-create index if not exists "IDX_{table}_kind" ON {table} {BTREE} (kind);
+create index if not exists "IDX_{table}_ac_id" ON {table} {btree} (ac_id);
+create index if not exists "IDX_{table}_kind" ON {table} {btree} (kind);
 
         "##,
             match drop_table {
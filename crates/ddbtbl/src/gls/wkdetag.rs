@@ -1,5 +1,7 @@
+use crate::bulk::{self, CopyField, CopyRow};
+use crate::dialect::Dialect;
 use iwf::ctx::Ctx;
-use iwf::sql::base::{self, DbBmc, BTREE};
+use iwf::sql::base::{self, DbBmc};
 use iwf::sql::ModelManager;
 use iwf::sql::Result;
 use modql::field::Fields;
@@ -17,33 +19,33 @@ pub struct WkdeTagForCreate {
     pub n_pixels: i32,
 }
 
+impl CopyRow for WkdeTagForCreate {
+    const COLUMNS: &'static [&'static str] = &["idx", "kind", "label", "n_labels", "n_pixels"];
+    fn copy_fields(&self) -> Vec<&dyn CopyField> {
+        vec![
+            &self.idx,
+            &self.kind,
+            &self.label,
+            &self.n_labels,
+            &self.n_pixels,
+        ]
+    }
+}
+
 impl WkdeTagBmc {
     pub async fn bulk_import(mm: &ModelManager, entries: Vec<WkdeTagForCreate>) -> Result<()> {
-        let mut tx = mm.db().begin().await?;
-        for entry in entries {
-            let _result = sqlx::query(&format!(
-                "INSERT INTO {} (idx,kind,label,n_labels,n_pixels)
-    VALUES ($1,$2,$3,$4,$5)",
-                Self::TABLE
-            ))
-            .bind(entry.idx)
-            .bind(entry.kind)
-            .bind(entry.label)
-            .bind(entry.n_labels)
-            .bind(entry.n_pixels)
-            .execute(&mut *tx)
-            .await;
-        }
-        tx.commit().await?;
+        bulk::copy_in(mm, Self::TABLE, &entries).await?;
         Ok(())
     }
     #[must_use]
-    pub fn get_create_sql(drop_table: bool) -> String {
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
         let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
         format!(
             r##"{}
 create table if not exists {table} (
-  id serial primary key,
+  id {pk},
   idx integer not null,
   kind character varying not null,
   label character varying not null,
@@ -51,8 +53,8 @@ create table if not exists {table} (
   n_pixels integer not null
 );
 
-create index if not exists "IDX_{table}_kind" ON {table} {BTREE} (kind);
-create index if not exists "IDX_{table}_label" ON {table} {BTREE} (label);
+create index if not exists "IDX_{table}_kind" ON {table} {btree} (kind);
+create index if not exists "IDX_{table}_label" ON {table} {btree} (label);
 
 
         "##,
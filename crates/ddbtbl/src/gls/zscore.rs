@@ -1,53 +1,73 @@
+use crate::bulk::{self, CopyField, CopyRow};
+use crate::dialect::Dialect;
 use iwf::ctx::Ctx;
-use iwf::sql::base::{self, DbBmc, BTREE};
+use iwf::sql::base::{self, DbBmc};
 use iwf::sql::ModelManager;
 use iwf::sql::Result;
 use modql::field::Fields;
-use modql::filter::{FilterNodes, ListOptions, OpValsFloat64, OpValsInt64};
+use modql::filter::{FilterNodes, ListOptions, OpValsFloat64, OpValsInt64, OpValsString};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use tabled::Tabled;
 
+/// Over the measured cells of the protein only.
+pub const MEASURED: &str = "measured";
+/// Over all cells of the protein, the measured and the imputed ones.
+pub const WITH_IMPUTED: &str = "with_imputed";
+
+/// Z-score of protein `ac_id` in sample `ann_id` across the samples of
+/// qmatrix `qmatrix_id`; `kind` is [`MEASURED`] or [`WITH_IMPUTED`].
 #[derive(Fields, Deserialize, Clone, Debug)]
 pub struct ZscoreForCreate {
     pub ac_id: i32,
     pub ann_id: i32,
     pub qmatrix_id: i32,
     pub zscore: f64,
+    pub kind: String,
+}
+
+impl CopyRow for ZscoreForCreate {
+    const COLUMNS: &'static [&'static str] = &["ac_id", "ann_id", "qmatrix_id", "zscore", "kind"];
+    fn copy_fields(&self) -> Vec<&dyn CopyField> {
+        vec![
+            &self.ac_id,
+            &self.ann_id,
+            &self.qmatrix_id,
+            &self.zscore,
+            &self.kind,
+        ]
+    }
 }
 
 impl ZscoreBmc {
     pub async fn bulk_import(mm: &ModelManager, entries: Vec<ZscoreForCreate>) -> Result<()> {
-        let mut tx = mm.db().begin().await?;
-        for entry in entries {
-            let _result = sqlx::query(&format!(
-                "INSERT INTO {} (ac_id,ann_id,qmatrix_id,zscore)
-        VALUES ($1, $2, $3, $4)",
-                Self::TABLE
-            ))
-            .bind(entry.ac_id)
-            .bind(entry.ann_id)
-            .bind(entry.qmatrix_id)
-            .bind(entry.zscore)
-            .execute(&mut *tx)
-            .await;
-        }
-        tx.commit().await?;
+        bulk::copy_in(mm, Self::TABLE, &entries).await?;
         Ok(())
     }
+    /// Column telling measured-only z-scores from those over all cells,
+    /// added after the initial schema.
+    #[must_use]
+    pub fn get_add_kind_sql(dialect: Dialect) -> String {
+        dialect.add_column(
+            Self::TABLE,
+            &format!("kind character varying not null default '{MEASURED}'"),
+        )
+    }
     #[must_use]
-    pub fn get_create_sql(drop_table: bool) -> String {
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
         let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
         format!(
             r##"{}
 create table if not exists {table} (
-  id serial primary key,
+  id {pk},
   ac_id integer not null,
   ann_id integer not null,
   qmatrix_id integer not null,
   zscore float not null
 );
-create index if not exists "IDX_{table}_ac_id" ON {table} {BTREE} (ac_id);
+create index if not exists "IDX_{table}_ac_id" ON {table} {btree} (ac_id);
         "##,
             match drop_table {
                 true => format!("drop table if exists {table};"),
@@ -64,6 +84,7 @@ pub struct Zscore {
     pub ann_id: i32,
     pub qmatrix_id: i32,
     pub zscore: f64,
+    pub kind: String,
 }
 
 #[derive(Fields, Default, Deserialize, Debug)]
@@ -72,6 +93,7 @@ pub struct ZscoreForUpdate {
     pub ann_id: Option<i32>,
     pub qmatrix_id: Option<i32>,
     pub zscore: Option<f64>,
+    pub kind: Option<String>,
 }
 
 #[derive(FilterNodes, Deserialize, Default, Debug)]
@@ -81,6 +103,7 @@ pub struct ZscoreFilter {
     ann_id: Option<OpValsInt64>,
     qmatrix_id: Option<OpValsInt64>,
     zscore: Option<OpValsFloat64>,
+    kind: Option<OpValsString>,
 }
 
 pub struct ZscoreBmc;
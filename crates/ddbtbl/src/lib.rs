@@ -1,3 +1,7 @@
+pub mod bulk;
 pub mod cluster;
+pub mod dialect;
 pub mod gls;
 pub mod opentarget;
+pub mod query;
+pub mod schema;
@@ -0,0 +1,196 @@
+use crate::bulk::{self, CopyField, CopyRow};
+use crate::dialect::Dialect;
+use ddbot::model::target::Target as OtTarget;
+use iwf::ctx::Ctx;
+use iwf::sql::base::{self, DbBmc};
+use iwf::sql::ModelManager;
+use iwf::sql::Result;
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, OpValsFloat64, OpValsInt64, OpValsString};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tabled::Tabled;
+
+/// Genetic constraint of target `ensg`; missing values are NULL.
+#[derive(Fields, Deserialize, Clone, Debug)]
+pub struct TargetConstraintForCreate {
+    pub ensg: String,
+    pub constraint_type: String,
+    pub score: Option<f64>,
+    pub exp: Option<f64>,
+    pub obs: Option<f64>,
+    pub oe: Option<f64>,
+    pub oe_lower: Option<f64>,
+    pub oe_upper: Option<f64>,
+}
+
+impl TargetConstraintForCreate {
+    #[must_use]
+    pub fn from_target(target: &OtTarget) -> Vec<Self> {
+        target
+            .constraint
+            .iter()
+            .flatten()
+            .map(|e| Self {
+                ensg: target.id.clone(),
+                constraint_type: e.constraint_type.clone(),
+                score: e.score.map(f64::from),
+                exp: e.exp.map(f64::from),
+                obs: e.obs.map(f64::from),
+                oe: e.oe.map(f64::from),
+                oe_lower: e.oe_lower.map(f64::from),
+                oe_upper: e.oe_upper.map(f64::from),
+            })
+            .collect()
+    }
+}
+
+impl CopyRow for TargetConstraintForCreate {
+    const COLUMNS: &'static [&'static str] = &[
+        "ensg",
+        "constraint_type",
+        "score",
+        "exp",
+        "obs",
+        "oe",
+        "oe_lower",
+        "oe_upper",
+    ];
+    fn copy_fields(&self) -> Vec<&dyn CopyField> {
+        vec![
+            &self.ensg,
+            &self.constraint_type,
+            &self.score,
+            &self.exp,
+            &self.obs,
+            &self.oe,
+            &self.oe_lower,
+            &self.oe_upper,
+        ]
+    }
+}
+
+impl TargetConstraintBmc {
+    pub async fn bulk_import(
+        mm: &ModelManager,
+        entries: Vec<TargetConstraintForCreate>,
+    ) -> Result<()> {
+        bulk::copy_in(mm, Self::TABLE, &entries).await?;
+        Ok(())
+    }
+    #[must_use]
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
+        let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
+        format!(
+            r##"{}
+create table if not exists {table} (
+  id {pk},
+  ensg character varying not null,
+  constraint_type character varying not null,
+  score float,
+  exp float,
+  obs float,
+  oe float,
+  oe_lower float,
+  oe_upper float
+);
+create index if not exists "IDX_{table}_ensg" ON {table} {btree} (ensg);
+        "##,
+            match drop_table {
+                true => format!("drop table if exists {table};"),
+                false => String::new(),
+            }
+        )
+    }
+}
+
+fn display_option(value: &Option<f64>) -> String {
+    value.map(|e| e.to_string()).unwrap_or_default()
+}
+
+#[derive(Debug, Clone, Fields, FromRow, Serialize, Deserialize, Default, Tabled)]
+pub struct TargetConstraint {
+    pub id: i32,
+    pub ensg: String,
+    pub constraint_type: String,
+    #[tabled(display_with = "display_option")]
+    pub score: Option<f64>,
+    #[tabled(display_with = "display_option")]
+    pub exp: Option<f64>,
+    #[tabled(display_with = "display_option")]
+    pub obs: Option<f64>,
+    #[tabled(display_with = "display_option")]
+    pub oe: Option<f64>,
+    #[tabled(display_with = "display_option")]
+    pub oe_lower: Option<f64>,
+    #[tabled(display_with = "display_option")]
+    pub oe_upper: Option<f64>,
+}
+
+#[derive(Fields, Default, Deserialize, Debug)]
+pub struct TargetConstraintForUpdate {
+    pub ensg: Option<String>,
+    pub constraint_type: Option<String>,
+    pub score: Option<f64>,
+    pub exp: Option<f64>,
+    pub obs: Option<f64>,
+    pub oe: Option<f64>,
+    pub oe_lower: Option<f64>,
+    pub oe_upper: Option<f64>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct TargetConstraintFilter {
+    id: Option<OpValsInt64>,
+    ensg: Option<OpValsString>,
+    constraint_type: Option<OpValsString>,
+    score: Option<OpValsFloat64>,
+    exp: Option<OpValsFloat64>,
+    obs: Option<OpValsFloat64>,
+    oe: Option<OpValsFloat64>,
+    oe_lower: Option<OpValsFloat64>,
+    oe_upper: Option<OpValsFloat64>,
+}
+
+pub struct TargetConstraintBmc;
+
+impl DbBmc for TargetConstraintBmc {
+    const TABLE: &'static str = "target_constraint";
+}
+
+impl TargetConstraintBmc {
+    pub async fn create(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        clone_c: TargetConstraintForCreate,
+    ) -> Result<i32> {
+        base::create::<Self, _>(ctx, mm, clone_c).await
+    }
+    pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<TargetConstraint> {
+        base::get::<Self, _>(ctx, mm, id).await
+    }
+
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<TargetConstraintFilter>>,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<TargetConstraint>> {
+        base::list::<Self, _, _>(ctx, mm, filters, list_options).await
+    }
+
+    pub async fn update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i32,
+        clone_u: TargetConstraintForUpdate,
+    ) -> Result<()> {
+        base::update::<Self, _>(ctx, mm, id, clone_u).await
+    }
+
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<()> {
+        base::delete::<Self>(ctx, mm, id).await
+    }
+}
@@ -0,0 +1,172 @@
+use crate::bulk::{self, CopyField, CopyRow};
+use crate::dialect::Dialect;
+use ddbot::model::target::Target as OtTarget;
+use iwf::ctx::Ctx;
+use iwf::sql::base::{self, DbBmc};
+use iwf::sql::ModelManager;
+use iwf::sql::Result;
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, OpValsInt64, OpValsString};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tabled::Tabled;
+
+/// GO annotation of target `ensg`.
+#[derive(Fields, Deserialize, Clone, Debug)]
+pub struct TargetGoForCreate {
+    pub ensg: String,
+    pub go_id: String,
+    pub source: String,
+    pub evidence: String,
+    pub aspect: String,
+    pub gene_product: String,
+    pub eco_id: String,
+}
+
+impl TargetGoForCreate {
+    #[must_use]
+    pub fn from_target(target: &OtTarget) -> Vec<Self> {
+        target
+            .go
+            .iter()
+            .flatten()
+            .map(|e| Self {
+                ensg: target.id.clone(),
+                go_id: e.id.clone(),
+                source: e.source.clone(),
+                evidence: e.evidence.clone(),
+                aspect: e.aspect.clone(),
+                gene_product: e.gene_product.clone(),
+                eco_id: e.eco_id.clone().unwrap_or_default(),
+            })
+            .collect()
+    }
+}
+
+impl CopyRow for TargetGoForCreate {
+    const COLUMNS: &'static [&'static str] = &[
+        "ensg",
+        "go_id",
+        "source",
+        "evidence",
+        "aspect",
+        "gene_product",
+        "eco_id",
+    ];
+    fn copy_fields(&self) -> Vec<&dyn CopyField> {
+        vec![
+            &self.ensg,
+            &self.go_id,
+            &self.source,
+            &self.evidence,
+            &self.aspect,
+            &self.gene_product,
+            &self.eco_id,
+        ]
+    }
+}
+
+impl TargetGoBmc {
+    pub async fn bulk_import(mm: &ModelManager, entries: Vec<TargetGoForCreate>) -> Result<()> {
+        bulk::copy_in(mm, Self::TABLE, &entries).await?;
+        Ok(())
+    }
+    #[must_use]
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
+        let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
+        format!(
+            r##"{}
+create table if not exists {table} (
+  id {pk},
+  ensg character varying not null,
+  go_id character varying not null,
+  source character varying not null,
+  evidence character varying not null,
+  aspect character varying not null,
+  gene_product character varying not null,
+  eco_id character varying not null
+);
+create index if not exists "IDX_{table}_ensg" ON {table} {btree} (ensg);
+create index if not exists "IDX_{table}_go_id" ON {table} {btree} (go_id);
+        "##,
+            match drop_table {
+                true => format!("drop table if exists {table};"),
+                false => String::new(),
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Fields, FromRow, Serialize, Deserialize, Default, Tabled)]
+pub struct TargetGo {
+    pub id: i32,
+    pub ensg: String,
+    pub go_id: String,
+    pub source: String,
+    pub evidence: String,
+    pub aspect: String,
+    pub gene_product: String,
+    pub eco_id: String,
+}
+
+#[derive(Fields, Default, Deserialize, Debug)]
+pub struct TargetGoForUpdate {
+    pub ensg: Option<String>,
+    pub go_id: Option<String>,
+    pub source: Option<String>,
+    pub evidence: Option<String>,
+    pub aspect: Option<String>,
+    pub gene_product: Option<String>,
+    pub eco_id: Option<String>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct TargetGoFilter {
+    id: Option<OpValsInt64>,
+    ensg: Option<OpValsString>,
+    go_id: Option<OpValsString>,
+    source: Option<OpValsString>,
+    evidence: Option<OpValsString>,
+    aspect: Option<OpValsString>,
+    gene_product: Option<OpValsString>,
+    eco_id: Option<OpValsString>,
+}
+
+pub struct TargetGoBmc;
+
+impl DbBmc for TargetGoBmc {
+    const TABLE: &'static str = "target_go";
+}
+
+impl TargetGoBmc {
+    pub async fn create(ctx: &Ctx, mm: &ModelManager, clone_c: TargetGoForCreate) -> Result<i32> {
+        base::create::<Self, _>(ctx, mm, clone_c).await
+    }
+    pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<TargetGo> {
+        base::get::<Self, _>(ctx, mm, id).await
+    }
+
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<TargetGoFilter>>,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<TargetGo>> {
+        base::list::<Self, _, _>(ctx, mm, filters, list_options).await
+    }
+
+    pub async fn update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i32,
+        clone_u: TargetGoForUpdate,
+    ) -> Result<()> {
+        base::update::<Self, _>(ctx, mm, id, clone_u).await
+    }
+
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<()> {
+        base::delete::<Self>(ctx, mm, id).await
+    }
+}
@@ -0,0 +1,157 @@
+use crate::bulk::{self, CopyField, CopyRow};
+use crate::dialect::Dialect;
+use ddbot::model::target::Target as OtTarget;
+use iwf::ctx::Ctx;
+use iwf::sql::base::{self, DbBmc};
+use iwf::sql::ModelManager;
+use iwf::sql::Result;
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, OpValsInt64, OpValsString};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tabled::Tabled;
+
+/// Subcellular location of target `ensg`.
+#[derive(Fields, Deserialize, Clone, Debug)]
+pub struct TargetLocationForCreate {
+    pub ensg: String,
+    pub location: String,
+    pub source: String,
+    pub term_sl: String,
+    pub label_sl: String,
+}
+
+impl TargetLocationForCreate {
+    #[must_use]
+    pub fn from_target(target: &OtTarget) -> Vec<Self> {
+        target
+            .subcellular_locations
+            .iter()
+            .flatten()
+            .map(|e| Self {
+                ensg: target.id.clone(),
+                location: e.location.clone(),
+                source: e.source.clone(),
+                term_sl: e.term_sl.clone().unwrap_or_default(),
+                label_sl: e.label_sl.clone().unwrap_or_default(),
+            })
+            .collect()
+    }
+}
+
+impl CopyRow for TargetLocationForCreate {
+    const COLUMNS: &'static [&'static str] = &["ensg", "location", "source", "term_sl", "label_sl"];
+    fn copy_fields(&self) -> Vec<&dyn CopyField> {
+        vec![
+            &self.ensg,
+            &self.location,
+            &self.source,
+            &self.term_sl,
+            &self.label_sl,
+        ]
+    }
+}
+
+impl TargetLocationBmc {
+    pub async fn bulk_import(
+        mm: &ModelManager,
+        entries: Vec<TargetLocationForCreate>,
+    ) -> Result<()> {
+        bulk::copy_in(mm, Self::TABLE, &entries).await?;
+        Ok(())
+    }
+    #[must_use]
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
+        let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
+        format!(
+            r##"{}
+create table if not exists {table} (
+  id {pk},
+  ensg character varying not null,
+  location character varying not null,
+  source character varying not null,
+  term_sl character varying not null,
+  label_sl character varying not null
+);
+create index if not exists "IDX_{table}_ensg" ON {table} {btree} (ensg);
+create index if not exists "IDX_{table}_location" ON {table} {btree} (location);
+        "##,
+            match drop_table {
+                true => format!("drop table if exists {table};"),
+                false => String::new(),
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Fields, FromRow, Serialize, Deserialize, Default, Tabled)]
+pub struct TargetLocation {
+    pub id: i32,
+    pub ensg: String,
+    pub location: String,
+    pub source: String,
+    pub term_sl: String,
+    pub label_sl: String,
+}
+
+#[derive(Fields, Default, Deserialize, Debug)]
+pub struct TargetLocationForUpdate {
+    pub ensg: Option<String>,
+    pub location: Option<String>,
+    pub source: Option<String>,
+    pub term_sl: Option<String>,
+    pub label_sl: Option<String>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct TargetLocationFilter {
+    id: Option<OpValsInt64>,
+    ensg: Option<OpValsString>,
+    location: Option<OpValsString>,
+    source: Option<OpValsString>,
+    term_sl: Option<OpValsString>,
+    label_sl: Option<OpValsString>,
+}
+
+pub struct TargetLocationBmc;
+
+impl DbBmc for TargetLocationBmc {
+    const TABLE: &'static str = "target_location";
+}
+
+impl TargetLocationBmc {
+    pub async fn create(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        clone_c: TargetLocationForCreate,
+    ) -> Result<i32> {
+        base::create::<Self, _>(ctx, mm, clone_c).await
+    }
+    pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<TargetLocation> {
+        base::get::<Self, _>(ctx, mm, id).await
+    }
+
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<TargetLocationFilter>>,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<TargetLocation>> {
+        base::list::<Self, _, _>(ctx, mm, filters, list_options).await
+    }
+
+    pub async fn update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i32,
+        clone_u: TargetLocationForUpdate,
+    ) -> Result<()> {
+        base::update::<Self, _>(ctx, mm, id, clone_u).await
+    }
+
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<()> {
+        base::delete::<Self>(ctx, mm, id).await
+    }
+}
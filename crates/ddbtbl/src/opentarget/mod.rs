@@ -1 +1,8 @@
+pub mod constraint;
+pub mod go;
+pub mod location;
+pub mod pathway;
+pub mod proteinid;
 pub mod target;
+pub mod tractability;
+pub mod transcript;
@@ -0,0 +1,150 @@
+use crate::bulk::{self, CopyField, CopyRow};
+use crate::dialect::Dialect;
+use ddbot::model::target::Target as OtTarget;
+use iwf::ctx::Ctx;
+use iwf::sql::base::{self, DbBmc};
+use iwf::sql::ModelManager;
+use iwf::sql::Result;
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, OpValsInt64, OpValsString};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tabled::Tabled;
+
+/// Reactome pathway of target `ensg`.
+#[derive(Fields, Deserialize, Clone, Debug)]
+pub struct TargetPathwayForCreate {
+    pub ensg: String,
+    pub pathway_id: String,
+    pub pathway: String,
+    pub top_level_term: String,
+}
+
+impl TargetPathwayForCreate {
+    #[must_use]
+    pub fn from_target(target: &OtTarget) -> Vec<Self> {
+        target
+            .pathways
+            .iter()
+            .flatten()
+            .map(|e| Self {
+                ensg: target.id.clone(),
+                pathway_id: e.pathway_id.clone(),
+                pathway: e.pathway.clone(),
+                top_level_term: e.top_level_term.clone(),
+            })
+            .collect()
+    }
+}
+
+impl CopyRow for TargetPathwayForCreate {
+    const COLUMNS: &'static [&'static str] = &["ensg", "pathway_id", "pathway", "top_level_term"];
+    fn copy_fields(&self) -> Vec<&dyn CopyField> {
+        vec![
+            &self.ensg,
+            &self.pathway_id,
+            &self.pathway,
+            &self.top_level_term,
+        ]
+    }
+}
+
+impl TargetPathwayBmc {
+    pub async fn bulk_import(
+        mm: &ModelManager,
+        entries: Vec<TargetPathwayForCreate>,
+    ) -> Result<()> {
+        bulk::copy_in(mm, Self::TABLE, &entries).await?;
+        Ok(())
+    }
+    #[must_use]
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
+        let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
+        format!(
+            r##"{}
+create table if not exists {table} (
+  id {pk},
+  ensg character varying not null,
+  pathway_id character varying not null,
+  pathway character varying not null,
+  top_level_term character varying not null
+);
+create index if not exists "IDX_{table}_ensg" ON {table} {btree} (ensg);
+create index if not exists "IDX_{table}_pathway_id" ON {table} {btree} (pathway_id);
+        "##,
+            match drop_table {
+                true => format!("drop table if exists {table};"),
+                false => String::new(),
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Fields, FromRow, Serialize, Deserialize, Default, Tabled)]
+pub struct TargetPathway {
+    pub id: i32,
+    pub ensg: String,
+    pub pathway_id: String,
+    pub pathway: String,
+    pub top_level_term: String,
+}
+
+#[derive(Fields, Default, Deserialize, Debug)]
+pub struct TargetPathwayForUpdate {
+    pub ensg: Option<String>,
+    pub pathway_id: Option<String>,
+    pub pathway: Option<String>,
+    pub top_level_term: Option<String>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct TargetPathwayFilter {
+    id: Option<OpValsInt64>,
+    ensg: Option<OpValsString>,
+    pathway_id: Option<OpValsString>,
+    pathway: Option<OpValsString>,
+    top_level_term: Option<OpValsString>,
+}
+
+pub struct TargetPathwayBmc;
+
+impl DbBmc for TargetPathwayBmc {
+    const TABLE: &'static str = "target_pathway";
+}
+
+impl TargetPathwayBmc {
+    pub async fn create(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        clone_c: TargetPathwayForCreate,
+    ) -> Result<i32> {
+        base::create::<Self, _>(ctx, mm, clone_c).await
+    }
+    pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<TargetPathway> {
+        base::get::<Self, _>(ctx, mm, id).await
+    }
+
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<TargetPathwayFilter>>,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<TargetPathway>> {
+        base::list::<Self, _, _>(ctx, mm, filters, list_options).await
+    }
+
+    pub async fn update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i32,
+        clone_u: TargetPathwayForUpdate,
+    ) -> Result<()> {
+        base::update::<Self, _>(ctx, mm, id, clone_u).await
+    }
+
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<()> {
+        base::delete::<Self>(ctx, mm, id).await
+    }
+}
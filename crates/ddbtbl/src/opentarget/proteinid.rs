@@ -0,0 +1,139 @@
+use crate::bulk::{self, CopyField, CopyRow};
+use crate::dialect::Dialect;
+use ddbot::model::target::Target as OtTarget;
+use iwf::ctx::Ctx;
+use iwf::sql::base::{self, DbBmc};
+use iwf::sql::ModelManager;
+use iwf::sql::Result;
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, OpValsInt64, OpValsString};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tabled::Tabled;
+
+/// Protein id of target `ensg`, e.g. a UniProt accession.
+#[derive(Fields, Deserialize, Clone, Debug)]
+pub struct TargetProteinIdForCreate {
+    pub ensg: String,
+    pub protein_id: String,
+    pub source: String,
+}
+
+impl TargetProteinIdForCreate {
+    #[must_use]
+    pub fn from_target(target: &OtTarget) -> Vec<Self> {
+        target
+            .protein_ids
+            .iter()
+            .flatten()
+            .map(|e| Self {
+                ensg: target.id.clone(),
+                protein_id: e.id.clone(),
+                source: e.source.clone(),
+            })
+            .collect()
+    }
+}
+
+impl CopyRow for TargetProteinIdForCreate {
+    const COLUMNS: &'static [&'static str] = &["ensg", "protein_id", "source"];
+    fn copy_fields(&self) -> Vec<&dyn CopyField> {
+        vec![&self.ensg, &self.protein_id, &self.source]
+    }
+}
+
+impl TargetProteinIdBmc {
+    pub async fn bulk_import(
+        mm: &ModelManager,
+        entries: Vec<TargetProteinIdForCreate>,
+    ) -> Result<()> {
+        bulk::copy_in(mm, Self::TABLE, &entries).await?;
+        Ok(())
+    }
+    #[must_use]
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
+        let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
+        format!(
+            r##"{}
+create table if not exists {table} (
+  id {pk},
+  ensg character varying not null,
+  protein_id character varying not null,
+  source character varying not null
+);
+create index if not exists "IDX_{table}_ensg" ON {table} {btree} (ensg);
+create index if not exists "IDX_{table}_protein_id" ON {table} {btree} (protein_id);
+        "##,
+            match drop_table {
+                true => format!("drop table if exists {table};"),
+                false => String::new(),
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Fields, FromRow, Serialize, Deserialize, Default, Tabled)]
+pub struct TargetProteinId {
+    pub id: i32,
+    pub ensg: String,
+    pub protein_id: String,
+    pub source: String,
+}
+
+#[derive(Fields, Default, Deserialize, Debug)]
+pub struct TargetProteinIdForUpdate {
+    pub ensg: Option<String>,
+    pub protein_id: Option<String>,
+    pub source: Option<String>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct TargetProteinIdFilter {
+    id: Option<OpValsInt64>,
+    ensg: Option<OpValsString>,
+    protein_id: Option<OpValsString>,
+    source: Option<OpValsString>,
+}
+
+pub struct TargetProteinIdBmc;
+
+impl DbBmc for TargetProteinIdBmc {
+    const TABLE: &'static str = "target_protein_id";
+}
+
+impl TargetProteinIdBmc {
+    pub async fn create(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        clone_c: TargetProteinIdForCreate,
+    ) -> Result<i32> {
+        base::create::<Self, _>(ctx, mm, clone_c).await
+    }
+    pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<TargetProteinId> {
+        base::get::<Self, _>(ctx, mm, id).await
+    }
+
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<TargetProteinIdFilter>>,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<TargetProteinId>> {
+        base::list::<Self, _, _>(ctx, mm, filters, list_options).await
+    }
+
+    pub async fn update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i32,
+        clone_u: TargetProteinIdForUpdate,
+    ) -> Result<()> {
+        base::update::<Self, _>(ctx, mm, id, clone_u).await
+    }
+
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<()> {
+        base::delete::<Self>(ctx, mm, id).await
+    }
+}
@@ -1,7 +1,16 @@
+use crate::bulk::{self, CopyField, CopyRow};
+use crate::dialect::Dialect;
+use crate::opentarget::constraint::{TargetConstraintBmc, TargetConstraintForCreate};
+use crate::opentarget::go::{TargetGoBmc, TargetGoForCreate};
+use crate::opentarget::location::{TargetLocationBmc, TargetLocationForCreate};
+use crate::opentarget::pathway::{TargetPathwayBmc, TargetPathwayForCreate};
+use crate::opentarget::proteinid::{TargetProteinIdBmc, TargetProteinIdForCreate};
+use crate::opentarget::tractability::{TargetTractabilityBmc, TargetTractabilityForCreate};
+use crate::opentarget::transcript::{TargetTranscriptBmc, TargetTranscriptForCreate};
 use camino::Utf8PathBuf;
 use ddbot;
 use iwf::ctx::Ctx;
-use iwf::sql::base::{self, DbBmc, BTREE};
+use iwf::sql::base::{self, DbBmc};
 use iwf::sql::ModelManager;
 use iwf::sql::{Error, Result};
 use log::warn;
@@ -19,47 +28,69 @@ pub struct TargetForCreate {
     pub biotype: String,
 }
 
+impl CopyRow for TargetForCreate {
+    const COLUMNS: &'static [&'static str] = &["ensg", "symbol", "name", "biotype"];
+    fn copy_fields(&self) -> Vec<&dyn CopyField> {
+        vec![&self.ensg, &self.symbol, &self.name, &self.biotype]
+    }
+}
+
 impl TargetBmc {
+    /// Loads the targets and their GO terms, locations, tractability,
+    /// pathways, constraint, protein ids and canonical transcript, linked by
+    /// `ensg`.
     pub async fn parse(mm: &ModelManager, target_bincode_path: &Utf8PathBuf) -> Result<()> {
-        let mut tx = mm.db().begin().await?;
         let targets = ddbot::bincode2targets(target_bincode_path)
             .map_err(|_| Error::Specified("Cannot parse".to_string()))?;
         warn!("N targets parsed: {}", targets.len());
-        for (_name, target) in targets {
-            let c = TargetForCreate {
+        let mut go = vec![];
+        let mut locations = vec![];
+        let mut tractability = vec![];
+        let mut pathways = vec![];
+        let mut constraints = vec![];
+        let mut protein_ids = vec![];
+        let mut transcripts = vec![];
+        let mut entries = vec![];
+        for target in targets.into_values() {
+            go.extend(TargetGoForCreate::from_target(&target));
+            locations.extend(TargetLocationForCreate::from_target(&target));
+            tractability.extend(TargetTractabilityForCreate::from_target(&target));
+            pathways.extend(TargetPathwayForCreate::from_target(&target));
+            constraints.extend(TargetConstraintForCreate::from_target(&target));
+            protein_ids.extend(TargetProteinIdForCreate::from_target(&target));
+            transcripts.extend(TargetTranscriptForCreate::from_target(&target));
+            entries.push(TargetForCreate {
                 ensg: target.id,
                 symbol: target.approved_symbol,
                 name: target.approved_name,
                 biotype: target.biotype,
-            };
-            let _result = sqlx::query(&format!(
-                "INSERT INTO {} (ensg,symbol,name,biotype)
-        VALUES ($1, $2, $3, $4)",
-                Self::TABLE
-            ))
-            .bind(c.ensg)
-            .bind(c.symbol)
-            .bind(c.name)
-            .bind(c.biotype)
-            .execute(&mut *tx)
-            .await;
+            });
         }
-        tx.commit().await?;
+        bulk::copy_in(mm, Self::TABLE, &entries).await?;
+        TargetGoBmc::bulk_import(mm, go).await?;
+        TargetLocationBmc::bulk_import(mm, locations).await?;
+        TargetTractabilityBmc::bulk_import(mm, tractability).await?;
+        TargetPathwayBmc::bulk_import(mm, pathways).await?;
+        TargetConstraintBmc::bulk_import(mm, constraints).await?;
+        TargetProteinIdBmc::bulk_import(mm, protein_ids).await?;
+        TargetTranscriptBmc::bulk_import(mm, transcripts).await?;
         Ok(())
     }
     #[must_use]
-    pub fn get_create_sql(drop_table: bool) -> String {
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
         let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
         format!(
             r##"{}
 create table if not exists {table} (
-  id serial primary key,
+  id {pk},
   ensg character varying not null,
   symbol character varying not null,
   name character varying not null,
   biotype character varying not null
 );
-create index if not exists "IDX_{table}_ensg" ON {table} {BTREE} (ensg);
+create index if not exists "IDX_{table}_ensg" ON {table} {btree} (ensg);
         "##,
             match drop_table {
                 true => format!("drop table if exists {table};"),
@@ -0,0 +1,145 @@
+use crate::bulk::{self, CopyField, CopyRow};
+use crate::dialect::Dialect;
+use ddbot::model::target::Target as OtTarget;
+use iwf::ctx::Ctx;
+use iwf::sql::base::{self, DbBmc};
+use iwf::sql::ModelManager;
+use iwf::sql::Result;
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, OpValsBool, OpValsInt64, OpValsString};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tabled::Tabled;
+
+/// Tractability assessment of target `ensg`; `label` is the assessment id.
+#[derive(Fields, Deserialize, Clone, Debug)]
+pub struct TargetTractabilityForCreate {
+    pub ensg: String,
+    pub label: String,
+    pub modality: String,
+    pub value: bool,
+}
+
+impl TargetTractabilityForCreate {
+    #[must_use]
+    pub fn from_target(target: &OtTarget) -> Vec<Self> {
+        target
+            .tractability
+            .iter()
+            .flatten()
+            .map(|e| Self {
+                ensg: target.id.clone(),
+                label: e.id.clone(),
+                modality: e.modality.clone(),
+                value: e.value,
+            })
+            .collect()
+    }
+}
+
+impl CopyRow for TargetTractabilityForCreate {
+    const COLUMNS: &'static [&'static str] = &["ensg", "label", "modality", "value"];
+    fn copy_fields(&self) -> Vec<&dyn CopyField> {
+        vec![&self.ensg, &self.label, &self.modality, &self.value]
+    }
+}
+
+impl TargetTractabilityBmc {
+    pub async fn bulk_import(
+        mm: &ModelManager,
+        entries: Vec<TargetTractabilityForCreate>,
+    ) -> Result<()> {
+        bulk::copy_in(mm, Self::TABLE, &entries).await?;
+        Ok(())
+    }
+    #[must_use]
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
+        let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
+        format!(
+            r##"{}
+create table if not exists {table} (
+  id {pk},
+  ensg character varying not null,
+  label character varying not null,
+  modality character varying not null,
+  value boolean not null
+);
+create index if not exists "IDX_{table}_ensg" ON {table} {btree} (ensg);
+create index if not exists "IDX_{table}_modality" ON {table} {btree} (modality);
+        "##,
+            match drop_table {
+                true => format!("drop table if exists {table};"),
+                false => String::new(),
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Fields, FromRow, Serialize, Deserialize, Default, Tabled)]
+pub struct TargetTractability {
+    pub id: i32,
+    pub ensg: String,
+    pub label: String,
+    pub modality: String,
+    pub value: bool,
+}
+
+#[derive(Fields, Default, Deserialize, Debug)]
+pub struct TargetTractabilityForUpdate {
+    pub ensg: Option<String>,
+    pub label: Option<String>,
+    pub modality: Option<String>,
+    pub value: Option<bool>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct TargetTractabilityFilter {
+    id: Option<OpValsInt64>,
+    ensg: Option<OpValsString>,
+    label: Option<OpValsString>,
+    modality: Option<OpValsString>,
+    value: Option<OpValsBool>,
+}
+
+pub struct TargetTractabilityBmc;
+
+impl DbBmc for TargetTractabilityBmc {
+    const TABLE: &'static str = "target_tractability";
+}
+
+impl TargetTractabilityBmc {
+    pub async fn create(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        clone_c: TargetTractabilityForCreate,
+    ) -> Result<i32> {
+        base::create::<Self, _>(ctx, mm, clone_c).await
+    }
+    pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<TargetTractability> {
+        base::get::<Self, _>(ctx, mm, id).await
+    }
+
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<TargetTractabilityFilter>>,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<TargetTractability>> {
+        base::list::<Self, _, _>(ctx, mm, filters, list_options).await
+    }
+
+    pub async fn update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i32,
+        clone_u: TargetTractabilityForUpdate,
+    ) -> Result<()> {
+        base::update::<Self, _>(ctx, mm, id, clone_u).await
+    }
+
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<()> {
+        base::delete::<Self>(ctx, mm, id).await
+    }
+}
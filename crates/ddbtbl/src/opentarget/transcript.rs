@@ -0,0 +1,170 @@
+use crate::bulk::{self, CopyField, CopyRow};
+use crate::dialect::Dialect;
+use ddbot::model::target::Target as OtTarget;
+use iwf::ctx::Ctx;
+use iwf::sql::base::{self, DbBmc};
+use iwf::sql::ModelManager;
+use iwf::sql::Result;
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, OpValsInt64, OpValsString};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tabled::Tabled;
+
+/// Canonical transcript of target `ensg`.
+#[derive(Fields, Deserialize, Clone, Debug)]
+pub struct TargetTranscriptForCreate {
+    pub ensg: String,
+    pub transcript_id: String,
+    pub chromosome: String,
+    pub start_pos: i64,
+    pub end_pos: i64,
+    pub strand: String,
+}
+
+impl TargetTranscriptForCreate {
+    #[must_use]
+    pub fn from_target(target: &OtTarget) -> Vec<Self> {
+        target
+            .transcript
+            .iter()
+            .map(|e| Self {
+                ensg: target.id.clone(),
+                transcript_id: e.id.clone(),
+                chromosome: e.chromosome.clone(),
+                start_pos: e.start as i64,
+                end_pos: e.end as i64,
+                strand: e.strand.clone(),
+            })
+            .collect()
+    }
+}
+
+impl CopyRow for TargetTranscriptForCreate {
+    const COLUMNS: &'static [&'static str] = &[
+        "ensg",
+        "transcript_id",
+        "chromosome",
+        "start_pos",
+        "end_pos",
+        "strand",
+    ];
+    fn copy_fields(&self) -> Vec<&dyn CopyField> {
+        vec![
+            &self.ensg,
+            &self.transcript_id,
+            &self.chromosome,
+            &self.start_pos,
+            &self.end_pos,
+            &self.strand,
+        ]
+    }
+}
+
+impl TargetTranscriptBmc {
+    pub async fn bulk_import(
+        mm: &ModelManager,
+        entries: Vec<TargetTranscriptForCreate>,
+    ) -> Result<()> {
+        bulk::copy_in(mm, Self::TABLE, &entries).await?;
+        Ok(())
+    }
+    #[must_use]
+    pub fn get_create_sql(dialect: Dialect, drop_table: bool) -> String {
+        let table = Self::TABLE;
+        let pk = dialect.primary_key();
+        let btree = dialect.index_method();
+        format!(
+            r##"{}
+create table if not exists {table} (
+  id {pk},
+  ensg character varying not null,
+  transcript_id character varying not null,
+  chromosome character varying not null,
+  start_pos bigint not null,
+  end_pos bigint not null,
+  strand character varying not null
+);
+create index if not exists "IDX_{table}_ensg" ON {table} {btree} (ensg);
+create index if not exists "IDX_{table}_transcript_id" ON {table} {btree} (transcript_id);
+        "##,
+            match drop_table {
+                true => format!("drop table if exists {table};"),
+                false => String::new(),
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Fields, FromRow, Serialize, Deserialize, Default, Tabled)]
+pub struct TargetTranscript {
+    pub id: i32,
+    pub ensg: String,
+    pub transcript_id: String,
+    pub chromosome: String,
+    pub start_pos: i64,
+    pub end_pos: i64,
+    pub strand: String,
+}
+
+#[derive(Fields, Default, Deserialize, Debug)]
+pub struct TargetTranscriptForUpdate {
+    pub ensg: Option<String>,
+    pub transcript_id: Option<String>,
+    pub chromosome: Option<String>,
+    pub start_pos: Option<i64>,
+    pub end_pos: Option<i64>,
+    pub strand: Option<String>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct TargetTranscriptFilter {
+    id: Option<OpValsInt64>,
+    ensg: Option<OpValsString>,
+    transcript_id: Option<OpValsString>,
+    chromosome: Option<OpValsString>,
+    start_pos: Option<OpValsInt64>,
+    end_pos: Option<OpValsInt64>,
+    strand: Option<OpValsString>,
+}
+
+pub struct TargetTranscriptBmc;
+
+impl DbBmc for TargetTranscriptBmc {
+    const TABLE: &'static str = "target_transcript";
+}
+
+impl TargetTranscriptBmc {
+    pub async fn create(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        clone_c: TargetTranscriptForCreate,
+    ) -> Result<i32> {
+        base::create::<Self, _>(ctx, mm, clone_c).await
+    }
+    pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<TargetTranscript> {
+        base::get::<Self, _>(ctx, mm, id).await
+    }
+
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<TargetTranscriptFilter>>,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<TargetTranscript>> {
+        base::list::<Self, _, _>(ctx, mm, filters, list_options).await
+    }
+
+    pub async fn update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i32,
+        clone_u: TargetTranscriptForUpdate,
+    ) -> Result<()> {
+        base::update::<Self, _>(ctx, mm, id, clone_u).await
+    }
+
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<()> {
+        base::delete::<Self>(ctx, mm, id).await
+    }
+}
@@ -0,0 +1,264 @@
+//! Queries that take workflow inputs (protein lists, atlas and cluster names).
+//! They are built with sea-query and return the statement together with its
+//! bound values, to be run with `sqlx::query_as_with`. The queries the report
+//! runs take the [`Dialect`] of its database.
+
+use crate::dialect::Dialect;
+use sea_query::{
+    Alias, Asterisk, Expr, Func, Iden, LikeExpr, PostgresQueryBuilder, Query, SelectStatement,
+    SqliteQueryBuilder,
+};
+use sea_query_binder::{SqlxBinder, SqlxValues};
+
+#[derive(Iden)]
+enum Qm {
+    Table,
+    QmatrixId,
+    AcId,
+    AnnId,
+    Value,
+    ScaledValue,
+    ShareValue,
+    Measured,
+}
+
+#[derive(Iden)]
+enum Qmatrix {
+    Table,
+    Id,
+    Name,
+}
+
+#[derive(Iden)]
+enum Ac {
+    Table,
+    Id,
+    Entry,
+}
+
+#[derive(Iden)]
+enum Ann {
+    Table,
+    Id,
+    GlobalGrp,
+    QmatrixType,
+}
+
+#[derive(Iden)]
+enum Umap {
+    Table,
+    Idx,
+    Y1,
+    Y2,
+    Kind,
+}
+
+#[derive(Iden)]
+enum ClusterGsea {
+    Table,
+    Term,
+    Overlap,
+    Genes,
+    Pvalue,
+    CombinedScore,
+    Kind,
+}
+
+fn build(query: &SelectStatement) -> (String, SqlxValues) {
+    build_for(query, Dialect::Postgres)
+}
+
+fn build_for(query: &SelectStatement, dialect: Dialect) -> (String, SqlxValues) {
+    match dialect {
+        Dialect::Postgres => query.build_sqlx(PostgresQueryBuilder),
+        Dialect::Sqlite => query.build_sqlx(SqliteQueryBuilder),
+    }
+}
+
+/// Escapes the `LIKE` wildcards of `prefix` and appends `%`.
+fn like_prefix(prefix: &str) -> LikeExpr {
+    let escaped = prefix
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    LikeExpr::new(format!("{escaped}%")).escape('\\')
+}
+
+/// `ac_id`, `ann_id` and `column` of the qmatrix `matrix`, limited to the
+/// proteins with an accession in `entries` when given.
+fn qm_column(matrix: &str, entries: Option<&[String]>, column: Qm) -> (String, SqlxValues) {
+    let mut query = Query::select();
+    query
+        .column((Qm::Table, Qm::AcId))
+        .column((Qm::Table, Qm::AnnId))
+        .expr_as(Expr::col((Qm::Table, column)), Alias::new("value"))
+        .from(Qm::Table)
+        .inner_join(
+            Qmatrix::Table,
+            Expr::col((Qm::Table, Qm::QmatrixId)).equals((Qmatrix::Table, Qmatrix::Id)),
+        )
+        .and_where(Expr::col((Qmatrix::Table, Qmatrix::Name)).eq(matrix));
+    if let Some(entries) = entries {
+        query
+            .inner_join(
+                Ac::Table,
+                Expr::col((Qm::Table, Qm::AcId)).equals((Ac::Table, Ac::Id)),
+            )
+            .and_where(Expr::col((Ac::Table, Ac::Entry)).is_in(entries.iter().map(String::as_str)));
+    }
+    build(&query)
+}
+
+/// `ac_id`, `ann_id` and the scaled value of the qmatrix `matrix`, in the
+/// scaling selected for it, limited to the proteins with an accession in
+/// `entries` when given.
+#[must_use]
+pub fn qm_values(matrix: &str, entries: Option<&[String]>) -> (String, SqlxValues) {
+    qm_column(matrix, entries, Qm::ScaledValue)
+}
+
+/// `ac_id`, `ann_id` and the share of the protein sum of the qmatrix `matrix`.
+#[must_use]
+pub fn qm_shares(matrix: &str) -> (String, SqlxValues) {
+    qm_column(matrix, None, Qm::ShareValue)
+}
+
+/// Shares of the samples annotated with `qmatrix_type`, the WKDE weights.
+#[must_use]
+pub fn qm_weights(qmatrix_type: &str) -> (String, SqlxValues) {
+    let query = Query::select()
+        .column((Qm::Table, Qm::AcId))
+        .column((Qm::Table, Qm::AnnId))
+        .expr_as(Expr::col((Qm::Table, Qm::ShareValue)), Alias::new("value"))
+        .from(Qm::Table)
+        .inner_join(
+            Ann::Table,
+            Expr::col((Qm::Table, Qm::AnnId)).equals((Ann::Table, Ann::Id)),
+        )
+        .and_where(Expr::col((Ann::Table, Ann::QmatrixType)).eq(qmatrix_type))
+        .to_owned();
+    build(&query)
+}
+
+/// UMAP coordinates of the proteins of `kind` joined with their shares as weights.
+#[must_use]
+pub fn wkde_input(kind: &str) -> (String, SqlxValues) {
+    let query = Query::select()
+        .column((Umap::Table, Umap::Idx))
+        .column((Umap::Table, Umap::Y1))
+        .column((Umap::Table, Umap::Y2))
+        .expr_as(Expr::cust("qm.ann_id::text"), Alias::new("col"))
+        .expr_as(
+            Expr::col((Qm::Table, Qm::ShareValue)),
+            Alias::new("weights"),
+        )
+        .from(Umap::Table)
+        .inner_join(Qm::Table, Expr::cust("umap.idx::int4 = qm.ac_id"))
+        .inner_join(
+            Ann::Table,
+            Expr::col((Qm::Table, Qm::AnnId)).equals((Ann::Table, Ann::Id)),
+        )
+        .and_where(Expr::col((Umap::Table, Umap::Kind)).eq(kind))
+        .and_where(Expr::col((Ann::Table, Ann::QmatrixType)).eq(kind))
+        .to_owned();
+    build(&query)
+}
+
+/// Sample embedding of the UMAP `kind`, with the sample id parsed from `idx`.
+#[must_use]
+pub fn umap_samples(kind: &str, dialect: Dialect) -> (String, SqlxValues) {
+    let query = Query::select()
+        .column((Ann::Table, Ann::Id))
+        .column((Ann::Table, Ann::GlobalGrp))
+        .column((Umap::Table, Umap::Y1))
+        .column((Umap::Table, Umap::Y2))
+        .from(Umap::Table)
+        .inner_join(
+            Ann::Table,
+            Expr::cust(format!("{} = ann.id", dialect.sample_id("umap.idx"))),
+        )
+        .and_where(Expr::col((Umap::Table, Umap::Kind)).eq(kind))
+        .to_owned();
+    build_for(&query, dialect)
+}
+
+/// Enrichment terms of the cluster sets whose kind starts with `prefix`.
+#[must_use]
+pub fn cluster_gsea_terms(prefix: &str, dialect: Dialect) -> (String, SqlxValues) {
+    let query = Query::select()
+        .columns([
+            ClusterGsea::Term,
+            ClusterGsea::Overlap,
+            ClusterGsea::Genes,
+            ClusterGsea::Pvalue,
+            ClusterGsea::CombinedScore,
+        ])
+        .expr_as(Expr::col(ClusterGsea::Kind), Alias::new("cluster_id"))
+        .from(ClusterGsea::Table)
+        .and_where(Expr::col(ClusterGsea::Kind).like(like_prefix(prefix)))
+        .to_owned();
+    build_for(&query, dialect)
+}
+
+/// Shares of `entries` in the `global_grp` samples of the qmatrices
+/// `matrices`.
+#[must_use]
+pub fn group_values(
+    entries: &[String],
+    global_grp: &str,
+    matrices: &[&str],
+    dialect: Dialect,
+) -> (String, SqlxValues) {
+    let query = Query::select()
+        .column((Qmatrix::Table, Qmatrix::Name))
+        .column((Ac::Table, Ac::Entry))
+        .column((Ann::Table, Ann::GlobalGrp))
+        .column((Qm::Table, Qm::ShareValue))
+        .from(Qm::Table)
+        .inner_join(
+            Ac::Table,
+            Expr::col((Qm::Table, Qm::AcId)).equals((Ac::Table, Ac::Id)),
+        )
+        .inner_join(
+            Ann::Table,
+            Expr::col((Qm::Table, Qm::AnnId)).equals((Ann::Table, Ann::Id)),
+        )
+        .inner_join(
+            Qmatrix::Table,
+            Expr::col((Qm::Table, Qm::QmatrixId)).equals((Qmatrix::Table, Qmatrix::Id)),
+        )
+        .and_where(Expr::col((Ac::Table, Ac::Entry)).is_in(entries.iter().map(String::as_str)))
+        .and_where(Expr::col((Ann::Table, Ann::GlobalGrp)).eq(global_grp))
+        .and_where(Expr::col((Qmatrix::Table, Qmatrix::Name)).is_in(matrices.iter().copied()))
+        .to_owned();
+    build_for(&query, dialect)
+}
+
+/// Mean and count of the measured values of the qmatrix `matrix` per protein
+/// and `global_grp`.
+#[must_use]
+pub fn group_means(matrix: &str) -> (String, SqlxValues) {
+    let query = Query::select()
+        .column((Qm::Table, Qm::AcId))
+        .column((Ann::Table, Ann::GlobalGrp))
+        .expr_as(
+            Func::avg(Expr::col((Qm::Table, Qm::Value))),
+            Alias::new("value"),
+        )
+        .expr_as(Func::count(Expr::col(Asterisk)), Alias::new("n"))
+        .from(Qm::Table)
+        .inner_join(
+            Ann::Table,
+            Expr::col((Qm::Table, Qm::AnnId)).equals((Ann::Table, Ann::Id)),
+        )
+        .inner_join(
+            Qmatrix::Table,
+            Expr::col((Qm::Table, Qm::QmatrixId)).equals((Qmatrix::Table, Qmatrix::Id)),
+        )
+        .and_where(Expr::col((Qmatrix::Table, Qmatrix::Name)).eq(matrix))
+        .and_where(Expr::col((Qm::Table, Qm::Measured)).eq(true))
+        .group_by_col((Qm::Table, Qm::AcId))
+        .group_by_col((Ann::Table, Ann::GlobalGrp))
+        .to_owned();
+    build(&query)
+}
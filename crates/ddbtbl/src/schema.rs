@@ -0,0 +1,132 @@
+//! Versioned schema migrations.
+//!
+//! Every applied migration is recorded in `schema_version`. A database without
+//! that table is initialised with all migrations; an older database is only
+//! upgraded in place when asked to, and a database written by a newer build is
+//! refused.
+
+use crate::gls::ac::AcBmc;
+use iwf::sql::base::DbBmc;
+use iwf::sql::ModelManager;
+use iwf::sql::{Error, Result};
+use log::info;
+use sqlx::Executor;
+
+/// Statements moving the schema from `version - 1` to `version`.
+pub struct Migration {
+    pub version: i32,
+    pub description: &'static str,
+    pub statements: Vec<String>,
+}
+
+pub struct SchemaVersionBmc;
+
+impl DbBmc for SchemaVersionBmc {
+    const TABLE: &'static str = "schema_version";
+}
+
+impl SchemaVersionBmc {
+    #[must_use]
+    pub fn get_create_sql() -> String {
+        let table = Self::TABLE;
+        format!(
+            r##"
+create table if not exists {table} (
+  version integer primary key,
+  description character varying not null,
+  applied_at timestamp with time zone not null default current_timestamp
+);
+        "##
+        )
+    }
+
+    async fn exists(mm: &ModelManager, table: &str) -> Result<bool> {
+        let (exists,): (bool,) = sqlx::query_as("select to_regclass($1) is not null")
+            .bind(table)
+            .fetch_one(mm.db())
+            .await?;
+        Ok(exists)
+    }
+
+    /// Applied schema version, 0 for an unversioned database.
+    pub async fn current(mm: &ModelManager) -> Result<i32> {
+        if !Self::exists(mm, Self::TABLE).await? {
+            return Ok(0);
+        }
+        let (version,): (i32,) = sqlx::query_as(&format!(
+            "select coalesce(max(version), 0) from {}",
+            Self::TABLE
+        ))
+        .fetch_one(mm.db())
+        .await?;
+        Ok(version)
+    }
+
+    /// Brings the database to the last of `migrations` and returns its version.
+    /// A database behind that version is only upgraded when `upgrade` is set.
+    pub async fn migrate(
+        mm: &ModelManager,
+        migrations: &[Migration],
+        upgrade: bool,
+    ) -> Result<i32> {
+        let latest = migrations.last().map_or(0, |e| e.version);
+        let current = Self::current(mm).await?;
+        if current > latest {
+            return Err(Error::Specified(format!(
+                "Database schema version {current} is newer than version {latest} of this build"
+            )));
+        }
+        if current == latest {
+            return Ok(current);
+        }
+        if current == 0 && Self::exists(mm, AcBmc::TABLE).await? {
+            return Err(Error::Specified(String::from(
+                "Database predates schema versioning, recreate it",
+            )));
+        }
+        if current > 0 && !upgrade {
+            return Err(Error::Specified(format!(
+                "Database schema version {current} does not match version {latest}, enable migrate to upgrade it"
+            )));
+        }
+
+        mm.db().execute(Self::get_create_sql().as_str()).await?;
+        for migration in migrations.iter().filter(|e| e.version > current) {
+            let mut tx = mm.db().begin().await?;
+            for statement in &migration.statements {
+                (&mut *tx).execute(statement.as_str()).await?;
+            }
+            sqlx::query(&format!(
+                "insert into {} (version, description) values ($1, $2)",
+                Self::TABLE
+            ))
+            .bind(migration.version)
+            .bind(migration.description)
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+            info!(
+                "Applied schema migration {}: {}",
+                migration.version, migration.description
+            );
+        }
+        Ok(latest)
+    }
+}
+
+/// Empties `tables` before they are reloaded.
+pub async fn truncate(mm: &ModelManager, tables: &[&str]) -> Result<()> {
+    sqlx::query(&format!("truncate {} restart identity", tables.join(",")))
+        .execute(mm.db())
+        .await?;
+    Ok(())
+}
+
+/// Deletes the rows of `table` whose `kind` is one of `kinds`.
+pub async fn delete_kinds(mm: &ModelManager, table: &str, kinds: &[String]) -> Result<()> {
+    sqlx::query(&format!("delete from {table} where kind = any($1)"))
+        .bind(kinds)
+        .execute(mm.db())
+        .await?;
+    Ok(())
+}
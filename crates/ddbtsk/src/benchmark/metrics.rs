@@ -0,0 +1,93 @@
+//! Multi-label comparison of predicted tissue labels with a reference list.
+//! Only proteins present in both the reference and the predictions are
+//! scored; every expected/predicted tissue pair of a protein adds one count to
+//! the confusion matrix, an empty prediction counts as `none`.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::hash::Hash;
+
+pub const NO_PREDICTION: &str = "none";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TissueScore {
+    pub tissue: String,
+    pub tp: usize,
+    pub fp: usize,
+    pub fn_: usize,
+    pub precision: f64,
+    pub recall: f64,
+    pub f1: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfusionCell {
+    pub expected: String,
+    pub predicted: String,
+    pub n: usize,
+}
+
+/// Per tissue scores, sorted by tissue, and the confusion matrix cells.
+/// Tissues that are never expected are scored as well, so false positive
+/// labels such as `common` stay visible.
+pub fn score<K: Eq + Hash>(
+    expected: &HashMap<K, BTreeSet<String>>,
+    predicted: &HashMap<K, BTreeSet<String>>,
+) -> (Vec<TissueScore>, Vec<ConfusionCell>) {
+    let mut counts: BTreeMap<&str, (usize, usize, usize)> = BTreeMap::new();
+    let mut confusion: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+    for (key, exp) in expected {
+        let Some(pred) = predicted.get(key) else {
+            continue;
+        };
+        for tissue in exp.union(pred) {
+            let count = counts.entry(tissue).or_default();
+            match (exp.contains(tissue), pred.contains(tissue)) {
+                (true, true) => count.0 += 1,
+                (false, true) => count.1 += 1,
+                (true, false) => count.2 += 1,
+                (false, false) => {}
+            }
+        }
+        for e in exp {
+            if pred.is_empty() {
+                *confusion.entry((e, NO_PREDICTION)).or_insert(0) += 1;
+            }
+            for p in pred {
+                *confusion.entry((e, p)).or_insert(0) += 1;
+            }
+        }
+    }
+    let ratio = |a: usize, b: usize| match b {
+        0 => 0.0,
+        _ => a as f64 / b as f64,
+    };
+    let scores = counts
+        .into_iter()
+        .map(|(tissue, (tp, fp, fn_))| {
+            let precision = ratio(tp, tp + fp);
+            let recall = ratio(tp, tp + fn_);
+            let f1 = match precision + recall > 0.0 {
+                true => 2.0 * precision * recall / (precision + recall),
+                false => 0.0,
+            };
+            TissueScore {
+                tissue: tissue.to_string(),
+                tp,
+                fp,
+                fn_,
+                precision,
+                recall,
+                f1,
+            }
+        })
+        .collect();
+    let confusion = confusion
+        .into_iter()
+        .map(|((expected, predicted), n)| ConfusionCell {
+            expected: expected.to_string(),
+            predicted: predicted.to_string(),
+            n,
+        })
+        .collect();
+    (scores, confusion)
+}
@@ -0,0 +1,57 @@
+//! Cache of external tool outputs. A run is keyed on an FNV-1a hash of its
+//! input files and parameters; the key is stored next to the output as
+//! `<output>.hash` and an output is reused only while the key matches.
+
+use crate::error::Result;
+use camino::{Utf8Path, Utf8PathBuf};
+use std::io::Read;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn update(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+}
+
+fn sidecar(output: &Utf8Path) -> Utf8PathBuf {
+    Utf8PathBuf::from(format!("{output}.hash"))
+}
+
+/// Hash of the content of `inputs` and of `params`.
+pub fn key(inputs: &[&Utf8Path], params: &str) -> Result<String> {
+    let mut hash = Fnv1a(FNV_OFFSET);
+    let mut buffer = vec![0; 64 * 1024];
+    for input in inputs {
+        let mut file = std::fs::File::open(input)?;
+        loop {
+            let n = file.read(&mut buffer)?;
+            if n == 0 {
+                break;
+            }
+            hash.update(&buffer[..n]);
+        }
+        hash.update(&[0xff]);
+    }
+    hash.update(params.as_bytes());
+    Ok(format!("{:016x}", hash.0))
+}
+
+/// Whether `output` exists and was computed for `key`.
+#[must_use]
+pub fn is_valid(output: &Utf8Path, key: &str) -> bool {
+    output.is_file()
+        && std::fs::read_to_string(sidecar(output)).is_ok_and(|stored| stored.trim() == key)
+}
+
+/// Records that `output` was computed for `key`.
+pub fn store(output: &Utf8Path, key: &str) -> Result<()> {
+    std::fs::write(sidecar(output), key)?;
+    Ok(())
+}
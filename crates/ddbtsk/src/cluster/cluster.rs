@@ -1,8 +1,12 @@
-use crate::error::Result;
+use crate::cache;
+use crate::cluster::hdbscan::{self, HdbscanParams};
+use crate::cluster::umap::{self, Metric, UmapParams};
+use crate::error::{Error, Result};
 use camino::Utf8PathBuf;
 use iwf::{IwfResult, IwfTask};
 use iwfmacros::Iwfargs;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
 
 #[derive(Iwfargs, Clone, Debug)]
 #[iwfarg(executable = "umap_cluster.py")]
@@ -40,7 +44,7 @@ pub struct ClusterInput {
     pub value: f64,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct ClusterOutput {
     pub idx: String,
     pub y1: f64,
@@ -68,6 +72,25 @@ impl Default for ClusterTask {
 }
 
 impl ClusterTask {
+    /// Parameters of the atlas embeddings, shared by the atlas clustering and
+    /// its label bootstrap.
+    #[must_use]
+    pub fn atlas() -> Self {
+        Self {
+            min_cluster_size: 20,
+            min_dist: 0.01,
+            metric: "correlation".into(),
+            ..Default::default()
+        }
+    }
+
+    /// Cache key of the written input, the parameters and the engine.
+    pub fn cache_key(&self, native: bool) -> Result<String> {
+        cache::key(
+            &[self.input.as_path()],
+            &format!("{self:?} native={native}"),
+        )
+    }
     pub fn write_input(&self, items: &Vec<ClusterInput>) -> Result<()> {
         let mut wtr = csv::WriterBuilder::new()
             .delimiter(b'\t')
@@ -88,6 +111,76 @@ impl ClusterTask {
         }
         Ok(items)
     }
+    /// Runs UMAP and HDBSCAN in-process instead of `umap_cluster.py` and writes
+    /// the same output file, so `read_output` works for both engines.
+    pub fn run_native(&self, items: &[ClusterInput]) -> Result<()> {
+        let mut wtr = csv::WriterBuilder::new()
+            .delimiter(b'\t')
+            .from_path(&self.output)?;
+        for item in self.cluster_native(items)? {
+            wtr.serialize(item)?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+    /// Returns the in-process embedding and cluster labels without touching
+    /// the file system.
+    pub fn cluster_native(&self, items: &[ClusterInput]) -> Result<Vec<ClusterOutput>> {
+        let mut rows: Vec<&str> = vec![];
+        let mut row_map = HashMap::new();
+        let cols: BTreeSet<&str> = items.iter().map(|e| e.col.as_str()).collect();
+        let col_map: HashMap<&str, usize> = cols.iter().enumerate().map(|(i, c)| (*c, i)).collect();
+        let mut data: Vec<Vec<f64>> = vec![];
+        for item in items {
+            let row = *row_map.entry(item.idx.as_str()).or_insert_with(|| {
+                rows.push(item.idx.as_str());
+                data.push(vec![0.0; cols.len()]);
+                rows.len() - 1
+            });
+            data[row][col_map[item.col.as_str()]] = item.value;
+        }
+        let umap_params = UmapParams {
+            n_neighbors: usize::try_from(self.n_neighbors).map_err(|_| {
+                Error::Specified(format!("Invalid n_neighbors: {}", self.n_neighbors))
+            })?,
+            n_components: usize::try_from(self.n_components).map_err(|_| {
+                Error::Specified(format!("Invalid n_components: {}", self.n_components))
+            })?,
+            min_dist: self.min_dist,
+            metric: Metric::from_name(&self.metric)?,
+            random_state: self.random_state as u64,
+            ..Default::default()
+        };
+        if umap_params.n_components < 2 {
+            return Err(Error::Specified(format!(
+                "Need at least two components for y1/y2, got {}",
+                self.n_components
+            )));
+        }
+        let embedding = umap::embed(&data, &umap_params)?;
+        let hdbscan_params = HdbscanParams {
+            min_cluster_size: usize::try_from(self.min_cluster_size).map_err(|_| {
+                Error::Specified(format!(
+                    "Invalid min_cluster_size: {}",
+                    self.min_cluster_size
+                ))
+            })?,
+            ..Default::default()
+        };
+        let labels = hdbscan::cluster(&embedding, &hdbscan_params)?;
+
+        Ok(rows
+            .iter()
+            .zip(&embedding)
+            .zip(labels)
+            .map(|((idx, y), labels)| ClusterOutput {
+                idx: idx.to_string(),
+                y1: y[0],
+                y2: y[1],
+                labels,
+            })
+            .collect())
+    }
 }
 
 impl IwfTask for ClusterTask {}
@@ -0,0 +1,284 @@
+//! In-process HDBSCAN following the hdbscan package: mutual reachability
+//! minimum spanning tree, condensed cluster tree and excess of mass selection.
+//! Noise points are labelled -1.
+
+use crate::error::{Error, Result};
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone)]
+pub struct HdbscanParams {
+    pub min_cluster_size: usize,
+    /// Defaults to `min_cluster_size` like the python implementation.
+    pub min_samples: Option<usize>,
+}
+
+impl Default for HdbscanParams {
+    fn default() -> Self {
+        Self {
+            min_cluster_size: 20,
+            min_samples: None,
+        }
+    }
+}
+
+struct CondensedRow {
+    parent: usize,
+    child: usize,
+    lambda: f64,
+    size: usize,
+}
+
+/// Returns one cluster label per input row using euclidean distances.
+pub fn cluster(data: &[Vec<f64>], params: &HdbscanParams) -> Result<Vec<i32>> {
+    if params.min_cluster_size < 2 {
+        return Err(Error::Specified(format!(
+            "HDBSCAN needs min_cluster_size >= 2, got {}",
+            params.min_cluster_size
+        )));
+    }
+    let n = data.len();
+    if n < params.min_cluster_size {
+        return Ok(vec![-1; n]);
+    }
+    let min_samples = params
+        .min_samples
+        .unwrap_or(params.min_cluster_size)
+        .clamp(1, n);
+    let core = core_distances(data, min_samples);
+    let mst = minimum_spanning_tree(data, &core);
+    let linkage = single_linkage(n, mst);
+    let condensed = condense_tree(n, &linkage, params.min_cluster_size);
+    let selected = select_clusters(n, &condensed);
+    Ok(label_points(n, &condensed, &selected))
+}
+
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// Distance to the `min_samples`-th nearest neighbour, counting the point itself.
+fn core_distances(data: &[Vec<f64>], min_samples: usize) -> Vec<f64> {
+    data.iter()
+        .map(|row| {
+            let mut dists: Vec<f64> = data.iter().map(|other| distance(row, other)).collect();
+            let (_, kth, _) = dists.select_nth_unstable_by(min_samples - 1, f64::total_cmp);
+            *kth
+        })
+        .collect()
+}
+
+/// Prim's algorithm on the dense mutual reachability graph, edges sorted by weight.
+fn minimum_spanning_tree(data: &[Vec<f64>], core: &[f64]) -> Vec<(usize, usize, f64)> {
+    let n = data.len();
+    let mut in_tree = vec![false; n];
+    let mut best = vec![f64::INFINITY; n];
+    let mut source = vec![0; n];
+    let mut edges = Vec::with_capacity(n - 1);
+    let mut current = 0;
+    in_tree[current] = true;
+    for _ in 1..n {
+        let mut next = None;
+        for j in 0..n {
+            if in_tree[j] {
+                continue;
+            }
+            let reach = distance(&data[current], &data[j])
+                .max(core[current])
+                .max(core[j]);
+            if reach < best[j] {
+                best[j] = reach;
+                source[j] = current;
+            }
+            next = match next {
+                Some(k) if best[k] <= best[j] => Some(k),
+                _ => Some(j),
+            };
+        }
+        let Some(j) = next else { break };
+        in_tree[j] = true;
+        edges.push((source[j], j, best[j]));
+        current = j;
+    }
+    edges.sort_by(|x, y| x.2.total_cmp(&y.2));
+    edges
+}
+
+/// Merges the sorted MST edges into a dendrogram; node `n + i` is the `i`-th merge
+/// and is stored as `(left, right, distance, size)`.
+fn single_linkage(n: usize, mst: Vec<(usize, usize, f64)>) -> Vec<(usize, usize, f64, usize)> {
+    let mut parent: Vec<usize> = (0..2 * n - 1).collect();
+    let mut size = vec![1; 2 * n - 1];
+    fn find(parent: &mut [usize], mut x: usize) -> usize {
+        while parent[x] != x {
+            parent[x] = parent[parent[x]];
+            x = parent[x];
+        }
+        x
+    }
+    let mut linkage = Vec::with_capacity(n - 1);
+    for (a, b, dist) in mst {
+        let (ra, rb) = (find(&mut parent, a), find(&mut parent, b));
+        let node = n + linkage.len();
+        parent[ra] = node;
+        parent[rb] = node;
+        size[node] = size[ra] + size[rb];
+        linkage.push((ra, rb, dist, size[node]));
+    }
+    linkage
+}
+
+fn condense_tree(
+    n: usize,
+    linkage: &[(usize, usize, f64, usize)],
+    min_cluster_size: usize,
+) -> Vec<CondensedRow> {
+    let root = n + linkage.len() - 1;
+    let node_size = |node: usize| match node < n {
+        true => 1,
+        false => linkage[node - n].3,
+    };
+    let leaves = |node: usize| -> Vec<usize> {
+        let mut out = vec![];
+        let mut queue = VecDeque::from([node]);
+        while let Some(x) = queue.pop_front() {
+            match x < n {
+                true => out.push(x),
+                false => {
+                    queue.push_back(linkage[x - n].0);
+                    queue.push_back(linkage[x - n].1);
+                }
+            }
+        }
+        out
+    };
+
+    let mut relabel = vec![0; root + 1];
+    relabel[root] = n;
+    let mut next_label = n + 1;
+    let mut rows = vec![];
+    let mut queue = VecDeque::from([root]);
+    while let Some(node) = queue.pop_front() {
+        if node < n {
+            continue;
+        }
+        let (left, right, dist, _) = linkage[node - n];
+        let lambda = match dist > 0.0 {
+            true => 1.0 / dist,
+            false => f64::INFINITY,
+        };
+        let parent = relabel[node];
+        let (left_size, right_size) = (node_size(left), node_size(right));
+        match (
+            left_size >= min_cluster_size,
+            right_size >= min_cluster_size,
+        ) {
+            (true, true) => {
+                for (child, size) in [(left, left_size), (right, right_size)] {
+                    relabel[child] = next_label;
+                    rows.push(CondensedRow {
+                        parent,
+                        child: next_label,
+                        lambda,
+                        size,
+                    });
+                    next_label += 1;
+                    queue.push_back(child);
+                }
+            }
+            (left_big, right_big) => {
+                for (child, big) in [(left, left_big), (right, right_big)] {
+                    match big {
+                        true => {
+                            relabel[child] = parent;
+                            queue.push_back(child);
+                        }
+                        false => rows.extend(leaves(child).into_iter().map(|p| CondensedRow {
+                            parent,
+                            child: p,
+                            lambda,
+                            size: 1,
+                        })),
+                    }
+                }
+            }
+        }
+    }
+    rows
+}
+
+/// Excess of mass selection; the root is never selected so a single blob is noise.
+fn select_clusters(n: usize, condensed: &[CondensedRow]) -> Vec<usize> {
+    let n_clusters = condensed
+        .iter()
+        .map(|r| r.parent.max(r.child))
+        .max()
+        .map_or(1, |m| m.max(n) - n + 1);
+    // Duplicate points merge at distance zero; cap them at the largest finite lambda.
+    let max_lambda = condensed
+        .iter()
+        .map(|r| r.lambda)
+        .filter(|l| l.is_finite())
+        .fold(0.0, f64::max);
+    let mut birth = vec![0.0; n_clusters];
+    let mut stability = vec![0.0; n_clusters];
+    let mut children: Vec<Vec<usize>> = vec![vec![]; n_clusters];
+    for row in condensed.iter().filter(|r| r.child >= n) {
+        birth[row.child - n] = row.lambda.min(max_lambda);
+        children[row.parent - n].push(row.child - n);
+    }
+    for row in condensed {
+        let lambda = row.lambda.min(max_lambda);
+        stability[row.parent - n] += (lambda - birth[row.parent - n]) * row.size as f64;
+    }
+
+    let mut is_cluster = vec![true; n_clusters];
+    is_cluster[0] = false;
+    for c in (1..n_clusters).rev() {
+        let subtree: f64 = children[c].iter().map(|k| stability[*k]).sum();
+        match subtree > stability[c] {
+            true => {
+                is_cluster[c] = false;
+                stability[c] = subtree;
+            }
+            false => {
+                let mut stack = children[c].clone();
+                while let Some(k) = stack.pop() {
+                    is_cluster[k] = false;
+                    stack.extend(children[k].iter().copied());
+                }
+            }
+        }
+    }
+    (0..n_clusters)
+        .filter(|c| is_cluster[*c])
+        .map(|c| c + n)
+        .collect()
+}
+
+fn label_points(n: usize, condensed: &[CondensedRow], selected: &[usize]) -> Vec<i32> {
+    let max_node = condensed
+        .iter()
+        .map(|r| r.child.max(r.parent))
+        .max()
+        .unwrap_or(n);
+    let mut parent_of = vec![None; max_node + 1];
+    for row in condensed {
+        parent_of[row.child] = Some(row.parent);
+    }
+    (0..n)
+        .map(|point| {
+            let mut node = parent_of[point];
+            while let Some(c) = node {
+                if let Ok(label) = selected.binary_search(&c) {
+                    return label as i32;
+                }
+                node = parent_of[c];
+            }
+            -1
+        })
+        .collect()
+}
@@ -0,0 +1,320 @@
+//! In-process weighted kernel density labelling of a 2D embedding.
+//!
+//! Every column (tissue or cell type) gets a weighted Gaussian density on a
+//! square pixel grid spanning the embedding. Each pixel is labelled with the
+//! columns that together make up `fraction` of its density; pixels without
+//! enough signal become `none` and pixels with too many labels `common`.
+//! Points inherit the label of the pixel they fall into.
+
+use crate::error::{Error, Result};
+use std::collections::{BTreeMap, HashSet};
+
+pub const NONE_LABEL: &str = "none";
+pub const COMMON_LABEL: &str = "common";
+
+#[derive(Debug, Clone)]
+pub struct KdeParams {
+    /// Cumulative density share a pixel label has to explain.
+    pub fraction: f64,
+    /// Kernel standard deviation relative to the embedding extent.
+    pub bandwidth: f64,
+    /// Minimal normalised density of the strongest column for a pixel to get a label.
+    pub min_value: f64,
+    /// Minimal density share of a column to become part of a pixel label.
+    pub no_class_criteria: f64,
+    /// Pixels needing more columns than this are labelled `common`.
+    pub max_n_labels: usize,
+    pub grid_size: usize,
+}
+
+impl Default for KdeParams {
+    fn default() -> Self {
+        Self {
+            fraction: 0.70,
+            bandwidth: 0.10,
+            min_value: 0.10,
+            no_class_criteria: 0.05,
+            max_n_labels: 2,
+            grid_size: 100,
+        }
+    }
+}
+
+/// A weighted observation of `column` at embedding position `(y1, y2)`.
+#[derive(Debug, Clone)]
+pub struct KdePoint<'a> {
+    pub idx: &'a str,
+    pub column: &'a str,
+    pub y1: f64,
+    pub y2: f64,
+    pub weight: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PixelLabel {
+    pub label: String,
+    pub n_labels: usize,
+    pub n_pixels: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointLabel {
+    pub idx: String,
+    pub y1: f64,
+    pub y2: f64,
+    pub label: String,
+    pub c1: usize,
+    pub c2: usize,
+    /// Density share of the strongest and second strongest column in the pixel.
+    pub v1: f64,
+    pub v2: f64,
+}
+
+struct Grid {
+    min: (f64, f64),
+    step: (f64, f64),
+    size: usize,
+}
+
+impl Grid {
+    fn new(points: &[KdePoint], size: usize) -> Self {
+        let (mut x0, mut x1, mut y0, mut y1) = (f64::MAX, f64::MIN, f64::MAX, f64::MIN);
+        for p in points {
+            x0 = x0.min(p.y1);
+            x1 = x1.max(p.y1);
+            y0 = y0.min(p.y2);
+            y1 = y1.max(p.y2);
+        }
+        let pad = |lo: f64, hi: f64| ((hi - lo) * 0.05).max(f64::EPSILON);
+        let (px, py) = (pad(x0, x1), pad(y0, y1));
+        let (x0, x1, y0, y1) = (x0 - px, x1 + px, y0 - py, y1 + py);
+        Self {
+            min: (x0, y0),
+            step: ((x1 - x0) / size as f64, (y1 - y0) / size as f64),
+            size,
+        }
+    }
+
+    fn cell(&self, y1: f64, y2: f64) -> (usize, usize) {
+        let c = |v: f64, min: f64, step: f64| (((v - min) / step) as usize).min(self.size - 1);
+        (
+            c(y1, self.min.0, self.step.0),
+            c(y2, self.min.1, self.step.1),
+        )
+    }
+}
+
+/// Returns the pixel label summary and one label per distinct point `idx`.
+pub fn label(
+    points: &[KdePoint],
+    params: &KdeParams,
+) -> Result<(Vec<PixelLabel>, Vec<PointLabel>)> {
+    if points.is_empty() {
+        return Ok((vec![], vec![]));
+    }
+    if params.grid_size < 2 || params.bandwidth <= 0.0 {
+        return Err(Error::Specified(format!(
+            "Invalid KDE grid size {} or bandwidth {}",
+            params.grid_size, params.bandwidth
+        )));
+    }
+    let grid = Grid::new(points, params.grid_size);
+    let n = grid.size;
+
+    let mut columns: BTreeMap<&str, Vec<f64>> = BTreeMap::new();
+    for p in points {
+        let (c1, c2) = grid.cell(p.y1, p.y2);
+        columns.entry(p.column).or_insert_with(|| vec![0.0; n * n])[c1 * n + c2] +=
+            p.weight.max(0.0);
+    }
+    let kernel = gaussian_kernel(params.bandwidth * n as f64);
+    let names: Vec<&str> = columns.keys().copied().collect();
+    let densities: Vec<Vec<f64>> = columns
+        .into_values()
+        .map(|hist| {
+            let mut density = smooth(&hist, n, &kernel);
+            let max = density.iter().copied().fold(0.0, f64::max);
+            if max > 0.0 {
+                density.iter_mut().for_each(|v| *v /= max);
+            }
+            density
+        })
+        .collect();
+
+    let mut pixel_labels = Vec::with_capacity(n * n);
+    for pixel in 0..n * n {
+        let mut values: Vec<(f64, usize)> = densities
+            .iter()
+            .enumerate()
+            .map(|(i, d)| (d[pixel], i))
+            .collect();
+        values.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+        pixel_labels.push(classify(&values, &names, params));
+    }
+
+    let mut summary: BTreeMap<&str, PixelLabel> = BTreeMap::new();
+    for (label, n_labels, _, _) in &pixel_labels {
+        summary
+            .entry(label)
+            .or_insert_with(|| PixelLabel {
+                label: label.clone(),
+                n_labels: *n_labels,
+                n_pixels: 0,
+            })
+            .n_pixels += 1;
+    }
+
+    let mut seen = HashSet::new();
+    let mut assignments = vec![];
+    for p in points {
+        if !seen.insert(p.idx) {
+            continue;
+        }
+        let (c1, c2) = grid.cell(p.y1, p.y2);
+        let (label, _, v1, v2) = &pixel_labels[c1 * n + c2];
+        assignments.push(PointLabel {
+            idx: p.idx.to_string(),
+            y1: p.y1,
+            y2: p.y2,
+            label: label.clone(),
+            c1,
+            c2,
+            v1: *v1,
+            v2: *v2,
+        });
+    }
+    Ok((summary.into_values().collect(), assignments))
+}
+
+/// `values` are the normalised densities of one pixel, strongest first.
+/// Returns the label, its number of columns and the two strongest shares.
+fn classify(
+    values: &[(f64, usize)],
+    names: &[&str],
+    params: &KdeParams,
+) -> (String, usize, f64, f64) {
+    let total: f64 = values.iter().map(|v| v.0).sum();
+    if values.is_empty() || values[0].0 < params.min_value || total <= 0.0 {
+        return (NONE_LABEL.into(), 0, 0.0, 0.0);
+    }
+    let share = |i: usize| values.get(i).map_or(0.0, |v| v.0 / total);
+    let (v1, v2) = (share(0), share(1));
+    let mut selected = vec![];
+    let mut cumulative = 0.0;
+    for (value, column) in values.iter().take(params.max_n_labels) {
+        if value / total < params.no_class_criteria {
+            break;
+        }
+        selected.push(names[*column]);
+        cumulative += value / total;
+        if cumulative >= params.fraction {
+            selected.sort_unstable();
+            return (selected.join("."), selected.len(), v1, v2);
+        }
+    }
+    (COMMON_LABEL.into(), 0, v1, v2)
+}
+
+fn gaussian_kernel(sigma: f64) -> Vec<f64> {
+    let sigma = sigma.max(0.5);
+    let radius = (3.0 * sigma).ceil() as i64;
+    (-radius..=radius)
+        .map(|i| (-0.5 * (i as f64 / sigma).powi(2)).exp())
+        .collect()
+}
+
+/// Separable convolution of the `n x n` histogram with the kernel.
+fn smooth(hist: &[f64], n: usize, kernel: &[f64]) -> Vec<f64> {
+    let radius = (kernel.len() / 2) as i64;
+    let convolve = |src: &[f64], stride: (usize, usize)| -> Vec<f64> {
+        let mut out = vec![0.0; n * n];
+        for a in 0..n {
+            for b in 0..n {
+                let mut acc = 0.0;
+                for (k, w) in kernel.iter().enumerate() {
+                    let j = b as i64 + k as i64 - radius;
+                    if j >= 0 && (j as usize) < n {
+                        acc += w * src[a * stride.0 + j as usize * stride.1];
+                    }
+                }
+                out[a * stride.0 + b * stride.1] = acc;
+            }
+        }
+        out
+    };
+    let rows = convolve(hist, (n, 1));
+    convolve(&rows, (1, n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAMES: [&str; 3] = ["liver", "brain", "heart"];
+
+    fn label_of(values: &[(f64, usize)], params: &KdeParams) -> (String, usize) {
+        let (label, n_labels, _, _) = classify(values, &NAMES, params);
+        (label, n_labels)
+    }
+
+    #[test]
+    fn classify_single_dominant_column() {
+        let (label, n_labels, v1, v2) =
+            classify(&[(1.0, 0), (0.05, 1)], &NAMES, &KdeParams::default());
+        assert_eq!((label.as_str(), n_labels), ("liver", 1));
+        assert!((v1 - 1.0 / 1.05).abs() < 1e-12);
+        assert!((v2 - 0.05 / 1.05).abs() < 1e-12);
+    }
+
+    #[test]
+    fn classify_joins_labels_sorted() {
+        let values = [(1.0, 0), (0.9, 1), (0.1, 2)];
+        assert_eq!(
+            label_of(&values, &KdeParams::default()),
+            ("brain.liver".into(), 2)
+        );
+    }
+
+    #[test]
+    fn classify_caps_labels_at_max_n_labels() {
+        let values = [(1.0, 0), (1.0, 1), (1.0, 2)];
+        assert_eq!(
+            label_of(&values, &KdeParams::default()),
+            (COMMON_LABEL.into(), 0)
+        );
+        let params = KdeParams {
+            max_n_labels: 3,
+            ..Default::default()
+        };
+        assert_eq!(label_of(&values, &params), ("brain.heart.liver".into(), 3));
+    }
+
+    #[test]
+    fn classify_stops_below_no_class_criteria() {
+        let params = KdeParams {
+            no_class_criteria: 0.4,
+            ..Default::default()
+        };
+        let values = [(1.0, 0), (0.9, 1), (0.8, 2)];
+        assert_eq!(label_of(&values, &params), (COMMON_LABEL.into(), 0));
+        let values = [(1.0, 0), (0.9, 1)];
+        assert_eq!(label_of(&values, &params), ("brain.liver".into(), 2));
+    }
+
+    #[test]
+    fn classify_none_without_signal() {
+        let params = KdeParams::default();
+        assert_eq!(label_of(&[], &params), (NONE_LABEL.into(), 0));
+        assert_eq!(
+            label_of(&[(0.0, 0), (0.0, 1)], &params),
+            (NONE_LABEL.into(), 0)
+        );
+    }
+
+    #[test]
+    fn classify_filters_on_min_value() {
+        let params = KdeParams::default();
+        assert_eq!(label_of(&[(0.09, 0)], &params), (NONE_LABEL.into(), 0));
+        assert_eq!(label_of(&[(0.1, 0)], &params), ("liver".into(), 1));
+    }
+}
@@ -1,2 +1,5 @@
 pub mod cluster;
+pub mod hdbscan;
+pub mod kde;
+pub mod umap;
 pub mod wkde;
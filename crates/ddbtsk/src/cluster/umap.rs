@@ -0,0 +1,530 @@
+//! In-process UMAP embedding following umap-learn (fuzzy simplicial set on
+//! the k nearest neighbours, spectral initialisation, optimised with negative
+//! sampling SGD). Unlike umap-learn, the nearest neighbours are searched
+//! exhaustively rather than with NN-descent, which is exact but quadratic in
+//! the number of rows, and a disconnected graph is initialised randomly
+//! instead of laying out its components separately.
+
+use crate::error::{Error, Result};
+use log::warn;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+
+const SMOOTH_K_TOLERANCE: f64 = 1e-5;
+const MIN_K_DIST_SCALE: f64 = 1e-3;
+const NEGATIVE_SAMPLE_RATE: f64 = 5.0;
+const REPULSION_STRENGTH: f64 = 1.0;
+const INITIAL_ALPHA: f64 = 1.0;
+const GRAD_CLIP: f64 = 4.0;
+const SPECTRAL_MAX_ITER: usize = 1000;
+const SPECTRAL_TOLERANCE: f64 = 1e-9;
+const SPECTRAL_NOISE: f64 = 1e-4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    Euclidean,
+    Manhattan,
+    Cosine,
+    Correlation,
+}
+
+impl Metric {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "euclidean" => Ok(Self::Euclidean),
+            "manhattan" => Ok(Self::Manhattan),
+            "cosine" => Ok(Self::Cosine),
+            "correlation" => Ok(Self::Correlation),
+            _ => Err(Error::Specified(format!("Unsupported metric: {name}"))),
+        }
+    }
+
+    /// Cosine and correlation are computed as `1 - dot` on rows prepared by
+    /// `prepare`, so they must not be used on raw rows.
+    fn distance(self, a: &[f64], b: &[f64]) -> f64 {
+        match self {
+            Self::Euclidean => a
+                .iter()
+                .zip(b)
+                .map(|(x, y)| (x - y).powi(2))
+                .sum::<f64>()
+                .sqrt(),
+            Self::Manhattan => a.iter().zip(b).map(|(x, y)| (x - y).abs()).sum(),
+            Self::Cosine | Self::Correlation => {
+                let dot: f64 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+                (1.0 - dot).max(0.0)
+            }
+        }
+    }
+
+    fn prepare(self, data: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        match self {
+            Self::Euclidean | Self::Manhattan => data.to_vec(),
+            Self::Cosine | Self::Correlation => data
+                .iter()
+                .map(|row| {
+                    let mean = match self {
+                        Self::Correlation => row.iter().sum::<f64>() / row.len() as f64,
+                        _ => 0.0,
+                    };
+                    let centered: Vec<f64> = row.iter().map(|v| v - mean).collect();
+                    let norm = centered.iter().map(|v| v * v).sum::<f64>().sqrt();
+                    match norm > 0.0 {
+                        true => centered.iter().map(|v| v / norm).collect(),
+                        false => centered,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UmapParams {
+    pub n_neighbors: usize,
+    pub n_components: usize,
+    pub min_dist: f64,
+    pub spread: f64,
+    pub n_epochs: Option<usize>,
+    pub metric: Metric,
+    pub random_state: u64,
+}
+
+impl Default for UmapParams {
+    fn default() -> Self {
+        Self {
+            n_neighbors: 15,
+            n_components: 2,
+            min_dist: 0.1,
+            spread: 1.0,
+            n_epochs: None,
+            metric: Metric::Euclidean,
+            random_state: 0,
+        }
+    }
+}
+
+/// Returns the embedding as one row of `n_components` coordinates per input row.
+pub fn embed(data: &[Vec<f64>], params: &UmapParams) -> Result<Vec<Vec<f64>>> {
+    let n = data.len();
+    if n < 2 {
+        return Err(Error::Specified(format!(
+            "UMAP needs at least two rows, got {n}"
+        )));
+    }
+    if params.n_components == 0 {
+        return Err(Error::Specified("UMAP needs n_components > 0".into()));
+    }
+    let k = params.n_neighbors.clamp(2, n);
+    let prepared = params.metric.prepare(data);
+    let (knn_indices, knn_dists) = nearest_neighbors(&prepared, k, params.metric);
+    let (sigmas, rhos) = smooth_knn_dist(&knn_dists, k as f64);
+    let edges = fuzzy_simplicial_set(&knn_indices, &knn_dists, &sigmas, &rhos);
+    let (a, b) = find_ab_params(params.spread, params.min_dist);
+    let n_epochs = params
+        .n_epochs
+        .unwrap_or(if n <= 10_000 { 500 } else { 200 });
+
+    let mut rng = StdRng::seed_from_u64(params.random_state);
+    let dim = params.n_components;
+    let mut embedding = match spectral_layout(n, dim, &edges, &mut rng) {
+        Some(embedding) => embedding,
+        None => {
+            warn!("UMAP graph is disconnected or too small, using a random initialisation");
+            (0..n * dim).map(|_| rng.gen_range(-10.0..10.0)).collect()
+        }
+    };
+    rescale(&mut embedding, dim);
+    optimize_layout(&mut embedding, dim, n, &edges, a, b, n_epochs, &mut rng);
+
+    Ok(embedding.chunks(dim).map(<[f64]>::to_vec).collect())
+}
+
+fn nearest_neighbors(
+    data: &[Vec<f64>],
+    k: usize,
+    metric: Metric,
+) -> (Vec<Vec<usize>>, Vec<Vec<f64>>) {
+    let mut indices = Vec::with_capacity(data.len());
+    let mut dists = Vec::with_capacity(data.len());
+    for (i, row) in data.iter().enumerate() {
+        let mut all: Vec<(f64, usize)> = data
+            .iter()
+            .enumerate()
+            .map(|(j, other)| match i == j {
+                true => (0.0, j),
+                false => (metric.distance(row, other), j),
+            })
+            .collect();
+        all.select_nth_unstable_by(k - 1, |x, y| x.0.total_cmp(&y.0).then(x.1.cmp(&y.1)));
+        all.truncate(k);
+        all.sort_by(|x, y| x.0.total_cmp(&y.0).then(x.1.cmp(&y.1)));
+        indices.push(all.iter().map(|e| e.1).collect());
+        dists.push(all.iter().map(|e| e.0).collect());
+    }
+    (indices, dists)
+}
+
+/// Number of connected components of the graph of `edges` on `n` vertices.
+fn n_components(n: usize, edges: &[(usize, usize, f64)]) -> usize {
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    let mut parent: Vec<usize> = (0..n).collect();
+    let mut components = n;
+    for (i, j, _) in edges {
+        let (a, b) = (root(&mut parent, *i), root(&mut parent, *j));
+        if a != b {
+            parent[a] = b;
+            components -= 1;
+        }
+    }
+    components
+}
+
+/// Removes the components along `basis` from `v` and normalises it; false
+/// when nothing is left.
+fn orthonormalize(v: &mut [f64], basis: &[Vec<f64>]) -> bool {
+    for u in basis {
+        let dot: f64 = v.iter().zip(u).map(|(x, y)| x * y).sum();
+        v.iter_mut().zip(u).for_each(|(x, y)| *x -= dot * y);
+    }
+    let norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+    if norm < f64::EPSILON {
+        return false;
+    }
+    v.iter_mut().for_each(|x| *x /= norm);
+    true
+}
+
+/// Spectral initialisation as in umap-learn: the eigenvectors of the
+/// normalised graph Laplacian with the smallest non-zero eigenvalues, scaled
+/// to a maximum of 10 with a little noise. They are found by subspace
+/// iteration on `I + D^-1/2 W D^-1/2`, deflated by the trivial eigenvector.
+/// `None` for a disconnected graph or fewer than `dim + 2` vertices.
+fn spectral_layout(
+    n: usize,
+    dim: usize,
+    edges: &[(usize, usize, f64)],
+    rng: &mut StdRng,
+) -> Option<Vec<f64>> {
+    if n < dim + 2 || n_components(n, edges) > 1 {
+        return None;
+    }
+    let mut degree = vec![0.0; n];
+    for (i, _, w) in edges {
+        degree[*i] += w;
+    }
+    let inv_sqrt: Vec<f64> = degree
+        .iter()
+        .map(|d| match *d > 0.0 {
+            true => 1.0 / d.sqrt(),
+            false => 0.0,
+        })
+        .collect();
+    let apply = |x: &[f64]| -> Vec<f64> {
+        let mut y = x.to_vec();
+        for (i, j, w) in edges {
+            y[*i] += w * inv_sqrt[*i] * inv_sqrt[*j] * x[*j];
+        }
+        y
+    };
+    let mut trivial: Vec<f64> = degree.iter().map(|d| d.sqrt()).collect();
+    if !orthonormalize(&mut trivial, &[]) {
+        return None;
+    }
+    let mut vectors: Vec<Vec<f64>> = vec![];
+    for _ in 0..dim {
+        let mut v: Vec<f64> = (0..n).map(|_| rng.gen_range(-1.0..1.0)).collect();
+        let basis: Vec<Vec<f64>> = std::iter::once(trivial.clone())
+            .chain(vectors.iter().cloned())
+            .collect();
+        if !orthonormalize(&mut v, &basis) {
+            return None;
+        }
+        vectors.push(v);
+    }
+    for _ in 0..SPECTRAL_MAX_ITER {
+        let mut basis = vec![trivial.clone()];
+        let mut converged = true;
+        for v in &vectors {
+            let mut next = apply(v);
+            if !orthonormalize(&mut next, &basis) {
+                return None;
+            }
+            let dot: f64 = next.iter().zip(v).map(|(x, y)| x * y).sum();
+            converged &= 1.0 - dot.abs() < SPECTRAL_TOLERANCE;
+            basis.push(next);
+        }
+        vectors = basis.split_off(1);
+        if converged {
+            break;
+        }
+    }
+    let max = vectors
+        .iter()
+        .flatten()
+        .fold(0.0, |m: f64, v| m.max(v.abs()));
+    if max <= 0.0 {
+        return None;
+    }
+    let expansion = 10.0 / max;
+    Some(
+        (0..n * dim)
+            .map(|e| {
+                let noise = rng.gen_range(-SPECTRAL_NOISE..SPECTRAL_NOISE);
+                vectors[e % dim][e / dim] * expansion + noise
+            })
+            .collect(),
+    )
+}
+
+/// Scales every coordinate of the initial embedding to `[0, 10]` as
+/// umap-learn does before optimising.
+fn rescale(embedding: &mut [f64], dim: usize) {
+    for d in 0..dim {
+        let column = embedding.iter().skip(d).step_by(dim);
+        let (lo, hi) = column.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+            (lo.min(*v), hi.max(*v))
+        });
+        if hi > lo {
+            embedding
+                .iter_mut()
+                .skip(d)
+                .step_by(dim)
+                .for_each(|v| *v = 10.0 * (*v - lo) / (hi - lo));
+        }
+    }
+}
+
+fn smooth_knn_dist(distances: &[Vec<f64>], k: f64) -> (Vec<f64>, Vec<f64>) {
+    let target = k.log2();
+    let n_total: usize = distances.iter().map(Vec::len).sum();
+    let mean_distances = distances.iter().flatten().sum::<f64>() / n_total as f64;
+    let mut sigmas = Vec::with_capacity(distances.len());
+    let mut rhos = Vec::with_capacity(distances.len());
+    for row in distances {
+        let rho = row.iter().copied().find(|d| *d > 0.0).unwrap_or(0.0);
+        let mut lo = 0.0;
+        let mut hi = f64::INFINITY;
+        let mut mid = 1.0;
+        for _ in 0..64 {
+            let psum: f64 = row
+                .iter()
+                .skip(1)
+                .map(|d| {
+                    let d = d - rho;
+                    match d > 0.0 {
+                        true => (-d / mid).exp(),
+                        false => 1.0,
+                    }
+                })
+                .sum();
+            if (psum - target).abs() < SMOOTH_K_TOLERANCE {
+                break;
+            }
+            if psum > target {
+                hi = mid;
+                mid = (lo + hi) / 2.0;
+            } else {
+                lo = mid;
+                mid = match hi.is_infinite() {
+                    true => mid * 2.0,
+                    false => (lo + hi) / 2.0,
+                };
+            }
+        }
+        let floor = match rho > 0.0 {
+            true => MIN_K_DIST_SCALE * row.iter().sum::<f64>() / row.len() as f64,
+            false => MIN_K_DIST_SCALE * mean_distances,
+        };
+        sigmas.push(mid.max(floor));
+        rhos.push(rho);
+    }
+    (sigmas, rhos)
+}
+
+/// Symmetrised membership strengths as directed `(head, tail, weight)` edges.
+fn fuzzy_simplicial_set(
+    knn_indices: &[Vec<usize>],
+    knn_dists: &[Vec<f64>],
+    sigmas: &[f64],
+    rhos: &[f64],
+) -> Vec<(usize, usize, f64)> {
+    let mut graph: Vec<HashMap<usize, f64>> = vec![HashMap::new(); knn_indices.len()];
+    for (i, (neighbors, dists)) in knn_indices.iter().zip(knn_dists).enumerate() {
+        for (j, d) in neighbors.iter().zip(dists) {
+            if *j == i {
+                continue;
+            }
+            let strength = match d - rhos[i] <= 0.0 || sigmas[i] == 0.0 {
+                true => 1.0,
+                false => (-(d - rhos[i]) / sigmas[i]).exp(),
+            };
+            graph[i].insert(*j, strength);
+        }
+    }
+    let mut edges = vec![];
+    for (i, row) in graph.iter().enumerate() {
+        for (j, a) in row {
+            let b = graph[*j].get(&i).copied();
+            let weight = a + b.unwrap_or(0.0) - a * b.unwrap_or(0.0);
+            edges.push((i, *j, weight));
+            if b.is_none() {
+                edges.push((*j, i, weight));
+            }
+        }
+    }
+    edges.sort_by(|x, y| x.0.cmp(&y.0).then(x.1.cmp(&y.1)));
+    edges
+}
+
+/// Fits `1 / (1 + a * x^(2b))` to the target curve with Levenberg-Marquardt.
+fn find_ab_params(spread: f64, min_dist: f64) -> (f64, f64) {
+    let xs: Vec<f64> = (1..300).map(|i| i as f64 * spread * 3.0 / 299.0).collect();
+    let ys: Vec<f64> = xs
+        .iter()
+        .map(|x| match *x < min_dist {
+            true => 1.0,
+            false => (-(x - min_dist) / spread).exp(),
+        })
+        .collect();
+    let sse = |a: f64, b: f64| -> f64 {
+        xs.iter()
+            .zip(&ys)
+            .map(|(x, y)| (1.0 / (1.0 + a * x.powf(2.0 * b)) - y).powi(2))
+            .sum()
+    };
+    let (mut a, mut b) = (1.8, 0.8);
+    let mut lambda = 1e-3;
+    let mut current = sse(a, b);
+    for _ in 0..200 {
+        let (mut jtj, mut jtr) = ([[0.0; 2]; 2], [0.0; 2]);
+        for (x, y) in xs.iter().zip(&ys) {
+            let xp = x.powf(2.0 * b);
+            let denom = (1.0 + a * xp).powi(2);
+            let r = 1.0 / (1.0 + a * xp) - y;
+            let da = -xp / denom;
+            let db = -a * xp * 2.0 * x.ln() / denom;
+            jtj[0][0] += da * da;
+            jtj[0][1] += da * db;
+            jtj[1][1] += db * db;
+            jtr[0] += da * r;
+            jtr[1] += db * r;
+        }
+        jtj[1][0] = jtj[0][1];
+        let m00 = jtj[0][0] * (1.0 + lambda);
+        let m11 = jtj[1][1] * (1.0 + lambda);
+        let det = m00 * m11 - jtj[0][1] * jtj[1][0];
+        if det.abs() < f64::EPSILON {
+            break;
+        }
+        let step_a = (m11 * jtr[0] - jtj[0][1] * jtr[1]) / det;
+        let step_b = (m00 * jtr[1] - jtj[1][0] * jtr[0]) / det;
+        let (na, nb) = (a - step_a, b - step_b);
+        let candidate = sse(na, nb);
+        if na > 0.0 && nb > 0.0 && candidate < current {
+            let converged = (current - candidate).abs() < 1e-12;
+            a = na;
+            b = nb;
+            current = candidate;
+            lambda /= 10.0;
+            if converged {
+                break;
+            }
+        } else {
+            lambda *= 10.0;
+        }
+    }
+    (a, b)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn optimize_layout(
+    embedding: &mut [f64],
+    dim: usize,
+    n_vertices: usize,
+    edges: &[(usize, usize, f64)],
+    a: f64,
+    b: f64,
+    n_epochs: usize,
+    rng: &mut StdRng,
+) {
+    let max_weight = edges.iter().map(|e| e.2).fold(0.0, f64::max);
+    let edges: Vec<&(usize, usize, f64)> = edges
+        .iter()
+        .filter(|e| e.2 >= max_weight / n_epochs as f64)
+        .collect();
+    let epochs_per_sample: Vec<f64> = edges.iter().map(|e| max_weight / e.2).collect();
+    let epochs_per_negative_sample: Vec<f64> = epochs_per_sample
+        .iter()
+        .map(|e| e / NEGATIVE_SAMPLE_RATE)
+        .collect();
+    let mut epoch_of_next_sample = epochs_per_sample.clone();
+    let mut epoch_of_next_negative_sample = epochs_per_negative_sample.clone();
+
+    let rdist = |emb: &[f64], j: usize, k: usize| -> f64 {
+        (0..dim)
+            .map(|d| (emb[j * dim + d] - emb[k * dim + d]).powi(2))
+            .sum()
+    };
+
+    for epoch in 0..n_epochs {
+        let n = epoch as f64;
+        let alpha = INITIAL_ALPHA * (1.0 - n / n_epochs as f64);
+        for (i, (j, k, _)) in edges.iter().map(|e| **e).enumerate() {
+            if epoch_of_next_sample[i] > n {
+                continue;
+            }
+            let dist_squared = rdist(embedding, j, k);
+            let grad_coeff = match dist_squared > 0.0 {
+                true => {
+                    -2.0 * a * b * dist_squared.powf(b - 1.0) / (a * dist_squared.powf(b) + 1.0)
+                }
+                false => 0.0,
+            };
+            for d in 0..dim {
+                let diff = embedding[j * dim + d] - embedding[k * dim + d];
+                let grad = (grad_coeff * diff).clamp(-GRAD_CLIP, GRAD_CLIP);
+                embedding[j * dim + d] += grad * alpha;
+                embedding[k * dim + d] -= grad * alpha;
+            }
+            epoch_of_next_sample[i] += epochs_per_sample[i];
+
+            let n_neg_samples = ((n - epoch_of_next_negative_sample[i])
+                / epochs_per_negative_sample[i])
+                .max(0.0) as usize;
+            for _ in 0..n_neg_samples {
+                let k = rng.gen_range(0..n_vertices);
+                if j == k {
+                    continue;
+                }
+                let dist_squared = rdist(embedding, j, k);
+                let grad_coeff = match dist_squared > 0.0 {
+                    true => {
+                        2.0 * REPULSION_STRENGTH * b
+                            / ((0.001 + dist_squared) * (a * dist_squared.powf(b) + 1.0))
+                    }
+                    false => 0.0,
+                };
+                for d in 0..dim {
+                    let grad = match grad_coeff > 0.0 {
+                        true => {
+                            let diff = embedding[j * dim + d] - embedding[k * dim + d];
+                            (grad_coeff * diff).clamp(-GRAD_CLIP, GRAD_CLIP)
+                        }
+                        false => GRAD_CLIP,
+                    };
+                    embedding[j * dim + d] += grad * alpha;
+                }
+            }
+            epoch_of_next_negative_sample[i] +=
+                n_neg_samples as f64 * epochs_per_negative_sample[i];
+        }
+    }
+}
@@ -1,4 +1,5 @@
-use crate::error::Result;
+use crate::cluster::kde::{self, KdeParams, KdePoint};
+use crate::error::{Error, Result};
 use camino::Utf8PathBuf;
 use iwf::{IwfResult, IwfTask};
 use iwfmacros::Iwfargs;
@@ -117,6 +118,59 @@ impl WkdeTask {
         }
         Ok((labels, assignments))
     }
+
+    /// Labels the embedding in-process instead of running `run_wkde.py`,
+    /// returning the records `read_output` would parse.
+    pub fn run_native(
+        &self,
+        input_data: &[WkdeInput],
+    ) -> Result<(Vec<WkdeOutput>, Vec<WkdeLabelOutput>)> {
+        let params = KdeParams {
+            fraction: self.fraction,
+            bandwidth: self.bandwidth,
+            min_value: self.min_value,
+            no_class_criteria: self.no_class_criteria,
+            max_n_labels: usize::try_from(self.max_n_labels).map_err(|_| {
+                Error::Specified(format!("Invalid max_n_labels: {}", self.max_n_labels))
+            })?,
+            ..Default::default()
+        };
+        let points: Vec<KdePoint> = input_data
+            .iter()
+            .map(|e| KdePoint {
+                idx: &e.idx,
+                column: &e.col,
+                y1: e.y1,
+                y2: e.y2,
+                weight: e.weights,
+            })
+            .collect();
+        let (pixels, assignments) = kde::label(&points, &params)?;
+        let labels = pixels
+            .into_iter()
+            .enumerate()
+            .map(|(i, e)| WkdeOutput {
+                label: e.label,
+                index: i as i32,
+                n_labels: e.n_labels as i32,
+                n_pixels: e.n_pixels as i32,
+            })
+            .collect();
+        let assignments = assignments
+            .into_iter()
+            .map(|e| WkdeLabelOutput {
+                idx: e.idx,
+                y1: e.y1,
+                y2: e.y2,
+                clss: e.label,
+                c1: e.c1 as i32,
+                c2: e.c2 as i32,
+                v1: e.v1,
+                v2: e.v2,
+            })
+            .collect();
+        Ok((labels, assignments))
+    }
 }
 
 #[derive(Debug)]
@@ -135,3 +189,37 @@ impl std::fmt::Display for WkdeResult {
         write!(f, "{self:?}")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn read_tsv<T: serde::de::DeserializeOwned>(name: &str) -> Vec<T> {
+        let path = format!("{}/testdata/{name}", env!("CARGO_MANIFEST_DIR"));
+        csv::ReaderBuilder::new()
+            .delimiter(b'\t')
+            .from_path(path)
+            .unwrap()
+            .deserialize()
+            .map(|rec| rec.unwrap())
+            .collect()
+    }
+
+    /// Three separated groups: liver, brain and an even liver/heart mix.
+    #[test]
+    fn run_native_labels_fixture() {
+        let input: Vec<WkdeInput> = read_tsv("wkde_input.tsv");
+        let expected: HashMap<String, String> = read_tsv::<(String, String)>("wkde_classes.tsv")
+            .into_iter()
+            .collect();
+        let (labels, assignments) = WkdeTask::default().run_native(&input).unwrap();
+        let names: Vec<&str> = labels.iter().map(|e| e.label.as_str()).collect();
+        assert_eq!(names, ["brain", "heart.liver", "liver", "none"]);
+        assert_eq!(labels.iter().map(|e| e.n_pixels).sum::<i32>(), 100 * 100);
+        assert_eq!(assignments.len(), expected.len());
+        for e in assignments {
+            assert_eq!(e.clss, expected[&e.idx], "{}", e.idx);
+        }
+    }
+}
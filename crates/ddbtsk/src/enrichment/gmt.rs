@@ -0,0 +1,74 @@
+//! Reader for the tab separated GMT gene set format
+//! (`term<TAB>description<TAB>gene<TAB>gene...`).
+
+use crate::error::{Error, Result};
+use camino::Utf8Path;
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(Debug, Clone)]
+pub struct GeneSet {
+    pub term: String,
+    pub description: String,
+    pub genes: Vec<String>,
+}
+
+/// Genes are upper-cased and deduplicated; blank lines are skipped.
+pub fn read_gmt(path: &Utf8Path) -> Result<Vec<GeneSet>> {
+    let content = std::fs::read_to_string(path)?;
+    let mut sets = vec![];
+    for (n, line) in content.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut fields = line.split('\t');
+        let (Some(term), Some(description)) = (fields.next(), fields.next()) else {
+            return Err(Error::Specified(format!(
+                "Malformed GMT line {} in {path}",
+                n + 1
+            )));
+        };
+        let mut genes: Vec<String> = fields
+            .map(str::trim)
+            .filter(|g| !g.is_empty())
+            .map(str::to_uppercase)
+            .collect();
+        genes.sort_unstable();
+        genes.dedup();
+        sets.push(GeneSet {
+            term: term.into(),
+            description: description.into(),
+            genes,
+        });
+    }
+    Ok(sets)
+}
+
+/// Name reported in the `Gene_set` column, i.e. the file name without `.gmt`.
+pub fn gene_set_name(path: &Utf8Path) -> String {
+    path.file_name()
+        .unwrap_or_default()
+        .trim_end_matches(".gmt")
+        .to_string()
+}
+
+/// All genes of all sets, the background of the native tests.
+pub fn universe(sets: &[GeneSet]) -> BTreeSet<&str> {
+    sets.iter()
+        .flat_map(|s| s.genes.iter().map(String::as_str))
+        .collect()
+}
+
+/// Returns the first spelling of `gene` or one of its aliases found in the
+/// universe, falling back to the upper-cased gene.
+pub fn match_gene(
+    gene: &str,
+    aliases: &HashMap<String, Vec<String>>,
+    universe: &BTreeSet<&str>,
+) -> String {
+    std::iter::once(gene)
+        .chain(aliases.get(gene).into_iter().flatten().map(String::as_str))
+        .map(str::to_uppercase)
+        .find(|g| universe.contains(g.as_str()))
+        .unwrap_or_else(|| gene.to_uppercase())
+}
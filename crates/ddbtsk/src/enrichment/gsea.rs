@@ -1,8 +1,12 @@
+use crate::cache;
+use crate::enrichment::gmt::{gene_set_name, match_gene, read_gmt, universe};
+use crate::enrichment::ora::ora;
 use crate::error::Result;
 use camino::Utf8PathBuf;
 use iwf::{IwfResult, IwfTask};
 use iwfmacros::Iwfargs;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 #[derive(Iwfargs, Clone, Debug)]
 #[iwfarg(executable = "run_gsea.py")]
@@ -85,6 +89,13 @@ pub struct GseaOutput {
 }
 
 impl GseaTask {
+    /// Cache key of the written input, the gene sets, the parameters and the engine.
+    pub fn cache_key(&self, native: bool) -> Result<String> {
+        cache::key(
+            &[self.input_path.as_path(), self.gmt_path.as_path()],
+            &format!("{self:?} native={native}"),
+        )
+    }
     pub fn write_input(&self, input_data: &Vec<GseaInput>) -> Result<()> {
         let mut wtr = csv::WriterBuilder::new()
             .delimiter(b'\t')
@@ -108,4 +119,46 @@ impl GseaTask {
         }
         Ok(ret)
     }
+    /// Runs the over-representation test in-process instead of `run_gsea.py`
+    /// and writes the same output file. Query ids are matched against the
+    /// GMT through `aliases` (e.g. accession to gene symbols) when they are
+    /// not gene set members themselves.
+    pub fn run_native(
+        &self,
+        input_data: &[GseaInput],
+        aliases: &HashMap<String, Vec<String>>,
+    ) -> Result<()> {
+        let sets = read_gmt(&self.gmt_path)?;
+        let gene_set = gene_set_name(&self.gmt_path);
+        let universe = universe(&sets);
+        let mut clusters: BTreeMap<&str, (usize, BTreeSet<String>)> = BTreeMap::new();
+        for item in input_data {
+            let gene = match_gene(&item.gene, aliases, &universe);
+            let cluster = clusters.entry(&item.cluster).or_default();
+            cluster.0 += 1;
+            cluster.1.insert(gene);
+        }
+
+        let mut wtr = csv::WriterBuilder::new()
+            .delimiter(b'\t')
+            .from_path(&self.output_path)?;
+        for (cluster_id, (n_query_genes, genes)) in clusters {
+            for row in ora(&genes, &sets) {
+                wtr.serialize(GseaOutput {
+                    gene_set: gene_set.clone(),
+                    term: row.term.clone(),
+                    overlap: row.overlap(),
+                    pvalue: row.pvalue,
+                    adjusted_pvalue: row.adjusted_pvalue,
+                    odds_ratio: row.odds_ratio,
+                    combined_score: row.combined_score,
+                    genes: row.genes.join(";"),
+                    cluster_id: cluster_id.into(),
+                    n_query_genes: n_query_genes as i32,
+                })?;
+            }
+        }
+        wtr.flush()?;
+        Ok(())
+    }
 }
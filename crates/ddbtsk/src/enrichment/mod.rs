@@ -1 +1,4 @@
+pub mod gmt;
 pub mod gsea;
+pub mod ora;
+pub mod prerank;
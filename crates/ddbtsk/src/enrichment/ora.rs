@@ -0,0 +1,183 @@
+//! Over-representation analysis with the hypergeometric upper tail
+//! (one sided Fisher's exact test) and Benjamini-Hochberg correction.
+//! The background is the union of all genes in the gene sets.
+
+use crate::enrichment::gmt::{universe, GeneSet};
+use std::collections::BTreeSet;
+
+#[derive(Debug, Clone)]
+pub struct OraRow {
+    pub term: String,
+    pub hits: usize,
+    pub term_size: usize,
+    pub pvalue: f64,
+    pub adjusted_pvalue: f64,
+    pub odds_ratio: f64,
+    pub combined_score: f64,
+    pub genes: Vec<String>,
+}
+
+impl OraRow {
+    /// `hits/term_size` as reported by enrichr.
+    pub fn overlap(&self) -> String {
+        format!("{}/{}", self.hits, self.term_size)
+    }
+}
+
+/// Tests every gene set with at least one hit, sorted by p-value.
+pub fn ora(query: &BTreeSet<String>, sets: &[GeneSet]) -> Vec<OraRow> {
+    let universe = universe(sets);
+    let query: BTreeSet<&str> = query
+        .iter()
+        .map(String::as_str)
+        .filter(|g| universe.contains(g))
+        .collect();
+    let n_universe = universe.len();
+    let n_query = query.len();
+    if n_query == 0 {
+        return vec![];
+    }
+    let ln_fact = ln_factorials(n_universe);
+
+    let mut rows: Vec<OraRow> = sets
+        .iter()
+        .filter_map(|set| {
+            let genes: Vec<String> = set
+                .genes
+                .iter()
+                .filter(|g| query.contains(g.as_str()))
+                .cloned()
+                .collect();
+            if genes.is_empty() {
+                return None;
+            }
+            let (x, k) = (genes.len(), set.genes.len());
+            let pvalue = hypergeometric_sf(x, n_universe, k, n_query, &ln_fact);
+            let odds_ratio = odds_ratio(x, n_universe, k, n_query);
+            Some(OraRow {
+                term: set.term.clone(),
+                hits: x,
+                term_size: k,
+                pvalue,
+                adjusted_pvalue: pvalue,
+                odds_ratio,
+                combined_score: -pvalue.max(f64::MIN_POSITIVE).ln() * odds_ratio,
+                genes,
+            })
+        })
+        .collect();
+    let adjusted = benjamini_hochberg(&rows.iter().map(|r| r.pvalue).collect::<Vec<_>>());
+    for (row, adj) in rows.iter_mut().zip(adjusted) {
+        row.adjusted_pvalue = adj;
+    }
+    rows.sort_by(|a, b| a.pvalue.total_cmp(&b.pvalue).then(a.term.cmp(&b.term)));
+    rows
+}
+
+/// Step-up adjusted p-values, returned in input order.
+pub fn benjamini_hochberg(pvalues: &[f64]) -> Vec<f64> {
+    let n = pvalues.len();
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|a, b| pvalues[*b].total_cmp(&pvalues[*a]));
+    let mut adjusted = vec![0.0; n];
+    let mut running = 1.0_f64;
+    for (rank, i) in order.into_iter().enumerate() {
+        let q = pvalues[i] * n as f64 / (n - rank) as f64;
+        running = running.min(q);
+        adjusted[i] = running;
+    }
+    adjusted
+}
+
+fn ln_factorials(n: usize) -> Vec<f64> {
+    let mut ret = Vec::with_capacity(n + 1);
+    ret.push(0.0);
+    for i in 1..=n {
+        ret.push(ret[i - 1] + (i as f64).ln());
+    }
+    ret
+}
+
+/// `P(X >= x)` for `x` hits when drawing `n` from `total` with `k` successes.
+fn hypergeometric_sf(x: usize, total: usize, k: usize, n: usize, ln_fact: &[f64]) -> f64 {
+    let ln_choose = |a: usize, b: usize| ln_fact[a] - ln_fact[b] - ln_fact[a - b];
+    let denom = ln_choose(total, n);
+    let upper = k.min(n);
+    let p: f64 = (x..=upper)
+        .filter(|i| n - i <= total - k)
+        .map(|i| (ln_choose(k, i) + ln_choose(total - k, n - i) - denom).exp())
+        .sum();
+    p.min(1.0)
+}
+
+/// Sample odds ratio of the 2x2 table, with a 0.5 correction on empty cells.
+fn odds_ratio(x: usize, total: usize, k: usize, n: usize) -> f64 {
+    let a = x as f64;
+    let b = (n - x) as f64;
+    let c = (k - x) as f64;
+    let d = (total + x) as f64 - (k + n) as f64;
+    match a * b * c * d == 0.0 {
+        true => ((a + 0.5) * (d + 0.5)) / ((b + 0.5) * (c + 0.5)),
+        false => (a * d) / (b * c),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-12, "{a} != {b}");
+    }
+
+    /// Reference values of `scipy.stats.hypergeom.sf(x - 1, total, k, n)`.
+    #[test]
+    fn hypergeometric_sf_known_values() {
+        let ln_fact = ln_factorials(1000);
+        for (x, total, k, n, expected) in [
+            (2, 20, 5, 4, 0.248_710_010_319_917_44),
+            (10, 1000, 50, 100, 0.021_440_329_381_402_473),
+            (1, 10, 3, 2, 0.533_333_333_333_333_3),
+            (3, 10, 3, 3, 0.008_333_333_333_333_333),
+            (0, 10, 3, 2, 1.0),
+        ] {
+            assert_close(hypergeometric_sf(x, total, k, n, &ln_fact), expected);
+        }
+    }
+
+    /// Reference values of statsmodels `multipletests(p, method="fdr_bh")`.
+    #[test]
+    fn benjamini_hochberg_known_values() {
+        let adjusted = benjamini_hochberg(&[0.01, 0.04, 0.03, 0.005]);
+        for (a, e) in adjusted.into_iter().zip([0.02, 0.04, 0.04, 0.02]) {
+            assert_close(a, e);
+        }
+        assert!(benjamini_hochberg(&[]).is_empty());
+    }
+
+    #[test]
+    fn benjamini_hochberg_ties() {
+        let adjusted = benjamini_hochberg(&[0.01, 0.03, 0.01]);
+        for (a, e) in adjusted.into_iter().zip([0.015, 0.03, 0.015]) {
+            assert_close(a, e);
+        }
+    }
+
+    #[test]
+    fn benjamini_hochberg_is_monotone() {
+        let pvalues = [0.2, 0.001, 0.04, 0.04, 0.9, 0.012, 0.3, 0.05];
+        let adjusted = benjamini_hochberg(&pvalues);
+        let mut pairs: Vec<(f64, f64)> = pvalues.into_iter().zip(adjusted).collect();
+        pairs.sort_by(|a, b| a.0.total_cmp(&b.0));
+        for w in pairs.windows(2) {
+            assert!(w[0].1 <= w[1].1);
+        }
+        assert!(pairs.iter().all(|(p, a)| a >= p && *a <= 1.0));
+    }
+
+    #[test]
+    fn odds_ratio_corrects_empty_cells() {
+        assert_close(odds_ratio(2, 20, 5, 4), 26.0 / 6.0);
+        assert_close(odds_ratio(0, 20, 5, 4), (0.5 * 11.5) / (4.5 * 5.5));
+    }
+}
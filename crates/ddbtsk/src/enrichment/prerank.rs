@@ -0,0 +1,199 @@
+//! Pre-ranked gene set enrichment analysis: weighted Kolmogorov-Smirnov
+//! running sum, gene permutation null distribution, normalised enrichment
+//! scores (NES) and the GSEA false discovery rate.
+
+use crate::enrichment::gmt::GeneSet;
+use rand::rngs::StdRng;
+use rand::seq::index::sample;
+use rand::SeedableRng;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct PrerankParams {
+    pub permutations: usize,
+    /// Exponent applied to the ranking scores in the running sum.
+    pub weight: f64,
+    pub min_size: usize,
+    pub max_size: usize,
+    pub seed: u64,
+}
+
+impl Default for PrerankParams {
+    fn default() -> Self {
+        Self {
+            permutations: 1000,
+            weight: 1.0,
+            min_size: 15,
+            max_size: 500,
+            seed: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PrerankRow {
+    pub term: String,
+    pub es: f64,
+    pub nes: f64,
+    pub pvalue: f64,
+    pub fdr: f64,
+    /// Genes of the set found in the ranking.
+    pub hits: usize,
+    pub leading_edge: Vec<String>,
+}
+
+/// `ranking` holds `(gene, score)`; genes must already match the GMT spelling.
+/// A gene listed more than once is ranked by its score of largest magnitude.
+/// Rows are sorted by descending NES.
+pub fn prerank(
+    ranking: &[(String, f64)],
+    sets: &[GeneSet],
+    params: &PrerankParams,
+) -> Vec<PrerankRow> {
+    let mut scores: HashMap<&str, f64> = HashMap::new();
+    for (gene, score) in ranking {
+        let best = scores.entry(gene.as_str()).or_insert(*score);
+        if score.abs() > best.abs() {
+            *best = *score;
+        }
+    }
+    let mut ranked: Vec<(&str, f64)> = scores.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(b.0)));
+    let n = ranked.len();
+    let position: HashMap<&str, usize> = ranked.iter().enumerate().map(|(i, g)| (g.0, i)).collect();
+    let weights: Vec<f64> = ranked
+        .iter()
+        .map(|g| g.1.abs().powf(params.weight))
+        .collect();
+
+    let mut rng = StdRng::seed_from_u64(params.seed);
+    let mut results = vec![];
+    for set in sets {
+        let mut hits: Vec<usize> = set
+            .genes
+            .iter()
+            .filter_map(|g| position.get(g.as_str()).copied())
+            .collect();
+        if hits.len() < params.min_size.max(1) || hits.len() > params.max_size || hits.len() >= n {
+            continue;
+        }
+        hits.sort_unstable();
+        let (es, peak) = enrichment_score(&hits, &weights, n);
+        let null: Vec<f64> = (0..params.permutations)
+            .map(|_| {
+                let mut random = sample(&mut rng, n, hits.len()).into_vec();
+                random.sort_unstable();
+                enrichment_score(&random, &weights, n).0
+            })
+            .collect();
+        let leading_edge = hits
+            .iter()
+            .filter(|p| match es >= 0.0 {
+                true => **p <= peak,
+                false => **p >= peak,
+            })
+            .map(|p| ranked[*p].0.to_string())
+            .collect();
+        results.push((set.term.clone(), es, null, hits.len(), leading_edge));
+    }
+
+    let mut rows: Vec<PrerankRow> = vec![];
+    let mut null_nes: Vec<f64> = vec![];
+    for (term, es, null, hits, leading_edge) in results {
+        let sign_mean = |positive: bool| -> f64 {
+            let values: Vec<f64> = null
+                .iter()
+                .copied()
+                .filter(|v| (*v >= 0.0) == positive)
+                .collect();
+            match values.is_empty() {
+                true => 0.0,
+                false => (values.iter().sum::<f64>() / values.len() as f64).abs(),
+            }
+        };
+        let (pos_mean, neg_mean) = (sign_mean(true), sign_mean(false));
+        let normalise = |v: f64| {
+            let mean = match v >= 0.0 {
+                true => pos_mean,
+                false => neg_mean,
+            };
+            match mean > 0.0 {
+                true => v / mean,
+                false => 0.0,
+            }
+        };
+        let same_sign = null.iter().filter(|v| (**v >= 0.0) == (es >= 0.0));
+        let n_same = same_sign.clone().count();
+        let pvalue = match n_same {
+            0 => 1.0,
+            _ => same_sign.filter(|v| v.abs() >= es.abs()).count() as f64 / n_same as f64,
+        };
+        let nes = normalise(es);
+        null_nes.extend(null.iter().map(|v| normalise(*v)));
+        rows.push(PrerankRow {
+            term,
+            es,
+            nes,
+            pvalue,
+            fdr: 1.0,
+            hits,
+            leading_edge,
+        });
+    }
+
+    let observed: Vec<f64> = rows.iter().map(|r| r.nes).collect();
+    for row in &mut rows {
+        row.fdr = fdr(row.nes, &null_nes, &observed);
+    }
+    rows.sort_by(|a, b| b.nes.total_cmp(&a.nes).then(a.term.cmp(&b.term)));
+    rows
+}
+
+/// Returns the maximum deviation of the running sum and its position.
+fn enrichment_score(hits: &[usize], weights: &[f64], n: usize) -> (f64, usize) {
+    let total: f64 = hits.iter().map(|p| weights[*p]).sum();
+    let miss_step = 1.0 / (n - hits.len()) as f64;
+    let hit_step = |p: usize| match total > 0.0 {
+        true => weights[p] / total,
+        false => 1.0 / hits.len() as f64,
+    };
+    let (mut best, mut peak) = (0.0_f64, 0);
+    let mut hit_sum = 0.0;
+    for (i, p) in hits.iter().enumerate() {
+        let before = hit_sum - (p - i) as f64 * miss_step;
+        if before.abs() > best.abs() {
+            best = before;
+            peak = p.saturating_sub(1);
+        }
+        hit_sum += hit_step(*p);
+        let after = hit_sum - (p - i) as f64 * miss_step;
+        if after.abs() > best.abs() {
+            best = after;
+            peak = *p;
+        }
+    }
+    (best, peak)
+}
+
+/// Share of null NES at least as extreme divided by the observed share, per sign.
+fn fdr(nes: f64, null_nes: &[f64], observed: &[f64]) -> f64 {
+    let share = |values: &[f64]| -> f64 {
+        let (same, extreme) =
+            values
+                .iter()
+                .fold((0, 0), |(s, e), v| match (nes >= 0.0, *v >= 0.0) {
+                    (true, true) => (s + 1, e + usize::from(*v >= nes)),
+                    (false, false) => (s + 1, e + usize::from(*v <= nes)),
+                    _ => (s, e),
+                });
+        match same {
+            0 => 0.0,
+            _ => extreme as f64 / same as f64,
+        }
+    };
+    let observed_share = share(observed);
+    match observed_share > 0.0 {
+        true => (share(null_nes) / observed_share).min(1.0),
+        false => 1.0,
+    }
+}
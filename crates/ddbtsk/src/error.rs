@@ -7,6 +7,7 @@ pub type Result<T> = core::result::Result<T, Error>;
 #[serde_as]
 #[derive(Debug, Serialize, From)]
 pub enum Error {
+    Specified(String),
     #[from]
     Std(#[serde_as(as = "DisplayFromStr")] std::io::Error),
     #[from]
@@ -0,0 +1,160 @@
+use crate::benchmark::metrics::score;
+use camino::Utf8PathBuf;
+use ddbtbl::gls::ac::AcBmc;
+use ddbtbl::gls::benchmark::{BenchmarkBmc, BenchmarkForCreate};
+use ddbtbl::gls::confusion::{ConfusionBmc, ConfusionForCreate};
+use ddbtbl::gls::reference::{ReferenceBmc, ReferenceForCreate};
+use ddbtbl::gls::tissue::TissueRegistry;
+use iwf::sql::ModelManager;
+use iwf::Result;
+use log::{debug, warn};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Imports a reference list of protein -> expected tissue(s) and scores the
+/// `global` and `tissue` labels of every score setting against it.
+/// Proteins are matched by accession, source id or entry name mnemonic.
+#[derive(Clone)]
+pub struct CalculateBenchmarkTask<'a> {
+    pub mm: &'a ModelManager,
+    pub tissues: &'a TissueRegistry,
+    pub reference: &'a Utf8PathBuf,
+}
+
+#[derive(sqlx::FromRow)]
+struct AcQuery {
+    id: i32,
+    entry: String,
+    entry_name: String,
+    frm: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct LabelQuery {
+    ac: String,
+    score_setting_id: i32,
+    global_label_string: String,
+    tissue_label_string: String,
+}
+
+fn split_label(label: &str) -> BTreeSet<String> {
+    label
+        .split('.')
+        .filter(|e| !e.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+impl CalculateBenchmarkTask<'_> {
+    pub async fn execute(self) -> Result<CalculateBenchmarkResult> {
+        let records = ReferenceBmc::parse(self.reference)?;
+        let acs = sqlx::query_as::<_, AcQuery>("select id,entry,entry_name,frm from ac")
+            .fetch_all(&self.mm.db)
+            .await?;
+        let mut proteins: HashMap<String, i32> = HashMap::new();
+        let mut collisions = BTreeSet::new();
+        for ac in acs {
+            for alias in AcBmc::aliases(&ac.entry_name, &ac.frm).chain([ac.entry.as_str()]) {
+                let ac_id = proteins.entry(alias.to_uppercase()).or_insert(ac.id);
+                if *ac_id != ac.id {
+                    collisions.insert(alias.to_uppercase());
+                }
+            }
+        }
+        if !collisions.is_empty() {
+            warn!(
+                "{} aliases name several proteins, the first one is used, e.g. {:?}",
+                collisions.len(),
+                collisions.iter().take(5).collect::<Vec<_>>()
+            );
+        }
+
+        let mut expected: HashMap<i32, BTreeSet<String>> = HashMap::new();
+        let mut entries = vec![];
+        let mut unmatched = BTreeSet::new();
+        let mut unknown_tissues = BTreeSet::new();
+        for record in records {
+            let Some(ac_id) = proteins.get(&record.protein.to_uppercase()) else {
+                unmatched.insert(record.protein);
+                continue;
+            };
+            let Ok(tissue) = self.tissues.resolve(&record.tissue) else {
+                unknown_tissues.insert(record.tissue);
+                continue;
+            };
+            let tissue = tissue.to_string();
+            expected.entry(*ac_id).or_default().insert(tissue.clone());
+            entries.push(ReferenceForCreate {
+                ac_id: *ac_id,
+                protein: record.protein,
+                tissue,
+            });
+        }
+        if !unmatched.is_empty() {
+            warn!(
+                "{} reference proteins not found, e.g. {:?}",
+                unmatched.len(),
+                unmatched.iter().take(5).collect::<Vec<_>>()
+            );
+        }
+        if !unknown_tissues.is_empty() {
+            warn!(
+                "{} reference tissues not in the vocabulary, their records are skipped: {:?}",
+                unknown_tissues.len(),
+                unknown_tissues
+            );
+        }
+        debug!("Reference: {} proteins", expected.len());
+        ReferenceBmc::bulk_import(self.mm, entries).await?;
+
+        let stmt =
+            "select ac,score_setting_id,global_label_string,tissue_label_string from labeldata";
+        let labels = sqlx::query_as::<_, LabelQuery>(stmt)
+            .fetch_all(&self.mm.db)
+            .await?;
+        let mut predicted: BTreeMap<(i32, &str), HashMap<i32, BTreeSet<String>>> = BTreeMap::new();
+        for label in &labels {
+            let ac_id = label.ac.parse::<i32>().map_err(|e| {
+                iwf::Error::Defined(format!("Invalid labeldata ac {}: {e}", label.ac))
+            })?;
+            for (column, value) in [
+                ("global", &label.global_label_string),
+                ("tissue", &label.tissue_label_string),
+            ] {
+                predicted
+                    .entry((label.score_setting_id, column))
+                    .or_default()
+                    .insert(ac_id, split_label(value));
+            }
+        }
+
+        let mut benchmarks = vec![];
+        let mut confusions = vec![];
+        for ((score_setting_id, column), predictions) in predicted {
+            let (scores, cells) = score(&expected, &predictions);
+            benchmarks.extend(scores.into_iter().map(|e| BenchmarkForCreate {
+                score_setting_id,
+                label_column: column.into(),
+                tissue: e.tissue,
+                n_tp: e.tp as i32,
+                n_fp: e.fp as i32,
+                n_fn: e.fn_ as i32,
+                precision: e.precision,
+                recall: e.recall,
+                f1: e.f1,
+            }));
+            confusions.extend(cells.into_iter().map(|e| ConfusionForCreate {
+                score_setting_id,
+                label_column: column.into(),
+                expected: e.expected,
+                predicted: e.predicted,
+                n: e.n as i32,
+            }));
+        }
+        BenchmarkBmc::bulk_import(self.mm, benchmarks).await?;
+        ConfusionBmc::bulk_import(self.mm, confusions).await?;
+        Ok(CalculateBenchmarkResult {})
+    }
+}
+
+#[derive(Debug)]
+pub struct CalculateBenchmarkResult {}
@@ -1,10 +1,15 @@
+use crate::cache;
 use crate::enrichment::gsea::{GseaInput, GseaResult, GseaTask};
 use camino::Utf8PathBuf;
 use ddbtbl::cluster::cluster_gsea::{GseaEnrBmc, GseaEnrForCreate};
+use ddbtbl::gls::ac::AcBmc;
 use iwf::sql::ModelManager;
 use iwf::{IwfExe, Result, WfParameters};
 use log::{debug, warn};
+use std::collections::HashMap;
 
+/// Over-representation of the `glsna` multilabels of every qmatrix, stored in
+/// `cluster_gsea` with the kind `glsn_<qmatrix name>`.
 #[derive(Clone)]
 pub struct CalculateEnrichmentTask<'a, T>
 where
@@ -14,11 +19,14 @@ where
     pub gmt: &'a Utf8PathBuf,
     pub params: &'a WfParameters,
     pub gsea: T,
+    pub native: bool,
+    pub force: bool,
 }
 
 #[derive(sqlx::FromRow)]
 struct GseaQmatrixQuery {
     qmatrix_id: i32,
+    name: String,
 }
 
 #[derive(sqlx::FromRow)]
@@ -32,15 +40,23 @@ where
     T: IwfExe + Clone,
 {
     pub async fn execute(self) -> Result<CalculateEnrichmentResult> {
-        let qmatrix_ids: Vec<GseaQmatrixQuery> =
-            sqlx::query_as::<_, GseaQmatrixQuery>("SELECT DISTINCT qmatrix_id FROM glsna;")
-                .fetch_all(&self.mm.db)
-                .await?;
-        for qmatrix_id in qmatrix_ids {
-            let stmt = format!("select entry as gene, multilabel as cluster from glsna inner join ac on ac_id = ac.id where qmatrix_id = {};", qmatrix_id.qmatrix_id);
-            let query_items: Vec<GseaQuery> = sqlx::query_as::<_, GseaQuery>(&stmt)
-                .fetch_all(&self.mm.db)
-                .await?;
+        let qmatrices: Vec<GseaQmatrixQuery> = sqlx::query_as::<_, GseaQmatrixQuery>(
+            "SELECT DISTINCT qmatrix_id, qmatrix.name FROM glsna inner join qmatrix on qmatrix_id = qmatrix.id;",
+        )
+        .fetch_all(&self.mm.db)
+        .await?;
+        let aliases = match self.native {
+            true => AcBmc::get_aliases(self.mm).await?,
+            false => HashMap::new(),
+        };
+        for qmatrix in qmatrices {
+            let kind = format!("glsn_{}", qmatrix.name);
+            let query_items: Vec<GseaQuery> = sqlx::query_as::<_, GseaQuery>(
+                "select entry as gene, multilabel as cluster from glsna inner join ac on ac_id = ac.id where qmatrix_id = $1;",
+            )
+            .bind(qmatrix.qmatrix_id)
+            .fetch_all(&self.mm.db)
+            .await?;
             let items = query_items
                 .into_iter()
                 .map(|e| GseaInput {
@@ -49,25 +65,36 @@ where
                 })
                 .collect();
             let gsea_task = GseaTask {
-                input_path: format!("gsea_{}_input.tsv", qmatrix_id.qmatrix_id).into(),
+                input_path: format!("gsea_{kind}_input.tsv").into(),
                 gmt_path: self.gmt.clone(),
-                output_path: format!("gsea_{}_output.tsv", qmatrix_id.qmatrix_id).into(),
+                output_path: format!("gsea_{kind}_output.tsv").into(),
                 ackind: "swissprot".into(),
 
                 ..Default::default()
             };
             gsea_task.write_input(&items).unwrap();
 
-            if !gsea_task.output_path.is_file() {
-                let gsea_result: GseaResult = self.gsea.execute(self.params, &gsea_task)?;
-                debug!("GseaResult: {gsea_result}");
+            let key = gsea_task.cache_key(self.native).map_err(|e| {
+                iwf::Error::Defined(format!("Cannot hash {}: {e}", gsea_task.input_path))
+            })?;
+            if self.force || !cache::is_valid(&gsea_task.output_path, &key) {
+                if self.native {
+                    gsea_task.run_native(&items, &aliases).map_err(|e| {
+                        iwf::Error::Defined(format!("Native enrichment failed: {e}"))
+                    })?;
+                } else {
+                    let gsea_result: GseaResult = self.gsea.execute(self.params, &gsea_task)?;
+                    debug!("GseaResult: {gsea_result}");
+                }
+                cache::store(&gsea_task.output_path, &key)
+                    .map_err(|e| iwf::Error::Defined(format!("Cannot store the cache key: {e}")))?;
             }
             match gsea_task.read_output() {
                 Ok(result) => {
                     let items = result
                         .into_iter()
                         .map(|e| GseaEnrForCreate {
-                            kind: format!("{}", qmatrix_id.qmatrix_id),
+                            kind: kind.clone(),
                             gene_set: e.gene_set,
                             term: e.term,
                             overlap: e.overlap,
@@ -82,10 +109,7 @@ where
                         .collect();
                     GseaEnrBmc::bulk_import(self.mm, items).await?;
                 }
-                Err(err) => warn!(
-                    "Could not read the output file for {}: {}",
-                    qmatrix_id.qmatrix_id, err
-                ),
+                Err(err) => warn!("Could not read the output file for {kind}: {err}"),
             }
         }
         Ok(CalculateEnrichmentResult {})
@@ -1,6 +1,8 @@
+use crate::cache;
 use crate::enrichment::gsea::{GseaInput, GseaResult, GseaTask};
 use camino::Utf8PathBuf;
 use ddbtbl::cluster::cluster_gsea::{GseaEnrBmc, GseaEnrForCreate};
+use ddbtbl::gls::ac::AcBmc;
 use iwf::sql::ModelManager;
 use iwf::{IwfExe, Result, WfParameters};
 #[allow(unused_imports)]
@@ -13,6 +15,8 @@ where
 {
     pub mm: &'a ModelManager,
     pub gsea: T,
+    pub native: bool,
+    pub force: bool,
     pub params: &'a WfParameters,
     pub gmt: &'a Utf8PathBuf,
 }
@@ -49,9 +53,21 @@ where
         };
         gsea_task.write_input(&items).unwrap();
 
-        if !gsea_task.output_path.is_file() {
-            let gsea_result: GseaResult = self.gsea.execute(self.params, &gsea_task)?;
-            warn!("GseaResult: {gsea_result}");
+        let key = gsea_task.cache_key(self.native).map_err(|e| {
+            iwf::Error::Defined(format!("Cannot hash {}: {e}", gsea_task.input_path))
+        })?;
+        if self.force || !cache::is_valid(&gsea_task.output_path, &key) {
+            if self.native {
+                let aliases = AcBmc::get_aliases(self.mm).await?;
+                gsea_task
+                    .run_native(&items, &aliases)
+                    .map_err(|e| iwf::Error::Defined(format!("Native enrichment failed: {e}")))?;
+            } else {
+                let gsea_result: GseaResult = self.gsea.execute(self.params, &gsea_task)?;
+                warn!("GseaResult: {gsea_result}");
+            }
+            cache::store(&gsea_task.output_path, &key)
+                .map_err(|e| iwf::Error::Defined(format!("Cannot store the cache key: {e}")))?;
         }
         match gsea_task.read_output() {
             Ok(result) => {
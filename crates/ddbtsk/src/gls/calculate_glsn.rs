@@ -0,0 +1,98 @@
+use ddbtbl::gls::glsn::{GlsnBmc, GlsnForCreate};
+use ddbtbl::gls::tissue::{Tissue, TissueRegistry};
+use ddbtbl::query;
+use iwf::sql::ModelManager;
+use iwf::Result;
+use log::debug;
+use std::collections::{BTreeMap, HashMap};
+
+/// Tissue scores of the proteins of atlas `name`: the mean share of a
+/// protein in the samples of a tissue, as a share of the sum of its tissue
+/// means. The scores of a protein sum to one; the `glsna` view turns them
+/// into multilabels.
+#[derive(Clone)]
+pub struct CalculateGlsnTask<'a> {
+    pub mm: &'a ModelManager,
+    pub tissues: &'a TissueRegistry,
+    pub name: &'a str,
+}
+
+#[derive(sqlx::FromRow)]
+struct GlsnQuery {
+    ac_id: i32,
+    ann_id: i32,
+    value: f64,
+}
+
+impl CalculateGlsnTask<'_> {
+    pub async fn execute(self) -> Result<CalculateGlsnResult> {
+        let Some((qmatrix_id,)) =
+            sqlx::query_as::<_, (i32,)>("select id from qmatrix where name = $1")
+                .bind(self.name)
+                .fetch_optional(&self.mm.db)
+                .await?
+        else {
+            return Err(iwf::Error::Defined(format!(
+                "No qmatrix for atlas {}",
+                self.name
+            )));
+        };
+        let groups: HashMap<i32, String> =
+            sqlx::query_as::<_, (i32, String)>("select id, global_grp from ann")
+                .fetch_all(&self.mm.db)
+                .await?
+                .into_iter()
+                .collect();
+        let (stmt, values) = query::qm_shares(self.name);
+        let items = sqlx::query_as_with::<_, GlsnQuery, _>(&stmt, values)
+            .fetch_all(&self.mm.db)
+            .await?;
+        debug!("N values of {}: {}", self.name, items.len());
+        let mut tissues: HashMap<&str, Tissue> = HashMap::new();
+        let mut sums: BTreeMap<i32, BTreeMap<Tissue, (f64, usize)>> = BTreeMap::new();
+        for item in items {
+            let Some(grp) = groups.get(&item.ann_id) else {
+                continue;
+            };
+            let tissue = match tissues.get(grp.as_str()) {
+                Some(tissue) => tissue.clone(),
+                None => {
+                    let tissue = self.tissues.resolve(grp)?;
+                    tissues.insert(grp, tissue.clone());
+                    tissue
+                }
+            };
+            let (sum, n) = sums
+                .entry(item.ac_id)
+                .or_default()
+                .entry(tissue)
+                .or_default();
+            *sum += item.value;
+            *n += 1;
+        }
+        let mut results = vec![];
+        for (ac_id, per_tissue) in sums {
+            let means: Vec<(Tissue, f64)> = per_tissue
+                .into_iter()
+                .map(|(tissue, (sum, n))| (tissue, sum / n as f64))
+                .collect();
+            let total: f64 = means.iter().map(|e| e.1).sum();
+            if total <= 0.0 {
+                continue;
+            }
+            for (tissue, mean) in means {
+                results.push(GlsnForCreate {
+                    qmatrix_id,
+                    ac_id,
+                    score: mean / total,
+                    tissue: tissue.name().into(),
+                });
+            }
+        }
+        GlsnBmc::bulk_import(self.mm, results).await?;
+        Ok(CalculateGlsnResult {})
+    }
+}
+
+#[derive(Debug)]
+pub struct CalculateGlsnResult {}
@@ -0,0 +1,171 @@
+use crate::cluster::cluster::{ClusterInput, ClusterTask};
+use crate::cluster::wkde::{WkdeInput, WkdeTask};
+use ddbtbl::gls::labelconfidence::{LabelConfidenceBmc, LabelConfidenceForCreate};
+use ddbtbl::gls::tissue::TissueRegistry;
+use ddbtbl::query;
+use iwf::sql::ModelManager;
+use iwf::Result;
+use log::debug;
+use rand::rngs::StdRng;
+use rand::seq::index::sample;
+use rand::SeedableRng;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Bootstrap of the atlas labels: every replicate clusters a random subset of
+/// the atlas samples with its own UMAP seed and relabels the embedding with
+/// the in-process WKDE. The share of replicates assigning a tissue to a protein
+/// is stored in `labelconfidence`.
+#[derive(Clone)]
+pub struct CalculateLabelConfidenceTask<'a> {
+    pub mm: &'a ModelManager,
+    pub tissues: &'a TissueRegistry,
+    pub name: &'a str,
+    pub replicates: usize,
+    /// Share of the samples used for the embedding of a replicate.
+    pub sample_fraction: f64,
+    pub fraction: f64,
+    pub bandwidth: f64,
+    pub min_value: f64,
+    pub no_class_criteria: f64,
+    pub max_n_labels: i32,
+}
+
+#[derive(sqlx::FromRow)]
+struct ConfidenceQuery {
+    ac_id: i32,
+    ann_id: i32,
+    value: f64,
+}
+
+impl CalculateLabelConfidenceTask<'_> {
+    pub async fn execute(self) -> Result<CalculateLabelConfidenceResult> {
+        let (stmt, values) = query::qm_values(self.name, None);
+        let items = sqlx::query_as_with::<_, ConfidenceQuery, _>(&stmt, values)
+            .fetch_all(&self.mm.db)
+            .await?;
+        let (wstmt, wvalues) = query::qm_weights(self.name);
+        let weights = sqlx::query_as_with::<_, ConfidenceQuery, _>(&wstmt, wvalues)
+            .fetch_all(&self.mm.db)
+            .await?;
+        let groups: HashMap<i32, String> =
+            sqlx::query_as::<_, (i32, String)>("select id, global_grp from ann")
+                .fetch_all(&self.mm.db)
+                .await?
+                .into_iter()
+                .collect();
+        let samples: Vec<i32> = items
+            .iter()
+            .map(|e| e.ann_id)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let n_samples = ((samples.len() as f64 * self.sample_fraction).round() as usize)
+            .clamp(2.min(samples.len()), samples.len());
+        debug!(
+            "Bootstrap {}: {} replicates of {n_samples}/{} samples",
+            self.name,
+            self.replicates,
+            samples.len()
+        );
+
+        let wkde_task = WkdeTask {
+            fraction: self.fraction,
+            bandwidth: self.bandwidth,
+            min_value: self.min_value,
+            no_class_criteria: self.no_class_criteria,
+            max_n_labels: self.max_n_labels,
+            ..Default::default()
+        };
+        let mut counts: HashMap<i32, HashMap<String, usize>> = HashMap::new();
+        for replicate in 0..self.replicates {
+            let mut rng = StdRng::seed_from_u64(replicate as u64);
+            let selected: HashSet<i32> = sample(&mut rng, samples.len(), n_samples)
+                .into_iter()
+                .map(|i| samples[i])
+                .collect();
+            let input: Vec<ClusterInput> = items
+                .iter()
+                .filter(|e| selected.contains(&e.ann_id))
+                .map(|e| ClusterInput {
+                    idx: format!("{}", e.ac_id),
+                    col: format!("c{}", e.ann_id),
+                    value: e.value,
+                })
+                .collect();
+            let cluster_task = ClusterTask {
+                random_state: replicate as i32,
+                ..ClusterTask::atlas()
+            };
+            let embedding: HashMap<i32, (f64, f64)> = cluster_task
+                .cluster_native(&input)
+                .map_err(|e| iwf::Error::Defined(format!("Bootstrap clustering failed: {e}")))?
+                .into_iter()
+                .filter_map(|e| Some((e.idx.parse().ok()?, (e.y1, e.y2))))
+                .collect();
+            let wkde_input: Vec<WkdeInput> = weights
+                .iter()
+                .filter_map(|e| {
+                    let (y1, y2) = embedding.get(&e.ac_id)?;
+                    Some(WkdeInput {
+                        idx: format!("{}", e.ac_id),
+                        y1: *y1,
+                        y2: *y2,
+                        col: format!("{}", e.ann_id),
+                        weights: e.value,
+                    })
+                })
+                .collect();
+            let (_, assignments) = wkde_task
+                .run_native(&wkde_input)
+                .map_err(|e| iwf::Error::Defined(format!("Bootstrap wkde failed: {e}")))?;
+            for assignment in assignments {
+                // Background pixels carry no tissue; their proteins count as
+                // unassigned in this replicate.
+                if matches!(assignment.clss.as_ref(), "none" | "common") {
+                    continue;
+                }
+                let ann_ids: Vec<i32> = assignment
+                    .clss
+                    .split('.')
+                    .map(|s| s.parse())
+                    .collect::<std::result::Result<_, _>>()
+                    .map_err(|e| {
+                        iwf::Error::Defined(format!("Invalid label {}: {e}", assignment.clss))
+                    })?;
+                let mut assigned = HashSet::new();
+                for ann_id in ann_ids {
+                    let group = groups.get(&ann_id).ok_or_else(|| {
+                        iwf::Error::Defined(format!("Unknown annotation: {ann_id}"))
+                    })?;
+                    assigned.insert(self.tissues.resolve(group)?.to_string());
+                }
+                let ac_id = assignment.idx.parse::<i32>().map_err(|e| {
+                    iwf::Error::Defined(format!("Invalid protein {}: {e}", assignment.idx))
+                })?;
+                let protein = counts.entry(ac_id).or_default();
+                for tissue in assigned {
+                    *protein.entry(tissue).or_insert(0) += 1;
+                }
+            }
+        }
+
+        let n_replicates = self.replicates as i32;
+        let mut entries = vec![];
+        for (ac_id, tissues) in counts {
+            for (tissue, n) in tissues {
+                entries.push(LabelConfidenceForCreate {
+                    ac_id,
+                    kind: self.name.into(),
+                    tissue,
+                    n_replicates,
+                    confidence: n as f64 / self.replicates as f64,
+                });
+            }
+        }
+        LabelConfidenceBmc::bulk_import(self.mm, entries).await?;
+        Ok(CalculateLabelConfidenceResult {})
+    }
+}
+
+#[derive(Debug)]
+pub struct CalculateLabelConfidenceResult {}
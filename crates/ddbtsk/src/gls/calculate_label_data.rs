@@ -1,5 +1,9 @@
-use ddbtbl::gls::labeldata::{DataSet, FullLabelData, LabelDataBmc, LabelDataForCreate, Tissue};
+use ddbtbl::gls::atlas::AtlasBmc;
+use ddbtbl::gls::labelchange::{LabelChangeBmc, LabelChangeForCreate};
+use ddbtbl::gls::labelconfidence::LabelConfidenceBmc;
+use ddbtbl::gls::labeldata::{FullLabelData, LabelDataBmc, LabelDataForCreate};
 use ddbtbl::gls::scoresetting::{ScoreSettingBmc, ScoreSettingForCreate};
+use ddbtbl::gls::tissue::TissueRegistry;
 use iwf::ctx::Ctx;
 use iwf::sql::ModelManager;
 use iwf::Result;
@@ -10,6 +14,10 @@ use std::collections::{hash_map::Entry, HashMap, HashSet};
 pub struct CalculateLabelDataTask<'a> {
     pub ctx: &'a Ctx,
     pub mm: &'a ModelManager,
+    pub tissues: &'a TissueRegistry,
+    /// Every setting gets its own `labeldata` rows; the first is the reference
+    /// of the `labelchange` summary.
+    pub settings: &'a [ScoreSettingForCreate],
 }
 
 #[allow(dead_code)]
@@ -25,6 +33,8 @@ struct LabelDataQuery {
 impl CalculateLabelDataTask<'_> {
     pub async fn execute(self) -> Result<CalculateLabelDataResult> {
         let stmt= "select kind,global_grp as tissues,entry,ac_id,ann.id as ann_id from wkdelabel inner join ac on ac_id = ac.id inner join ann on ann.id = any(labels)";
+        let registry = AtlasBmc::load(self.mm).await?;
+        let atlases: HashMap<&str, _> = registry.iter().map(|e| (e.name.as_str(), e)).collect();
         let mut lbldata = HashMap::new();
         let items: Vec<LabelDataQuery> = sqlx::query_as::<_, LabelDataQuery>(stmt)
             .fetch_all(&self.mm.db)
@@ -38,23 +48,65 @@ impl CalculateLabelDataTask<'_> {
                     ..Default::default()
                 }),
             };
-            let t = match l.label.entry(DataSet::from_str(&item.kind)) {
+            let atlas = atlases
+                .get(item.kind.as_str())
+                .copied()
+                .ok_or_else(|| iwf::Error::Defined(format!("Unknown atlas: {}", item.kind)))?;
+            let t = match l.label.entry(atlas.clone()) {
                 Entry::Occupied(o) => o.into_mut(),
                 Entry::Vacant(v) => v.insert(HashSet::new()),
             };
-            t.insert(Tissue::from_str(&item.tissues).unwrap());
+            t.insert(self.tissues.resolve(&item.tissues)?);
         }
-        let score_setting = ScoreSettingForCreate::default();
-        let score_id = ScoreSettingBmc::create(self.ctx, self.mm, score_setting.clone()).await?;
-        let mut vv: Vec<LabelDataForCreate> = vec![];
-        for (_protein, mut label) in lbldata {
-            label.score_setting_id = score_id;
-            label.count(&score_setting);
-            label.create_labels(&score_setting);
-            label.create_global_label(&score_setting);
-            vv.push(label.into());
+        let confidence = LabelConfidenceBmc::get_confidence(self.mm).await?;
+        let mut reference: Option<(i32, HashMap<i32, [String; 3]>)> = None;
+        let mut changes = vec![];
+        for score_setting in self.settings {
+            let score_id =
+                ScoreSettingBmc::create(self.ctx, self.mm, score_setting.clone()).await?;
+            debug!("Calculating labels for {score_setting} ({score_id})");
+            let mut vv: Vec<LabelDataForCreate> = vec![];
+            let mut labels = HashMap::new();
+            for (protein, label) in &lbldata {
+                let mut label = label.clone();
+                label.score_setting_id = score_id;
+                label.count(score_setting, &registry);
+                label.create_labels(score_setting);
+                label.create_global_label(score_setting);
+                label.create_label_confidence(confidence.get(protein));
+                label.create_entropy_labels();
+                labels.insert(
+                    *protein,
+                    [
+                        label.global_label_string.clone(),
+                        label.tissue_label_string.clone(),
+                        label.cell_label_string.clone(),
+                    ],
+                );
+                vv.push(label.into());
+            }
+            LabelDataBmc::bulk_import(self.mm, vv).await?;
+            match &reference {
+                None => reference = Some((score_id, labels)),
+                Some((reference_id, reference_labels)) => {
+                    let n_changed = |i: usize| {
+                        labels
+                            .iter()
+                            .filter(|(k, v)| reference_labels.get(*k).map(|r| &r[i]) != Some(&v[i]))
+                            .count() as i32
+                    };
+                    changes.push(LabelChangeForCreate {
+                        score_setting_id: score_id,
+                        reference_id: *reference_id,
+                        n_proteins: labels.len() as i32,
+                        n_global_changed: n_changed(0),
+                        n_tissue_changed: n_changed(1),
+                        n_cell_changed: n_changed(2),
+                    });
+                }
+            }
         }
-        LabelDataBmc::bulk_import(self.mm, vv).await?;
+        LabelChangeBmc::bulk_import(self.mm, changes).await?;
         Ok(CalculateLabelDataResult {})
     }
 }
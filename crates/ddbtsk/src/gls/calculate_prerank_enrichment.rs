@@ -0,0 +1,85 @@
+use crate::enrichment::gmt::{gene_set_name, match_gene, read_gmt, universe};
+use crate::enrichment::prerank::{prerank, PrerankParams};
+use camino::Utf8PathBuf;
+use ddbtbl::cluster::cluster_gsea::{GseaEnrBmc, GseaEnrForCreate};
+use ddbtbl::gls::ac::AcBmc;
+use ddbtbl::gls::scoresetting::ScoreSettingBmc;
+use iwf::sql::ModelManager;
+use iwf::Result;
+use log::debug;
+
+/// Pre-ranked GSEA on the cumulative `atlas` or `cell` score of `labeldata`
+/// under the reference score setting.
+/// Rows are stored in `cluster_gsea` with kind `prerank_<score>`; the ES goes
+/// to `odds_ratio`, the NES to `combined_score` and the FDR to `adjusted_pvalue`.
+#[derive(Clone)]
+pub struct CalculatePrerankEnrichmentTask<'a> {
+    pub mm: &'a ModelManager,
+    pub gmt: &'a Utf8PathBuf,
+    pub score: &'a str,
+    pub permutations: usize,
+}
+
+#[derive(sqlx::FromRow)]
+struct PrerankQuery {
+    gene: String,
+    score: f64,
+}
+
+impl CalculatePrerankEnrichmentTask<'_> {
+    pub async fn execute(self) -> Result<CalculatePrerankEnrichmentResult> {
+        let column = match self.score {
+            "atlas" => "cummulative_atlas_score",
+            "cell" => "cummulative_cell_score",
+            _ => {
+                return Err(iwf::Error::Defined(format!(
+                    "Unknown prerank score: {}",
+                    self.score
+                )))
+            }
+        };
+        let kind = format!("prerank_{}", self.score);
+        let stmt = format!("select entry as gene,{column} as score from labeldata inner join ac on ac::int = ac.id where score_setting_id = $1");
+        let items = sqlx::query_as::<_, PrerankQuery>(&stmt)
+            .bind(ScoreSettingBmc::reference_id(self.mm).await?)
+            .fetch_all(&self.mm.db)
+            .await?;
+        debug!("N ranked proteins for {kind}: {}", items.len());
+
+        let sets = read_gmt(self.gmt)
+            .map_err(|e| iwf::Error::Defined(format!("Cannot read {}: {e}", self.gmt)))?;
+        let universe = universe(&sets);
+        let aliases = AcBmc::get_aliases(self.mm).await?;
+        let ranking: Vec<(String, f64)> = items
+            .into_iter()
+            .map(|e| (match_gene(&e.gene, &aliases, &universe), e.score))
+            .collect();
+        let params = PrerankParams {
+            permutations: self.permutations,
+            ..Default::default()
+        };
+        let gene_set = gene_set_name(self.gmt);
+        let n_query_genes = ranking.len() as i32;
+        let items: Vec<GseaEnrForCreate> = prerank(&ranking, &sets, &params)
+            .into_iter()
+            .map(|e| GseaEnrForCreate {
+                kind: kind.clone(),
+                gene_set: gene_set.clone(),
+                term: e.term,
+                overlap: format!("{}/{}", e.leading_edge.len(), e.hits),
+                pvalue: e.pvalue,
+                adjusted_pvalue: e.fdr,
+                odds_ratio: e.es,
+                combined_score: e.nes,
+                genes: e.leading_edge.join(";"),
+                cluster_id: kind.clone(),
+                n_query_genes,
+            })
+            .collect();
+        GseaEnrBmc::bulk_import(self.mm, items).await?;
+        Ok(CalculatePrerankEnrichmentResult {})
+    }
+}
+
+#[derive(Debug)]
+pub struct CalculatePrerankEnrichmentResult {}
@@ -1,6 +1,8 @@
+use crate::cache;
 use crate::enrichment::gsea::{GseaInput, GseaResult, GseaTask};
 use camino::Utf8PathBuf;
 use ddbtbl::cluster::cluster_gsea::{GseaEnrBmc, GseaEnrForCreate};
+use ddbtbl::gls::ac::AcBmc;
 use iwf::sql::ModelManager;
 use iwf::{IwfExe, Result, WfParameters};
 use log::warn;
@@ -15,6 +17,8 @@ where
     pub gmt: &'a Utf8PathBuf,
     pub params: &'a WfParameters,
     pub gsea: T,
+    pub native: bool,
+    pub force: bool,
 }
 
 #[derive(sqlx::FromRow)]
@@ -42,6 +46,10 @@ where
             knd.insert(item.gene.clone(), item.cluster.clone());
         }
 
+        let aliases = match self.native {
+            true => AcBmc::get_aliases(self.mm).await?,
+            false => HashMap::new(),
+        };
         for (kind, entries) in data {
             let items = entries
                 .into_iter()
@@ -60,8 +68,19 @@ where
             };
             gsea_task.write_input(&items).unwrap();
 
-            if !gsea_task.output_path.is_file() {
-                let _gsea_result: GseaResult = self.gsea.execute(self.params, &gsea_task)?;
+            let key = gsea_task.cache_key(self.native).map_err(|e| {
+                iwf::Error::Defined(format!("Cannot hash {}: {e}", gsea_task.input_path))
+            })?;
+            if self.force || !cache::is_valid(&gsea_task.output_path, &key) {
+                if self.native {
+                    gsea_task.run_native(&items, &aliases).map_err(|e| {
+                        iwf::Error::Defined(format!("Native enrichment failed: {e}"))
+                    })?;
+                } else {
+                    let _gsea_result: GseaResult = self.gsea.execute(self.params, &gsea_task)?;
+                }
+                cache::store(&gsea_task.output_path, &key)
+                    .map_err(|e| iwf::Error::Defined(format!("Cannot store the cache key: {e}")))?;
             }
             match gsea_task.read_output() {
                 Ok(result) => {
@@ -0,0 +1,66 @@
+use ddbtbl::gls::scaling::Scaling;
+use ddbtbl::gls::zscore::{ZscoreBmc, ZscoreForCreate, MEASURED, WITH_IMPUTED};
+use iwf::sql::ModelManager;
+use iwf::Result;
+use log::debug;
+use std::collections::BTreeMap;
+
+/// Per-protein z-scores of the normalised values across the samples of every
+/// qmatrix, once over the measured cells and once including the imputed ones.
+#[derive(Clone)]
+pub struct CalculateZscoreTask<'a> {
+    pub mm: &'a ModelManager,
+}
+
+#[derive(sqlx::FromRow)]
+struct ZscoreQuery {
+    ac_id: i32,
+    ann_id: i32,
+    norm_value: f64,
+    measured: bool,
+}
+
+impl CalculateZscoreTask<'_> {
+    pub async fn execute(self) -> Result<CalculateZscoreResult> {
+        let qmatrices: Vec<(i32,)> = sqlx::query_as("select id from qmatrix order by id")
+            .fetch_all(&self.mm.db)
+            .await?;
+        for (qmatrix_id,) in qmatrices {
+            let items = sqlx::query_as::<_, ZscoreQuery>(
+                "select ac_id, ann_id, norm_value, measured from qm where qmatrix_id = $1",
+            )
+            .bind(qmatrix_id)
+            .fetch_all(&self.mm.db)
+            .await?;
+            let mut proteins: BTreeMap<i32, Vec<ZscoreQuery>> = BTreeMap::new();
+            for item in items {
+                proteins.entry(item.ac_id).or_default().push(item);
+            }
+            let mut results = vec![];
+            for (kind, with_imputed) in [(MEASURED, false), (WITH_IMPUTED, true)] {
+                for (ac_id, cells) in &proteins {
+                    let cells: Vec<&ZscoreQuery> = cells
+                        .iter()
+                        .filter(|e| with_imputed || e.measured)
+                        .collect();
+                    let values: Vec<f64> = cells.iter().map(|e| e.norm_value).collect();
+                    for (cell, zscore) in cells.iter().zip(Scaling::Zscore.apply(&values)) {
+                        results.push(ZscoreForCreate {
+                            ac_id: *ac_id,
+                            ann_id: cell.ann_id,
+                            qmatrix_id,
+                            zscore,
+                            kind: kind.into(),
+                        });
+                    }
+                }
+            }
+            debug!("Zscores of qmatrix {qmatrix_id}: {}", results.len());
+            ZscoreBmc::bulk_import(self.mm, results).await?;
+        }
+        Ok(CalculateZscoreResult {})
+    }
+}
+
+#[derive(Debug)]
+pub struct CalculateZscoreResult {}
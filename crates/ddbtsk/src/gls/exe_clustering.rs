@@ -1,6 +1,9 @@
+use crate::cache;
 use crate::cluster::cluster::{ClusterInput, ClusterOutput, ClusterResult, ClusterTask};
+use crate::workdir;
 use ddbtbl::cluster::cluster::{ClusterBmc, ClusterForCreate};
 use ddbtbl::cluster::umap::{UmapBmc, UmapForCreate};
+use ddbtbl::query;
 use iwf::ctx::Ctx;
 use iwf::sql::ModelManager;
 use iwf::IwfExe;
@@ -19,6 +22,8 @@ where
     pub mm: &'a ModelManager,
     pub gsea: T,
     pub params: &'a WfParameters,
+    pub native: bool,
+    pub force: bool,
     pub name: &'a str,
 }
 
@@ -64,17 +69,16 @@ where
     T: IwfExe + Clone,
 {
     pub async fn execute(self) -> Result<ExeClusteringResult> {
+        let dir = workdir::create("cluster", self.name)
+            .map_err(|e| iwf::Error::Defined(format!("Cannot create the workdir: {e}")))?;
         let cluster_task = ClusterTask {
-            input: format!("{}_umap_input.tsv", self.name).into(),
-            output: format!("{}_umap_output.tsv", self.name).into(),
-            min_cluster_size: 20,
-            min_dist: 0.01,
-            metric: "correlation".into(),
-            ..Default::default()
+            input: dir.join("umap_input.tsv"),
+            output: dir.join("umap_output.tsv"),
+            ..ClusterTask::atlas()
         };
-        let stmt = format!("select ac_id, ann_id, scaled_value as value from qm inner join qmatrix on qmatrix_id = qmatrix.id where qmatrix.name = '{}'", self.name);
+        let (stmt, values) = query::qm_values(self.name, None);
         debug!("Stmt: {stmt}");
-        let items: Vec<ClusterInput> = sqlx::query_as::<_, ClusterQuery>(&stmt)
+        let items: Vec<ClusterInput> = sqlx::query_as_with::<_, ClusterQuery, _>(&stmt, values)
             .fetch_all(&self.mm.db)
             .await?
             .into_iter()
@@ -86,9 +90,21 @@ where
             .collect();
         println!("N {}: {}", self.name, items.len());
         cluster_task.write_input(&items).unwrap();
-        if !cluster_task.output.is_file() {
-            let cluster_result: ClusterResult = self.gsea.execute(self.params, &cluster_task)?;
-            debug!("ClusterResult: {cluster_result}");
+        let key = cluster_task
+            .cache_key(self.native)
+            .map_err(|e| iwf::Error::Defined(format!("Cannot hash {}: {e}", cluster_task.input)))?;
+        if self.force || !cache::is_valid(&cluster_task.output, &key) {
+            if self.native {
+                cluster_task
+                    .run_native(&items)
+                    .map_err(|e| iwf::Error::Defined(format!("Native clustering failed: {e}")))?;
+            } else {
+                let cluster_result: ClusterResult =
+                    self.gsea.execute(self.params, &cluster_task)?;
+                debug!("ClusterResult: {cluster_result}");
+            }
+            cache::store(&cluster_task.output, &key)
+                .map_err(|e| iwf::Error::Defined(format!("Cannot store the cache key: {e}")))?;
         }
         let results = cluster_task.read_output().unwrap();
         read_and_store_clusters(self.ctx, self.mm, &results, self.name).await?;
@@ -1,7 +1,8 @@
 use crate::cluster::wkde::{WkdeInput, WkdeResult, WkdeTask};
-use camino::Utf8PathBuf;
+use crate::workdir;
 use ddbtbl::gls::wkdelabel::{WkdeLabelBmc, WkdeLabelForCreate};
 use ddbtbl::gls::wkdetag::{WkdeTagBmc, WkdeTagForCreate};
+use ddbtbl::query;
 use iwf::sql::ModelManager;
 use iwf::IwfExe;
 use iwf::Result;
@@ -33,6 +34,8 @@ where
     pub bandwidth: f64,
     pub min_value: f64,
     pub no_class_criteria: f64,
+    pub max_n_labels: i32,
+    pub native: bool,
 }
 
 impl<T> ExeWkdeTask<'_, T>
@@ -40,8 +43,8 @@ where
     T: IwfExe + Clone,
 {
     pub async fn execute(self) -> Result<ExeWkdeResult> {
-        let qstmt = format!("select idx,y1,y2,ann_id::text as col,scaled_value as weights from umap inner join qm on umap.idx::int4 = qm.ac_id inner join ann on ann_id = ann.id where kind = '{}' and qmatrix_type = '{}'", self.kind, self.kind);
-        let quant_items: Vec<WkdeInput> = sqlx::query_as::<_, WkdeQuery>(&qstmt)
+        let (qstmt, values) = query::wkde_input(self.kind);
+        let quant_items: Vec<WkdeInput> = sqlx::query_as_with::<_, WkdeQuery, _>(&qstmt, values)
             .fetch_all(&self.mm.db)
             .await?
             .into_iter()
@@ -54,7 +57,9 @@ where
             })
             .collect();
 
-        let input_path = Utf8PathBuf::from(format!("wkde_{}.tsv", self.kind));
+        let input_path = workdir::create("wkde", self.kind)
+            .map_err(|e| iwf::Error::Defined(format!("Cannot create the workdir: {e}")))?
+            .join("input.tsv");
         println!(
             "wkde: N quant returned: {}; {input_path}",
             quant_items.len()
@@ -65,18 +70,23 @@ where
             bandwidth: self.bandwidth,
             min_value: self.min_value,
             no_class_criteria: self.no_class_criteria,
-            max_n_labels: 2,
+            max_n_labels: self.max_n_labels,
             name: format!("wkde_{}", self.kind),
             ..Default::default()
         };
-        task.write_input(&quant_items).unwrap();
-        let result: WkdeResult = self.wkde.execute(self.params, &task)?;
-        println!("Len RR: qunat: {}; {}", quant_items.len(), result);
-        let (data1, data2) = match task.read_output() {
-            Ok(o) => o,
-            Err(e) => {
-                warn!("Reading output failed: {e}; task: {task}");
-                return Ok(ExeWkdeResult {});
+        let (data1, data2) = if self.native {
+            task.run_native(&quant_items)
+                .map_err(|e| iwf::Error::Defined(format!("Native wkde failed: {e}")))?
+        } else {
+            task.write_input(&quant_items).unwrap();
+            let result: WkdeResult = self.wkde.execute(self.params, &task)?;
+            println!("Len RR: qunat: {}; {}", quant_items.len(), result);
+            match task.read_output() {
+                Ok(o) => o,
+                Err(e) => {
+                    warn!("Reading output failed: {e}; task: {task}");
+                    return Ok(ExeWkdeResult {});
+                }
             }
         };
         let in1: Vec<WkdeTagForCreate> = data1
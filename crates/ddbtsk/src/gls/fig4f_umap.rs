@@ -1,4 +1,6 @@
+use crate::cache;
 use crate::cluster::cluster::{ClusterInput, ClusterOutput, ClusterResult, ClusterTask};
+use crate::workdir;
 use ddbtbl::cluster::cluster::{ClusterBmc, ClusterForCreate};
 use ddbtbl::cluster::umap::{UmapBmc, UmapForCreate};
 use iwf::ctx::Ctx;
@@ -18,6 +20,8 @@ where
     pub mm: &'a ModelManager,
     pub gsea: T,
     pub params: &'a WfParameters,
+    pub native: bool,
+    pub force: bool,
 }
 
 #[derive(sqlx::FromRow)]
@@ -32,9 +36,11 @@ where
     T: IwfExe + Clone,
 {
     pub async fn execute(self) -> Result<Fig4fUmapResult> {
+        let dir = workdir::create("cluster", "fig4f")
+            .map_err(|e| iwf::Error::Defined(format!("Cannot create the workdir: {e}")))?;
         let cluster_task = ClusterTask {
-            input: "f4f_umap_input.tsv".into(),
-            output: "f4f_umap_output.tsv".into(),
+            input: dir.join("umap_input.tsv"),
+            output: dir.join("umap_output.tsv"),
             min_cluster_size: 20,
             min_dist: 0.01,
             metric: "correlation".into(),
@@ -54,8 +60,22 @@ where
             })
             .collect();
         cluster_task.write_input(&items).unwrap();
-        let cluster_result: ClusterResult = self.gsea.execute(self.params, &cluster_task)?;
-        debug!("ClusterResult: {cluster_result}");
+        let key = cluster_task
+            .cache_key(self.native)
+            .map_err(|e| iwf::Error::Defined(format!("Cannot hash {}: {e}", cluster_task.input)))?;
+        if self.force || !cache::is_valid(&cluster_task.output, &key) {
+            if self.native {
+                cluster_task
+                    .run_native(&items)
+                    .map_err(|e| iwf::Error::Defined(format!("Native clustering failed: {e}")))?;
+            } else {
+                let cluster_result: ClusterResult =
+                    self.gsea.execute(self.params, &cluster_task)?;
+                debug!("ClusterResult: {cluster_result}");
+            }
+            cache::store(&cluster_task.output, &key)
+                .map_err(|e| iwf::Error::Defined(format!("Cannot store the cache key: {e}")))?;
+        }
         let results = cluster_task.read_output().unwrap();
         read_and_store_clusters(self.ctx, self.mm, &results, "fig4f").await?;
 
@@ -1,6 +1,9 @@
+use crate::cache;
 use crate::cluster::cluster::{ClusterInput, ClusterOutput, ClusterResult, ClusterTask};
+use crate::workdir;
 use ddbtbl::cluster::cluster::{ClusterBmc, ClusterForCreate};
 use ddbtbl::cluster::umap::{UmapBmc, UmapForCreate};
+use ddbtbl::gls::scoresetting::ScoreSettingBmc;
 use iwf::ctx::Ctx;
 use iwf::sql::ModelManager;
 use iwf::{IwfExe, Result, WfParameters};
@@ -15,6 +18,8 @@ where
     pub mm: &'a ModelManager,
     pub ctx: &'a Ctx,
     pub params: &'a WfParameters,
+    pub native: bool,
+    pub force: bool,
     pub gsea: T,
 }
 
@@ -30,16 +35,19 @@ where
     T: IwfExe + Clone,
 {
     pub async fn execute(self) -> Result<MiPatientUmapResult> {
+        let dir = workdir::create("cluster", "fig4j")
+            .map_err(|e| iwf::Error::Defined(format!("Cannot create the workdir: {e}")))?;
         let cluster_task = ClusterTask {
-            input: "mi_patient_umap_input.tsv".into(),
-            output: "mi_patient_umap_output.tsv".into(),
+            input: dir.join("umap_input.tsv"),
+            output: dir.join("umap_output.tsv"),
             min_cluster_size: 20,
             min_dist: 0.01,
             metric: "correlation".into(),
             ..Default::default()
         };
-        let stmt = "select qm.ac_id,qm.ann_id,qm.scaled_value as value from qm inner join qmatrix on qmatrix_id = qmatrix.id inner join ac on ac_id = ac.id inner join labeldata on qm.ac_id = labeldata.ac::int where qmatrix.name = 'plmi' and global_label_string = 'heart'";
+        let stmt = "select qm.ac_id,qm.ann_id,qm.scaled_value as value from qm inner join qmatrix on qmatrix_id = qmatrix.id inner join ac on ac_id = ac.id inner join labeldata on qm.ac_id = labeldata.ac::int where qmatrix.name = 'plmi' and global_label_string = 'heart' and score_setting_id = $1";
         let items: Vec<ClusterInput> = sqlx::query_as::<_, PatientUmapQuery>(stmt)
+            .bind(ScoreSettingBmc::reference_id(self.mm).await?)
             .fetch_all(&self.mm.db)
             .await?
             .into_iter()
@@ -54,9 +62,21 @@ where
             return Ok(MiPatientUmapResult {});
         }
         cluster_task.write_input(&items).unwrap();
-        if !cluster_task.output.is_file() {
-            let cluster_result: ClusterResult = self.gsea.execute(self.params, &cluster_task)?;
-            debug!("ClusterResult: {cluster_result}");
+        let key = cluster_task
+            .cache_key(self.native)
+            .map_err(|e| iwf::Error::Defined(format!("Cannot hash {}: {e}", cluster_task.input)))?;
+        if self.force || !cache::is_valid(&cluster_task.output, &key) {
+            if self.native {
+                cluster_task
+                    .run_native(&items)
+                    .map_err(|e| iwf::Error::Defined(format!("Native clustering failed: {e}")))?;
+            } else {
+                let cluster_result: ClusterResult =
+                    self.gsea.execute(self.params, &cluster_task)?;
+                debug!("ClusterResult: {cluster_result}");
+            }
+            cache::store(&cluster_task.output, &key)
+                .map_err(|e| iwf::Error::Defined(format!("Cannot store the cache key: {e}")))?;
         }
         let results = cluster_task.read_output().unwrap();
         read_and_store_clusters(self.ctx, self.mm, &results, "fig4j").await?;
@@ -1,9 +1,14 @@
+pub mod calculate_benchmark;
 pub mod calculate_enrichment;
 pub mod calculate_enrichment_fig2e;
 pub mod calculate_enrichment_list;
+pub mod calculate_glsn;
+pub mod calculate_label_confidence;
 pub mod calculate_label_data;
+pub mod calculate_prerank_enrichment;
 pub mod calculate_variance;
 pub mod calculate_wkde_enrichment;
+pub mod calculate_zscore;
 pub mod exe_clustering;
 pub mod exe_wkde;
 pub mod fig4f_umap;
@@ -11,14 +16,23 @@ pub mod fig4g_umap;
 pub mod mi_patient_umap;
 pub mod sepsis_patient_umap;
 
+pub use calculate_benchmark::{CalculateBenchmarkResult, CalculateBenchmarkTask};
 pub use calculate_enrichment::{CalculateEnrichmentResult, CalculateEnrichmentTask};
 pub use calculate_enrichment_fig2e::{
     CalculateEnrichmentFig2eResult, CalculateEnrichmentFig2eTask,
 };
 pub use calculate_enrichment_list::{CalculateEnrichmentListResult, CalculateEnrichmentListTask};
+pub use calculate_glsn::{CalculateGlsnResult, CalculateGlsnTask};
+pub use calculate_label_confidence::{
+    CalculateLabelConfidenceResult, CalculateLabelConfidenceTask,
+};
 pub use calculate_label_data::{CalculateLabelDataResult, CalculateLabelDataTask};
+pub use calculate_prerank_enrichment::{
+    CalculatePrerankEnrichmentResult, CalculatePrerankEnrichmentTask,
+};
 pub use calculate_variance::{CalculateVarianceResult, CalculateVarianceTask};
 pub use calculate_wkde_enrichment::{CalculateWkdeEnrichmentResult, CalculateWkdeEnrichmentTask};
+pub use calculate_zscore::{CalculateZscoreResult, CalculateZscoreTask};
 pub use exe_clustering::{ExeClusteringResult, ExeClusteringTask};
 pub use exe_wkde::{ExeWkdeResult, ExeWkdeTask};
 pub use fig4f_umap::{Fig4fUmapResult, Fig4fUmapTask};
@@ -1,6 +1,9 @@
+use crate::cache;
 use crate::cluster::cluster::{ClusterInput, ClusterOutput, ClusterResult, ClusterTask};
+use crate::workdir;
 use ddbtbl::cluster::cluster::{ClusterBmc, ClusterForCreate};
 use ddbtbl::cluster::umap::{UmapBmc, UmapForCreate};
+use ddbtbl::query;
 use iwf::ctx::Ctx;
 use iwf::sql::ModelManager;
 use iwf::{IwfExe, Result, WfParameters};
@@ -15,6 +18,8 @@ where
     pub ctx: &'a Ctx,
     pub mm: &'a ModelManager,
     pub params: &'a WfParameters,
+    pub native: bool,
+    pub force: bool,
     pub protein_list: &'a Vec<String>,
     pub gsea: T,
 }
@@ -31,19 +36,20 @@ where
     T: IwfExe + Clone,
 {
     pub async fn execute(self) -> Result<SepsisPatientUmapResult> {
+        let dir = workdir::create("cluster", "fig4n")
+            .map_err(|e| iwf::Error::Defined(format!("Cannot create the workdir: {e}")))?;
         let cluster_task = ClusterTask {
-            input: "f4n_umap_input.tsv".into(),
-            output: "f4n_umap_output.tsv".into(),
+            input: dir.join("umap_input.tsv"),
+            output: dir.join("umap_output.tsv"),
             min_cluster_size: 20,
             min_dist: 0.01,
             metric: "correlation".into(),
             n_neighbors: 5,
             ..Default::default()
         };
-        let selvec: Vec<String> = self.protein_list.iter().map(|e| format!("'{e}'")).collect();
-        let stmt = format!("select ac_id,ann_id,scaled_value as value from qm inner join qmatrix on qmatrix_id = qmatrix.id inner join ac on ac_id = ac.id where qmatrix.name = 'plsepsis' and entry in ({})", selvec.join(","));
+        let (stmt, values) = query::qm_values("plsepsis", Some(self.protein_list.as_slice()));
         debug!("Stmt: {stmt}");
-        let items: Vec<ClusterInput> = sqlx::query_as::<_, PatientUmapQuery>(&stmt)
+        let items: Vec<ClusterInput> = sqlx::query_as_with::<_, PatientUmapQuery, _>(&stmt, values)
             .fetch_all(&self.mm.db)
             .await?
             .into_iter()
@@ -54,9 +60,21 @@ where
             })
             .collect();
         cluster_task.write_input(&items).unwrap();
-        if !cluster_task.output.is_file() {
-            let cluster_result: ClusterResult = self.gsea.execute(self.params, &cluster_task)?;
-            debug!("ClusterResult: {cluster_result}");
+        let key = cluster_task
+            .cache_key(self.native)
+            .map_err(|e| iwf::Error::Defined(format!("Cannot hash {}: {e}", cluster_task.input)))?;
+        if self.force || !cache::is_valid(&cluster_task.output, &key) {
+            if self.native {
+                cluster_task
+                    .run_native(&items)
+                    .map_err(|e| iwf::Error::Defined(format!("Native clustering failed: {e}")))?;
+            } else {
+                let cluster_result: ClusterResult =
+                    self.gsea.execute(self.params, &cluster_task)?;
+                debug!("ClusterResult: {cluster_result}");
+            }
+            cache::store(&cluster_task.output, &key)
+                .map_err(|e| iwf::Error::Defined(format!("Cannot store the cache key: {e}")))?;
         }
         let results = cluster_task.read_output().unwrap();
         read_and_store_clusters(self.ctx, self.mm, &results, "fig4n").await?;
@@ -1,4 +1,7 @@
+pub mod benchmark;
+pub mod cache;
 pub mod cluster;
 pub mod enrichment;
 pub mod error;
 pub mod gls;
+pub mod workdir;
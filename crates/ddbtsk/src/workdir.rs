@@ -0,0 +1,12 @@
+//! Working directories of the external tools. Every step and atlas gets its
+//! own directory, so atlases processed concurrently never share files.
+
+use crate::error::Result;
+use camino::Utf8PathBuf;
+
+/// Creates `<step>/<name>` below the workflow directory and returns it.
+pub fn create(step: &str, name: &str) -> Result<Utf8PathBuf> {
+    let dir = Utf8PathBuf::from(step).join(name);
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
@@ -1,3 +1,4 @@
+use ddbtbl::dialect::Dialect;
 use iwf::ctx::Ctx;
 use iwf::sql::base::{self, DbBmc};
 use iwf::sql::ModelManager;
@@ -14,12 +15,14 @@ impl AclabelsBmc {
         format!("DROP VIEW IF EXISTS {};", Self::TABLE)
     }
     #[must_use]
-    pub fn get_create_sql() -> String {
+    pub fn get_create_sql(dialect: Dialect) -> String {
         let table = Self::TABLE;
+        let labels = dialect.string_agg("global_grp", ",");
+        let join = dialect.any("ann.id", "labels");
         format!(
             r##"
 CREATE VIEW {table} AS
-      select row_number() OVER () AS id, ac_id,kind,string_agg(global_grp, ',' order by global_grp) as labels from wkdelabel inner join ann on ann.id = any(labels) group by ac_id,kind;
+      select row_number() OVER () AS id, ac_id,kind,{labels} as labels from wkdelabel inner join ann on {join} group by ac_id,kind;
 "##
         )
     }
@@ -0,0 +1,77 @@
+use ddbtbl::dialect::Dialect;
+use iwf::ctx::Ctx;
+use iwf::sql::base::{self, DbBmc};
+use iwf::sql::ModelManager;
+use iwf::sql::Result;
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, OpValsFloat64, OpValsInt64, OpValsString};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tabled::Tabled;
+
+impl AczscoreBmc {
+    #[must_use]
+    pub fn get_drop_sql() -> String {
+        format!("DROP VIEW IF EXISTS {};", Self::TABLE)
+    }
+    /// Z-scores with the protein, the sample and the qmatrix name; `kind` is
+    /// `measured` (measured cells only) or `with_imputed` (all cells). The view
+    /// only joins and reads the same in both dialects.
+    #[must_use]
+    pub fn get_create_sql(_dialect: Dialect) -> String {
+        let table = Self::TABLE;
+        format!(
+            r##"
+CREATE VIEW {table} AS
+      select zscore.id, qmatrix.name as qmatrix, zscore.kind, zscore.ac_id, ac.entry, ac.entry_name, zscore.ann_id, ann.measurement, ann.global_grp, ann.sample_grp, zscore.zscore from zscore inner join ac on zscore.ac_id = ac.id inner join ann on zscore.ann_id = ann.id inner join qmatrix on zscore.qmatrix_id = qmatrix.id;
+"##
+        )
+    }
+}
+
+#[derive(Debug, Clone, Fields, FromRow, Serialize, Deserialize, Default, Tabled)]
+pub struct Aczscore {
+    pub id: i32,
+    pub qmatrix: String,
+    pub kind: String,
+    pub ac_id: i32,
+    pub entry: String,
+    pub entry_name: String,
+    pub ann_id: i32,
+    pub measurement: String,
+    pub global_grp: String,
+    pub sample_grp: String,
+    pub zscore: f64,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct AczscoreFilter {
+    id: Option<OpValsInt64>,
+    qmatrix: Option<OpValsString>,
+    kind: Option<OpValsString>,
+    ac_id: Option<OpValsInt64>,
+    entry: Option<OpValsString>,
+    global_grp: Option<OpValsString>,
+    zscore: Option<OpValsFloat64>,
+}
+
+pub struct AczscoreBmc;
+
+impl DbBmc for AczscoreBmc {
+    const TABLE: &'static str = "ac_zscore";
+}
+
+impl AczscoreBmc {
+    pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<Aczscore> {
+        base::get::<Self, _>(ctx, mm, id).await
+    }
+
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<AczscoreFilter>>,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<Aczscore>> {
+        base::list::<Self, _, _>(ctx, mm, filters, list_options).await
+    }
+}
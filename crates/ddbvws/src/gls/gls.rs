@@ -1,3 +1,5 @@
+use ddbtbl::dialect::Dialect;
+use ddbtbl::gls::scoresetting::ScoreSettingBmc;
 use iwf::ctx::Ctx;
 use iwf::sql::base::{self, DbBmc};
 use iwf::sql::ModelManager;
@@ -14,12 +16,14 @@ impl GlsBmc {
         format!("DROP VIEW IF EXISTS {};", Self::TABLE)
     }
     #[must_use]
-    pub fn get_create_sql() -> String {
+    pub fn get_create_sql(dialect: Dialect) -> String {
         let table = Self::TABLE;
+        let gls = dialect.greatest("cummulative_atlas_score", "cummulative_cell_score");
+        let reference = ScoreSettingBmc::REFERENCE_ID_SQL;
         format!(
             r##"
 CREATE VIEW {table} AS
-      select row_number() OVER () AS id, ac,greatest(cummulative_atlas_score,cummulative_cell_score) as gls, case when cummulative_cell_score >= cummulative_atlas_score then cell_label_string else tissue_label_string end as sel,global_label_string,cell_label_string,tissue_label_string, case when cummulative_cell_score >= cummulative_atlas_score then 'cell' else 'tissue' end as kind from labeldata;
+      select row_number() OVER () AS id, ac,{gls} as gls, case when cummulative_cell_score >= cummulative_atlas_score then cell_label_string else tissue_label_string end as sel,global_label_string,cell_label_string,tissue_label_string, case when cummulative_cell_score >= cummulative_atlas_score then 'cell' else 'tissue' end as kind from labeldata where score_setting_id = ({reference});
 
 "##
         )
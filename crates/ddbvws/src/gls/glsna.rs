@@ -0,0 +1,67 @@
+use ddbtbl::dialect::Dialect;
+use iwf::ctx::Ctx;
+use iwf::sql::base::{self, DbBmc};
+use iwf::sql::ModelManager;
+use iwf::sql::Result;
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, OpValsInt64, OpValsString};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tabled::Tabled;
+
+impl GlsnaBmc {
+    #[must_use]
+    pub fn get_drop_sql() -> String {
+        format!("DROP VIEW IF EXISTS {};", Self::TABLE)
+    }
+    /// Multilabel of every protein and qmatrix: its tissues scoring at least
+    /// half of its top `glsn` score, in name order.
+    #[must_use]
+    pub fn get_create_sql(dialect: Dialect) -> String {
+        let table = Self::TABLE;
+        let multilabel = dialect.string_agg("tissue", ",");
+        format!(
+            r##"
+CREATE VIEW {table} AS
+      select row_number() OVER () AS id, qmatrix_id, ac_id, {multilabel} as multilabel from (select qmatrix_id, ac_id, tissue, score, max(score) over (partition by qmatrix_id, ac_id) as top_score from glsn) scored where score > 0 and score >= top_score / 2 group by qmatrix_id, ac_id;
+"##
+        )
+    }
+}
+
+#[derive(Debug, Clone, Fields, FromRow, Serialize, Deserialize, Default, Tabled)]
+pub struct Glsna {
+    pub id: i64,
+    pub qmatrix_id: i32,
+    pub ac_id: i32,
+    pub multilabel: String,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct GlsnaFilter {
+    id: Option<OpValsInt64>,
+    qmatrix_id: Option<OpValsInt64>,
+    ac_id: Option<OpValsInt64>,
+    multilabel: Option<OpValsString>,
+}
+
+pub struct GlsnaBmc;
+
+impl DbBmc for GlsnaBmc {
+    const TABLE: &'static str = "glsna";
+}
+
+impl GlsnaBmc {
+    pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i32) -> Result<Glsna> {
+        base::get::<Self, _>(ctx, mm, id).await
+    }
+
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<GlsnaFilter>>,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<Glsna>> {
+        base::list::<Self, _, _>(ctx, mm, filters, list_options).await
+    }
+}
@@ -13,20 +13,25 @@ impl LabelspivotBmc {
     pub fn get_drop_sql() -> String {
         format!("DROP VIEW IF EXISTS {};", Self::TABLE)
     }
+    /// One `<atlas>_clusters` column per registered atlas; the names are
+    /// checked by `ddbtbl::gls::atlas::check_name` when the registry is stored
+    /// or loaded.
     #[must_use]
-    pub fn get_create_sql() -> String {
+    pub fn get_create_sql(atlases: &[String]) -> String {
         let table = Self::TABLE;
+        let columns: String = atlases
+            .iter()
+            .map(|e| {
+                format!(
+                    ",\n    MAX(CASE WHEN kind = '{e}' THEN labels ELSE '' END) AS \"{e}_clusters\""
+                )
+            })
+            .collect();
         format!(
             r##"
 CREATE VIEW {table} AS
       select row_number() OVER () AS id, 
-    ac_id,
-    MAX(CASE WHEN kind = 'haatlas' THEN labels ELSE '' END) AS haatlas_clusters,
-    MAX(CASE WHEN kind = 'emblatlas' THEN labels ELSE '' END) AS emblatlas_clusters,
-    MAX(CASE WHEN kind = 'msratlas' THEN labels ELSE '' END) AS msratlas_clusters,
-    MAX(CASE WHEN kind = 'mspatlas' THEN labels ELSE '' END) AS mspatlas_clusters,
-    MAX(CASE WHEN kind = 'hacells' THEN labels ELSE '' END) AS hacells_clusters,
-    MAX(CASE WHEN kind = 'emblcells' THEN labels ELSE '' END) AS emblcells_clusters
+    ac_id{columns}
 FROM ac_labels
 GROUP BY ac_id
 ORDER BY ac_id;
@@ -1,3 +1,5 @@
 pub mod aclabels;
+pub mod aczscore;
 pub mod gls;
+pub mod glsna;
 pub mod labelspivot;
@@ -0,0 +1,29 @@
+use clap::{Parser, ValueEnum};
+use iwf::wfopts;
+
+use crate::services::pipeline::Selection;
+
+#[derive(Parser, Debug)]
+pub struct Cli {
+    #[command(flatten)]
+    pub wf: wfopts::Cli,
+    #[command(flatten)]
+    pub steps: Selection,
+    /// Steps recomputed even when their cached output is valid
+    #[arg(long, global = true, value_enum, value_delimiter = ',')]
+    pub force: Vec<Force>,
+}
+
+/// Tool outputs that `--force` recomputes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Force {
+    Cluster,
+    Enrichment,
+    All,
+}
+
+/// Whether `step` was selected with `--force`.
+#[must_use]
+pub fn is_forced(force: &[Force], step: Force) -> bool {
+    force.iter().any(|e| *e == Force::All || *e == step)
+}
@@ -1,29 +1,51 @@
 //! gls
 
+mod cli;
 mod services;
 
 use camino::Utf8PathBuf;
 use clap::Parser;
+#[cfg(feature = "report")]
+use ddbstp::db::ReportDb;
+use ddbtbl::gls::atlas::AtlasDef;
+use ddbtbl::gls::impute::{Imputation, ImputeMethod, Knn, LowPercentile, MinProb};
+use ddbtbl::gls::normalize::Normalization;
+use ddbtbl::gls::qm::QmProcessing;
+use ddbtbl::gls::scaling::Scaling;
+use ddbtbl::gls::scoresetting::ScoreSettingGrid;
+#[cfg(feature = "rebuild")]
+use ddbtbl::gls::stepstate::StepStateBmc;
+#[cfg(feature = "rebuild")]
+use ddbtbl::gls::tissue::TissueRegistry;
 #[cfg(feature = "rebuild")]
 use ddbtsk::gls::ExeClusteringTask;
 use iwf::ctx::Ctx;
 use iwf::md::{MBook, StdPage};
 use iwf::osobject::{download_named_resources, OsObject};
+#[cfg(feature = "rebuild")]
+use iwf::sql::DbCredentials;
 use iwf::sql::ModelManager;
-use iwf::{wfopts, DefaultSettings, IwfExe, IwfRun, IwfSettings, IwfSetup, IwfTuning};
+use iwf::{DefaultSettings, IwfExe, IwfRun, IwfSettings, IwfSetup, IwfTuning};
 use iwfmacros::IwfWf;
 #[allow(unused_imports)]
-use log::warn;
+use log::{info, warn};
 use ordered_float::OrderedFloat;
 use rust_xlsxwriter::Workbook;
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap};
 use std::hash::Hash;
 
+#[cfg(feature = "rebuild")]
+use services::pipeline::{self, Step};
+#[cfg(feature = "rebuild")]
+use std::collections::HashSet;
+
 #[cfg(feature = "rebuild")]
 use ddbtsk::gls::{
-    CalculateEnrichmentFig2eTask, CalculateEnrichmentListTask, CalculateLabelDataTask,
-    CalculateVarianceTask, CalculateWkdeEnrichmentTask, ExeWkdeTask, Fig4fUmapTask, Fig4gUmapTask,
+    CalculateBenchmarkTask, CalculateEnrichmentFig2eTask, CalculateEnrichmentListTask,
+    CalculateEnrichmentTask, CalculateGlsnTask, CalculateLabelConfidenceTask,
+    CalculateLabelDataTask, CalculatePrerankEnrichmentTask, CalculateVarianceTask,
+    CalculateWkdeEnrichmentTask, CalculateZscoreTask, ExeWkdeTask, Fig4fUmapTask, Fig4gUmapTask,
     MiPatientUmapTask, SepsisPatientUmapTask,
 };
 
@@ -31,12 +53,12 @@ const APP_INFO: &str = concat!(env!("CARGO_BIN_NAME"), "_", env!("CARGO_PKG_VERS
 
 #[tokio::main]
 async fn main() -> iwf::Result<()> {
-    let cli = wfopts::Cli::parse();
+    let cli = cli::Cli::parse();
     iwf::setup_logger();
-    let ret = iwf::wfo::<GlsWf, DefaultSettings>(&cli, APP_INFO).await?;
+    let ret = iwf::wfo::<GlsWf, DefaultSettings>(&cli.wf, APP_INFO).await?;
     if let Some((params, task, settings, execs)) = ret {
         match params.action {
-            iwf::WfAction::Run => run(&params, task, settings, execs).await?,
+            iwf::WfAction::Run => run(&params, task, settings, execs, &cli).await?,
         }
     }
     Ok(())
@@ -47,7 +69,100 @@ pub struct Qmi {
     filepath: Utf8PathBuf,
     name: String,
     ex: bool,
-    impute: bool,
+    #[serde(default)]
+    impute: Impute,
+    #[serde(default)]
+    normalization: Normalization,
+    #[serde(default)]
+    scaling: Scaling,
+}
+
+impl From<&Qmi> for QmProcessing {
+    fn from(item: &Qmi) -> Self {
+        Self {
+            imputation: Imputation::from(&item.impute),
+            normalization: item.normalization,
+            scaling: item.scaling,
+        }
+    }
+}
+
+/// Imputation of a qmatrix: a strategy, or the legacy flag selecting the
+/// low-percentile draw with (`true`) or without the imputed cells in scaling.
+#[derive(Serialize, Deserialize, Debug, Hash)]
+#[serde(untagged)]
+pub enum Impute {
+    Legacy(bool),
+    Strategy(ImputeSettings),
+}
+
+impl Default for Impute {
+    fn default() -> Self {
+        Self::Legacy(false)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Hash)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum ImputeStrategy {
+    None,
+    LowPercentile {
+        fraction: OrderedFloat<f64>,
+    },
+    MinProb {
+        quantile: OrderedFloat<f64>,
+        sd_scale: OrderedFloat<f64>,
+    },
+    ProteinMin,
+    Knn {
+        k: usize,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Hash)]
+pub struct ImputeSettings {
+    #[serde(flatten)]
+    strategy: ImputeStrategy,
+    #[serde(default)]
+    seed: u64,
+    /// Counts the imputed cells when scaling per protein.
+    #[serde(default)]
+    scale: bool,
+}
+
+impl From<&Impute> for Imputation {
+    fn from(item: &Impute) -> Self {
+        let settings = match item {
+            Impute::Legacy(scale) => {
+                return Self {
+                    scale: *scale,
+                    legacy: true,
+                    ..Default::default()
+                }
+            }
+            Impute::Strategy(settings) => settings,
+        };
+        let method = match settings.strategy {
+            ImputeStrategy::None => ImputeMethod::None,
+            ImputeStrategy::LowPercentile { fraction } => {
+                ImputeMethod::LowPercentile(LowPercentile {
+                    fraction: fraction.into_inner(),
+                })
+            }
+            ImputeStrategy::MinProb { quantile, sd_scale } => ImputeMethod::MinProb(MinProb {
+                quantile: quantile.into_inner(),
+                sd_scale: sd_scale.into_inner(),
+            }),
+            ImputeStrategy::ProteinMin => ImputeMethod::ProteinMin,
+            ImputeStrategy::Knn { k } => ImputeMethod::Knn(Knn { k }),
+        };
+        Self {
+            method,
+            seed: settings.seed,
+            scale: settings.scale,
+            legacy: false,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Hash)]
@@ -59,6 +174,60 @@ pub struct WkdeSettings {
     max_n_labels: u8,
 }
 
+/// Bootstrap of the atlas labels, 0 replicates skips it.
+#[derive(Serialize, Deserialize, Debug, Default, Hash)]
+pub struct BootstrapSettings {
+    replicates: usize,
+    sample_fraction: OrderedFloat<f64>,
+}
+
+/// Candidate values of the label score parameters; empty lists keep the defaults.
+#[derive(Serialize, Deserialize, Debug, Default, Hash)]
+pub struct ScoreGrid {
+    #[serde(default)]
+    cell_atlas_weight: Vec<OrderedFloat<f64>>,
+    #[serde(default)]
+    common_threashold: Vec<i32>,
+    #[serde(default)]
+    delta_to_include: Vec<OrderedFloat<f64>>,
+    #[serde(default)]
+    max_labels: Vec<i32>,
+    #[serde(default)]
+    multi_label_weight: Vec<OrderedFloat<f64>>,
+}
+
+impl From<&ScoreGrid> for ScoreSettingGrid {
+    fn from(item: &ScoreGrid) -> Self {
+        let floats = |v: &[OrderedFloat<f64>]| v.iter().map(|e| e.into_inner()).collect();
+        Self {
+            cell_atlas_weight: floats(&item.cell_atlas_weight),
+            common_threashold: item.common_threashold.clone(),
+            delta_to_include: floats(&item.delta_to_include),
+            max_labels: item.max_labels.clone(),
+            multi_label_weight: floats(&item.multi_label_weight),
+        }
+    }
+}
+
+/// Handling of an existing analysis database.
+#[derive(Serialize, Deserialize, Debug, Default, Hash)]
+pub struct SchemaSettings {
+    /// Upgrades an older schema in place instead of refusing the run.
+    #[serde(default)]
+    migrate: bool,
+    /// Drops and recreates the database before the run.
+    #[serde(default)]
+    recreate: bool,
+}
+
+/// Selects the in-process engines instead of the containerised scripts.
+#[derive(Serialize, Deserialize, Debug, Default, Hash)]
+pub struct NativeSettings {
+    cluster: bool,
+    wkde: bool,
+    enrichment: bool,
+}
+
 #[derive(IwfWf, Serialize, Deserialize, Debug, Default, Hash)]
 #[iwfworkflow(method = "gls v0.1.0", name = "gls", description = "gls workflow")]
 pub struct GlsWf {
@@ -75,7 +244,37 @@ pub struct GlsWf {
     pub qmi: Vec<Qmi>,
     pub gmt: Utf8PathBuf,
     pub wkde_settings: WkdeSettings,
+    #[serde(default)]
+    pub score_grid: ScoreGrid,
+    #[serde(default)]
+    pub bootstrap: BootstrapSettings,
     pub sample_map: BTreeMap<String, (String, String)>,
+    #[serde(default)]
+    pub native: NativeSettings,
+    #[serde(default)]
+    pub schema: SchemaSettings,
+    /// Atlas registry, the publication atlases when empty.
+    #[serde(default)]
+    pub atlases: Vec<AtlasDef>,
+    /// YAML/JSON file extending the tissue vocabulary.
+    #[serde(default)]
+    pub tissues: Option<Utf8PathBuf>,
+    /// TSV of protein -> expected tissue(s) the labels are benchmarked against.
+    #[serde(default)]
+    pub reference: Option<Utf8PathBuf>,
+    /// SQLite file the tables and views are exported to after the run.
+    #[serde(default)]
+    pub sqlite: Option<Utf8PathBuf>,
+    /// SQLite file written by `sqlite` the report is built from, without
+    /// connecting to Postgres.
+    #[serde(default)]
+    pub report_sqlite: Option<Utf8PathBuf>,
+    /// Permutations of the pre-ranked GSEA on the GLS scores, 0 skips it.
+    #[serde(default)]
+    pub prerank_permutations: usize,
+    /// Atlases clustered and labelled concurrently, one per CPU for 0.
+    #[serde(default)]
+    pub jobs: usize,
 }
 
 #[derive(Deserialize)]
@@ -105,9 +304,17 @@ async fn run(
     params: &iwf::WfParameters,
     task: GlsWf,
     settings: DefaultSettings,
-    execs: HashMap<String, impl IwfExe + Clone>,
+    execs: HashMap<String, impl IwfExe + Clone + Sync>,
+    cli: &cli::Cli,
 ) -> iwf::Result<()> {
     let ctx = &Ctx::new(1)?;
+    #[cfg(feature = "report")]
+    if let Some(path) = &task.report_sqlite {
+        let db = ReportDb::sqlite(path)
+            .await
+            .map_err(|e| iwf::Error::Defined(format!("Cannot open {path}: {e}")))?;
+        return create_report(ctx, &db, params.wd_path.clone(), &task).await;
+    }
     let mut dbc = settings.dbcredentials();
     let db = params
         .wd_path
@@ -116,27 +323,18 @@ async fn run(
         .replace('.', "_");
 
     dbc.update_database(&db);
-    #[cfg(feature = "rebuild")]
-    iwf::sql::create_db(&dbc).await?;
-    #[cfg(feature = "rebuild")]
-    iwf::setup_dgs(&dbc).await?;
-
-    let mm = &ModelManager::new(&dbc.get_db_url()).await?;
+    let mm = &services::schema::connect(&dbc, task.schema.recreate).await?;
+    services::schema::check(mm, task.schema.migrate).await?;
     let objects: HashMap<_, _> = task.objects.clone().into_iter().collect();
     let _datasets = download_named_resources(&objects, &settings.oscredentials()).await?;
     #[cfg(feature = "rebuild")]
-    {
-        services::opendata::create(mm, &dbc).await?;
-        services::rawdata::create(ctx, mm, &dbc, &task.qmi).await?;
-        services::deriveddata::create(&dbc).await?;
-        match protocol(ctx, mm, &task, execs, params).await {
-            Ok(()) => (),
-            Err(e) => iwf::dgs(&e)?,
-        }
+    match protocol(ctx, mm, &dbc, &task, execs, params, cli).await {
+        Ok(()) => (),
+        Err(e) => iwf::dgs(&e)?,
     }
 
     #[cfg(feature = "report")]
-    match create_report(ctx, mm, params.wd_path.clone(), &task).await {
+    match create_report(ctx, &ReportDb::postgres(mm), params.wd_path.clone(), &task).await {
         Ok(()) => (),
         Err(e) => iwf::dgs(&e)?,
     }
@@ -144,6 +342,10 @@ async fn run(
     let dgs = iwf::get_dgsfc()?;
     iwf::DgsBmc::bulk_import(mm, dgs).await?;
 
+    if let Some(path) = &task.sqlite {
+        services::sqlite::export(mm, path).await?;
+    }
+
     Ok(())
 }
 
@@ -151,115 +353,283 @@ async fn run(
 async fn protocol(
     ctx: &Ctx,
     mm: &ModelManager,
+    dbc: &DbCredentials,
     task: &GlsWf,
-    execs: HashMap<String, impl IwfExe + Clone>,
+    execs: HashMap<String, impl IwfExe + Clone + Sync>,
     params: &iwf::WfParameters,
+    cli: &cli::Cli,
 ) -> iwf::Result<()> {
-    let gsea = iwf::get_exec(&execs, "gseapy");
-    let wkde = iwf::get_exec(&execs, "wkde");
-    let gmt = &task.gmt;
-
-    for name in &task.atlas_list {
-        let _ = ExeClusteringTask {
-            ctx,
-            mm,
-            gsea: gsea.clone(),
-            params,
-            name,
+    let plan = cli.steps.plan();
+    let done = StepStateBmc::done(mm).await?;
+    pipeline::check(&plan, &done)?;
+    let mut ran = HashSet::new();
+    for step in plan {
+        if cli.steps.skip(step, &done, &ran) {
+            info!("Skipping step {}, completed earlier", step.name());
+            continue;
         }
-        .execute()
-        .await?;
-    }
-    for kind in &task.atlas_list {
-        let _ = ExeWkdeTask {
-            mm,
-            params,
-            wkde: wkde.clone(),
-            kind,
-            fraction: task.wkde_settings.fraction.into(),
-            bandwidth: task.wkde_settings.bandwidth.into(),
-            no_class_criteria: task.wkde_settings.no_class_criteria.into(),
-            min_value: task.wkde_settings.min_value.into(),
+        info!("Running step {}", step.name());
+        StepStateBmc::reset(mm, &step.dependents()).await?;
+        StepStateBmc::start(mm, step.name()).await?;
+        pipeline::clear(mm, step, &task.atlas_list).await?;
+        if let Err(e) = run_step(step, ctx, mm, dbc, task, &execs, params, &cli.force).await {
+            StepStateBmc::fail(mm, step.name(), &e.to_string()).await?;
+            return Err(e);
         }
-        .execute()
-        .await?;
+        StepStateBmc::finish(mm, step.name()).await?;
+        ran.insert(step);
     }
+    Ok(())
+}
 
-    let _ = CalculateWkdeEnrichmentTask {
-        mm,
-        gmt,
-        gsea: gsea.clone(),
-        params,
-    }
-    .execute()
-    .await?;
-    let _ = CalculateLabelDataTask { ctx, mm }.execute().await?;
-    let _ = SepsisPatientUmapTask {
-        ctx,
-        mm,
-        gsea: gsea.clone(),
-        params,
-        protein_list: &task.pl_4n,
-    }
-    .execute()
-    .await?;
-    let _ = MiPatientUmapTask {
-        ctx,
-        mm,
-        gsea: gsea.clone(),
-        params,
-    }
-    .execute()
-    .await?;
-    let _ = Fig4gUmapTask {
-        ctx,
-        mm,
-        gsea: gsea.clone(),
-        params,
-        protein_list: &task.f4g,
-        name: "fig4g",
-    }
-    .execute()
-    .await?;
-    let _ = Fig4fUmapTask {
-        ctx,
-        mm,
-        gsea: gsea.clone(),
-        params,
-    }
-    .execute()
-    .await?;
-    let _ = CalculateEnrichmentListTask {
-        mm,
-        gsea: gsea.clone(),
-        params,
-        gmt,
-        kind: "fig3b",
-        protein_list: &task.f3b,
-    }
-    .execute()
-    .await?;
-    let _ = CalculateVarianceTask {
-        mm,
-        mpr: &task.sample_map.clone().into_iter().collect(),
-    }
-    .execute()
-    .await?;
-    let _ = CalculateEnrichmentFig2eTask {
-        mm,
-        gsea,
-        params,
-        gmt,
+#[cfg(feature = "rebuild")]
+#[allow(clippy::too_many_arguments, clippy::too_many_lines)]
+async fn run_step(
+    step: Step,
+    ctx: &Ctx,
+    mm: &ModelManager,
+    dbc: &DbCredentials,
+    task: &GlsWf,
+    execs: &HashMap<String, impl IwfExe + Clone + Sync>,
+    params: &iwf::WfParameters,
+    force: &[cli::Force],
+) -> iwf::Result<()> {
+    let gsea = iwf::get_exec(execs, "gseapy");
+    let wkde = iwf::get_exec(execs, "wkde");
+    let gmt = &task.gmt;
+    let force_cluster = cli::is_forced(force, cli::Force::Cluster);
+    let force_enrichment = cli::is_forced(force, cli::Force::Enrichment);
+
+    match step {
+        Step::Load => {
+            services::opendata::create(mm).await?;
+            let atlases = match task.atlases.is_empty() {
+                true => AtlasDef::builtin(),
+                false => task.atlases.clone(),
+            };
+            services::rawdata::create(ctx, mm, &task.qmi, &atlases).await?;
+            services::deriveddata::create(mm, dbc).await?;
+        }
+        Step::Cluster => {
+            pipeline::for_each_atlas(step, &task.atlas_list, task.jobs, |name| {
+                let gsea = gsea.clone();
+                async move {
+                    let _ = ExeClusteringTask {
+                        ctx,
+                        mm,
+                        gsea,
+                        params,
+                        native: task.native.cluster,
+                        force: force_cluster,
+                        name,
+                    }
+                    .execute()
+                    .await?;
+                    Ok(())
+                }
+            })?;
+        }
+        Step::Wkde => {
+            pipeline::for_each_atlas(step, &task.atlas_list, task.jobs, |kind| {
+                let wkde = wkde.clone();
+                async move {
+                    let _ = ExeWkdeTask {
+                        mm,
+                        params,
+                        wkde,
+                        kind,
+                        fraction: task.wkde_settings.fraction.into(),
+                        bandwidth: task.wkde_settings.bandwidth.into(),
+                        no_class_criteria: task.wkde_settings.no_class_criteria.into(),
+                        min_value: task.wkde_settings.min_value.into(),
+                        max_n_labels: task.wkde_settings.max_n_labels.into(),
+                        native: task.native.wkde,
+                    }
+                    .execute()
+                    .await?;
+                    Ok(())
+                }
+            })?;
+        }
+        Step::LabelConfidence => {
+            if task.bootstrap.replicates > 0 {
+                let tissues = &TissueRegistry::load(mm, task.tissues.as_deref()).await?;
+                for name in &task.atlas_list {
+                    let _ = CalculateLabelConfidenceTask {
+                        mm,
+                        tissues,
+                        name,
+                        replicates: task.bootstrap.replicates,
+                        sample_fraction: task.bootstrap.sample_fraction.into(),
+                        fraction: task.wkde_settings.fraction.into(),
+                        bandwidth: task.wkde_settings.bandwidth.into(),
+                        min_value: task.wkde_settings.min_value.into(),
+                        no_class_criteria: task.wkde_settings.no_class_criteria.into(),
+                        max_n_labels: task.wkde_settings.max_n_labels.into(),
+                    }
+                    .execute()
+                    .await?;
+                }
+            }
+        }
+        Step::WkdeEnrichment => {
+            let _ = CalculateWkdeEnrichmentTask {
+                mm,
+                gmt,
+                gsea: gsea.clone(),
+                params,
+                native: task.native.enrichment,
+                force: force_enrichment,
+            }
+            .execute()
+            .await?;
+        }
+        Step::LabelData => {
+            let tissues = &TissueRegistry::load(mm, task.tissues.as_deref()).await?;
+            let settings = &ScoreSettingGrid::from(&task.score_grid).combinations();
+            let _ = CalculateLabelDataTask {
+                ctx,
+                mm,
+                tissues,
+                settings,
+            }
+            .execute()
+            .await?;
+        }
+        Step::Benchmark => {
+            if let Some(reference) = &task.reference {
+                let tissues = &TissueRegistry::load(mm, task.tissues.as_deref()).await?;
+                let _ = CalculateBenchmarkTask {
+                    mm,
+                    tissues,
+                    reference,
+                }
+                .execute()
+                .await?;
+            }
+        }
+        Step::Prerank => {
+            if task.prerank_permutations > 0 {
+                for score in ["atlas", "cell"] {
+                    let _ = CalculatePrerankEnrichmentTask {
+                        mm,
+                        gmt,
+                        score,
+                        permutations: task.prerank_permutations,
+                    }
+                    .execute()
+                    .await?;
+                }
+            }
+        }
+        Step::PatientUmap => {
+            let _ = SepsisPatientUmapTask {
+                ctx,
+                mm,
+                gsea: gsea.clone(),
+                params,
+                native: task.native.cluster,
+                force: force_cluster,
+                protein_list: &task.pl_4n,
+            }
+            .execute()
+            .await?;
+            let _ = MiPatientUmapTask {
+                ctx,
+                mm,
+                gsea: gsea.clone(),
+                params,
+                native: task.native.cluster,
+                force: force_cluster,
+            }
+            .execute()
+            .await?;
+            let _ = Fig4gUmapTask {
+                ctx,
+                mm,
+                gsea: gsea.clone(),
+                params,
+                native: task.native.cluster,
+                force: force_cluster,
+                protein_list: &task.f4g,
+                name: "fig4g",
+            }
+            .execute()
+            .await?;
+            let _ = Fig4fUmapTask {
+                ctx,
+                mm,
+                gsea: gsea.clone(),
+                params,
+                native: task.native.cluster,
+                force: force_cluster,
+            }
+            .execute()
+            .await?;
+        }
+        Step::EnrichmentList => {
+            let _ = CalculateEnrichmentListTask {
+                mm,
+                gsea: gsea.clone(),
+                params,
+                native: task.native.enrichment,
+                force: force_enrichment,
+                gmt,
+                kind: "fig3b",
+                protein_list: &task.f3b,
+            }
+            .execute()
+            .await?;
+        }
+        Step::Variance => {
+            let _ = CalculateVarianceTask {
+                mm,
+                mpr: &task.sample_map.clone().into_iter().collect(),
+            }
+            .execute()
+            .await?;
+        }
+        Step::Fig2e => {
+            let _ = CalculateEnrichmentFig2eTask {
+                mm,
+                gsea,
+                params,
+                native: task.native.enrichment,
+                force: force_enrichment,
+                gmt,
+            }
+            .execute()
+            .await?;
+        }
+        Step::Zscore => {
+            let _ = CalculateZscoreTask { mm }.execute().await?;
+        }
+        Step::Glsn => {
+            let tissues = &TissueRegistry::load(mm, task.tissues.as_deref()).await?;
+            for name in &task.atlas_list {
+                let _ = CalculateGlsnTask { mm, tissues, name }.execute().await?;
+            }
+        }
+        Step::GlsnEnrichment => {
+            let _ = CalculateEnrichmentTask {
+                mm,
+                gmt,
+                params,
+                gsea,
+                native: task.native.enrichment,
+                force: force_enrichment,
+            }
+            .execute()
+            .await?;
+        }
     }
-    .execute()
-    .await?;
     Ok(())
 }
 
 #[cfg(feature = "report")]
 async fn create_report(
     _ctx: &Ctx,
-    mm: &ModelManager,
+    db: &ReportDb,
     basepath: Utf8PathBuf,
     task: &GlsWf,
 ) -> iwf::Result<()> {
@@ -270,143 +640,165 @@ async fn create_report(
     };
     let mut wb = Workbook::new();
     book.add_page(StdPage::get_page(
-        &ddbstp::gls::fig1d::get(mm)
+        &ddbstp::gls::fig1d::get(db)
             .await
             .map_err(|e| iwf::Error::Defined(format!("Cannot create fig1d: {e}")))?,
         &mut wb,
     )?);
     book.add_page(StdPage::get_page(
-        &ddbstp::gls::fig1e::get(mm)
+        &ddbstp::gls::fig1e::get(db)
             .await
             .map_err(|e| iwf::Error::Defined(format!("Cannot create fig1e: {e}")))?,
         &mut wb,
     )?);
     book.add_page(StdPage::get_page(
-        &ddbstp::gls::fig2c::get(mm)
+        &ddbstp::gls::fig2c::get(db)
             .await
             .map_err(|e| iwf::Error::Defined(format!("Cannot create fig2c: {e}")))?,
         &mut wb,
     )?);
     book.add_page(StdPage::get_page(
-        &ddbstp::gls::fig2d::get(mm)
+        &ddbstp::gls::fig2d::get(db)
             .await
             .map_err(|e| iwf::Error::Defined(format!("Cannot create fig2d: {e}")))?,
         &mut wb,
     )?);
     book.add_page(StdPage::get_page(
-        &ddbstp::gls::fig2e::get(mm, "brain_gls")
+        &ddbstp::gls::fig2e::get(db, "brain_gls")
             .await
             .map_err(|e| iwf::Error::Defined(format!("Cannot create fig2e: {e}")))?,
         &mut wb,
     )?);
     book.add_page(StdPage::get_page(
-        &ddbstp::gls::fig2f::get(mm)
+        &ddbstp::gls::fig2f::get(db)
             .await
             .map_err(|e| iwf::Error::Defined(format!("Cannot create fig2f: {e}")))?,
         &mut wb,
     )?);
     book.add_page(StdPage::get_page(
-        &ddbstp::gls::fig2g::get(mm)
+        &ddbstp::gls::fig2g::get(db)
             .await
             .map_err(|e| iwf::Error::Defined(format!("Cannot create fig2g: {e}")))?,
         &mut wb,
     )?);
     book.add_page(StdPage::get_page(
-        &ddbstp::gls::fig3a::get(mm, &task.f3a)
+        &ddbstp::gls::fig3a::get(db, &task.f3a)
             .await
             .map_err(|e| iwf::Error::Defined(format!("Cannot create fig3a: {e}")))?,
         &mut wb,
     )?);
     book.add_page(StdPage::get_page(
-        &ddbstp::gls::fig3b::get(mm)
+        &ddbstp::gls::fig3b::get(db)
             .await
             .map_err(|e| iwf::Error::Defined(format!("Cannot create fig3b: {e}")))?,
         &mut wb,
     )?);
     book.add_page(StdPage::get_page(
-        &ddbstp::gls::fig3c::get(mm, &task.nvar_grp)
+        &ddbstp::gls::fig3c::get(db, &task.nvar_grp)
             .await
             .map_err(|e| iwf::Error::Defined(format!("Cannot create fig3c: {e}")))?,
         &mut wb,
     )?);
     book.add_page(StdPage::get_page(
-        &ddbstp::gls::fig3d::get(mm)
+        &ddbstp::gls::fig3d::get(db)
             .await
             .map_err(|e| iwf::Error::Defined(format!("Cannot create fig3d: {e}")))?,
         &mut wb,
     )?);
     book.add_page(StdPage::get_page(
-        &ddbstp::gls::fig4d::get(mm)
+        &ddbstp::gls::fig4d::get(db)
             .await
             .map_err(|e| iwf::Error::Defined(format!("Cannot create fig4d: {e}")))?,
         &mut wb,
     )?);
     book.add_page(StdPage::get_page(
-        &ddbstp::gls::fig4e::get(mm)
+        &ddbstp::gls::fig4e::get(db)
             .await
             .map_err(|e| iwf::Error::Defined(format!("Cannot create fig4e: {e}")))?,
         &mut wb,
     )?);
     book.add_page(StdPage::get_page(
-        &ddbstp::gls::fig4f::get(mm)
+        &ddbstp::gls::fig4f::get(db)
             .await
             .map_err(|e| iwf::Error::Defined(format!("Cannot create fig4f: {e}")))?,
         &mut wb,
     )?);
     book.add_page(StdPage::get_page(
-        &ddbstp::gls::fig4g::get(mm, "fig4")
+        &ddbstp::gls::fig4g::get(db, "fig4")
             .await
             .map_err(|e| iwf::Error::Defined(format!("Cannot create fig4g: {e}")))?,
         &mut wb,
     )?);
     book.add_page(StdPage::get_page(
-        &ddbstp::gls::fig4i::get(mm)
+        &ddbstp::gls::fig4i::get(db)
             .await
             .map_err(|e| iwf::Error::Defined(format!("Cannot create fig4i: {e}")))?,
         &mut wb,
     )?);
     book.add_page(StdPage::get_page(
-        &ddbstp::gls::fig4ja::get(mm)
+        &ddbstp::gls::fig4ja::get(db)
             .await
             .map_err(|e| iwf::Error::Defined(format!("Cannot create fig4ja: {e}")))?,
         &mut wb,
     )?);
     book.add_page(StdPage::get_page(
-        &ddbstp::gls::fig4jb::get(mm)
+        &ddbstp::gls::fig4jb::get(db)
             .await
             .map_err(|e| iwf::Error::Defined(format!("Cannot create fig4jb: {e}")))?,
         &mut wb,
     )?);
     book.add_page(StdPage::get_page(
-        &ddbstp::gls::fig4k::get(mm)
+        &ddbstp::gls::fig4k::get(db)
             .await
             .map_err(|e| iwf::Error::Defined(format!("Cannot create fig4k: {e}")))?,
         &mut wb,
     )?);
     book.add_page(StdPage::get_page(
-        &ddbstp::gls::fig4l::get(mm)
+        &ddbstp::gls::fig4l::get(db)
             .await
             .map_err(|e| iwf::Error::Defined(format!("Cannot create fig4l: {e}")))?,
         &mut wb,
     )?);
     book.add_page(StdPage::get_page(
-        &ddbstp::gls::fig4m::get(mm)
+        &ddbstp::gls::fig4m::get(db)
             .await
             .map_err(|e| iwf::Error::Defined(format!("Cannot create fig4m: {e}")))?,
         &mut wb,
     )?);
     book.add_page(StdPage::get_page(
-        &ddbstp::gls::fig4n::get(mm)
+        &ddbstp::gls::fig4n::get(db)
             .await
             .map_err(|e| iwf::Error::Defined(format!("Cannot create fig4n: {e}")))?,
         &mut wb,
     )?);
     book.add_page(StdPage::get_page(
-        &ddbstp::gls::fig4o::get(mm)
+        &ddbstp::gls::fig4o::get(db)
             .await
             .map_err(|e| iwf::Error::Defined(format!("Cannot create fig4o: {e}")))?,
         &mut wb,
     )?);
+    if task.reference.is_some() {
+        book.add_page(StdPage::get_page(
+            &ddbstp::gls::benchmark::get(db)
+                .await
+                .map_err(|e| iwf::Error::Defined(format!("Cannot create benchmark: {e}")))?,
+            &mut wb,
+        )?);
+        book.add_page(StdPage::get_page(
+            &ddbstp::gls::benchmark_settings::get(db)
+                .await
+                .map_err(|e| {
+                    iwf::Error::Defined(format!("Cannot create benchmark_settings: {e}"))
+                })?,
+            &mut wb,
+        )?);
+        book.add_page(StdPage::get_page(
+            &ddbstp::gls::confusion::get(db)
+                .await
+                .map_err(|e| iwf::Error::Defined(format!("Cannot create confusion: {e}")))?,
+            &mut wb,
+        )?);
+    }
 
     book.render()?;
     wb.save(format!("gls_{}.xlsx", env!("CARGO_PKG_VERSION")))?;
@@ -1,39 +1,57 @@
-use iwf::sql::DbCredentials;
+use iwf::sql::base::DbBmc;
+use iwf::sql::{DbCredentials, ModelManager};
 
 use ddbtbl::cluster::cluster::ClusterBmc;
 use ddbtbl::cluster::cluster_gsea::GseaEnrBmc;
 use ddbtbl::cluster::umap::UmapBmc;
+use ddbtbl::gls::atlas::AtlasBmc;
+use ddbtbl::gls::benchmark::BenchmarkBmc;
+use ddbtbl::gls::confusion::ConfusionBmc;
 use ddbtbl::gls::glsn::GlsnBmc;
+use ddbtbl::gls::labelchange::LabelChangeBmc;
+use ddbtbl::gls::labelconfidence::LabelConfidenceBmc;
 use ddbtbl::gls::labeldata::LabelDataBmc;
+use ddbtbl::gls::reference::ReferenceBmc;
 use ddbtbl::gls::scoresetting::ScoreSettingBmc;
 use ddbtbl::gls::variance::VarianceBmc;
 use ddbtbl::gls::wkdelabel::WkdeLabelBmc;
 use ddbtbl::gls::wkdetag::WkdeTagBmc;
 use ddbtbl::gls::zscore::ZscoreBmc;
-use ddbvws::gls::aclabels::AclabelsBmc;
-use ddbvws::gls::gls::GlsBmc;
+use ddbtbl::schema::truncate;
 use ddbvws::gls::labelspivot::LabelspivotBmc;
 
-pub async fn create(dbc: &DbCredentials) -> iwf::Result<()> {
+pub async fn create(mm: &ModelManager, dbc: &DbCredentials) -> iwf::Result<()> {
+    let atlases: Vec<String> = AtlasBmc::load(mm)
+        .await?
+        .into_iter()
+        .map(|e| e.name)
+        .collect();
+    truncate(
+        mm,
+        &[
+            ZscoreBmc::TABLE,
+            UmapBmc::TABLE,
+            ClusterBmc::TABLE,
+            GlsnBmc::TABLE,
+            GseaEnrBmc::TABLE,
+            WkdeLabelBmc::TABLE,
+            WkdeTagBmc::TABLE,
+            VarianceBmc::TABLE,
+            LabelDataBmc::TABLE,
+            LabelChangeBmc::TABLE,
+            LabelConfidenceBmc::TABLE,
+            ReferenceBmc::TABLE,
+            BenchmarkBmc::TABLE,
+            ConfusionBmc::TABLE,
+            ScoreSettingBmc::TABLE,
+        ],
+    )
+    .await?;
     iwf::sql::create_tables(
         dbc,
         vec![
-            GlsBmc::get_drop_sql(),
-            AclabelsBmc::get_drop_sql(),
             LabelspivotBmc::get_drop_sql(),
-            ZscoreBmc::get_create_sql(true),
-            UmapBmc::get_create_sql(true),
-            ClusterBmc::get_create_sql(true),
-            GlsnBmc::get_create_sql(true),
-            GseaEnrBmc::get_create_sql(true),
-            WkdeLabelBmc::get_create_sql(true),
-            WkdeTagBmc::get_create_sql(true),
-            VarianceBmc::get_create_sql(true),
-            LabelDataBmc::get_create_sql(true),
-            ScoreSettingBmc::get_create_sql(true),
-            GlsBmc::get_create_sql(),
-            AclabelsBmc::get_create_sql(),
-            LabelspivotBmc::get_create_sql(),
+            LabelspivotBmc::get_create_sql(&atlases),
         ],
     )
     .await?;
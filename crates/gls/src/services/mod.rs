@@ -2,5 +2,8 @@
 pub mod deriveddata;
 #[cfg(feature = "rebuild")]
 pub mod opendata;
+pub mod pipeline;
 #[cfg(feature = "rebuild")]
 pub mod rawdata;
+pub mod schema;
+pub mod sqlite;
@@ -1,13 +1,32 @@
 use camino::Utf8PathBuf;
-use iwf::sql::{DbCredentials, ModelManager};
+use iwf::sql::base::DbBmc;
+use iwf::sql::ModelManager;
 
 use ddbtbl::gls::ac::AcBmc;
+use ddbtbl::opentarget::constraint::TargetConstraintBmc;
+use ddbtbl::opentarget::go::TargetGoBmc;
+use ddbtbl::opentarget::location::TargetLocationBmc;
+use ddbtbl::opentarget::pathway::TargetPathwayBmc;
+use ddbtbl::opentarget::proteinid::TargetProteinIdBmc;
 use ddbtbl::opentarget::target::TargetBmc;
+use ddbtbl::opentarget::tractability::TargetTractabilityBmc;
+use ddbtbl::opentarget::transcript::TargetTranscriptBmc;
+use ddbtbl::schema::truncate;
 
-pub async fn create(mm: &ModelManager, dbc: &DbCredentials) -> iwf::Result<()> {
-    iwf::sql::create_tables(
-        dbc,
-        vec![AcBmc::get_create_sql(true), TargetBmc::get_create_sql(true)],
+pub async fn create(mm: &ModelManager) -> iwf::Result<()> {
+    truncate(
+        mm,
+        &[
+            AcBmc::TABLE,
+            TargetBmc::TABLE,
+            TargetConstraintBmc::TABLE,
+            TargetGoBmc::TABLE,
+            TargetLocationBmc::TABLE,
+            TargetPathwayBmc::TABLE,
+            TargetProteinIdBmc::TABLE,
+            TargetTractabilityBmc::TABLE,
+            TargetTranscriptBmc::TABLE,
+        ],
     )
     .await?;
 
@@ -0,0 +1,316 @@
+//! Steps of the rebuild protocol and their dependencies.
+//!
+//! The completion of every step is recorded in `step_state`, so a run can be
+//! limited to some steps or resumed after a failure. Running a step replaces
+//! its outputs and forgets the state of the steps depending on it.
+
+use clap::{Args, ValueEnum};
+use iwf::sql::base::DbBmc;
+use iwf::sql::ModelManager;
+use log::warn;
+use std::collections::HashSet;
+use std::future::Future;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ddbtbl::cluster::cluster_gsea::GseaEnrBmc;
+use ddbtbl::cluster::umap::UmapBmc;
+use ddbtbl::gls::benchmark::BenchmarkBmc;
+use ddbtbl::gls::confusion::ConfusionBmc;
+use ddbtbl::gls::glsn::GlsnBmc;
+use ddbtbl::gls::labelchange::LabelChangeBmc;
+use ddbtbl::gls::labelconfidence::LabelConfidenceBmc;
+use ddbtbl::gls::labeldata::LabelDataBmc;
+use ddbtbl::gls::reference::ReferenceBmc;
+use ddbtbl::gls::scoresetting::ScoreSettingBmc;
+use ddbtbl::gls::variance::VarianceBmc;
+use ddbtbl::gls::wkdelabel::WkdeLabelBmc;
+use ddbtbl::gls::wkdetag::WkdeTagBmc;
+use ddbtbl::gls::zscore::ZscoreBmc;
+use ddbtbl::schema::{delete_kinds, truncate};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, ValueEnum)]
+pub enum Step {
+    /// Open data, raw data and the emptied derived tables.
+    Load,
+    Cluster,
+    Wkde,
+    LabelConfidence,
+    WkdeEnrichment,
+    LabelData,
+    Benchmark,
+    Prerank,
+    /// Sepsis, MI and figure 4 patient UMAPs.
+    PatientUmap,
+    EnrichmentList,
+    Variance,
+    Fig2e,
+    Zscore,
+    /// Per-atlas tissue scores of the proteins.
+    Glsn,
+    GlsnEnrichment,
+}
+
+impl Step {
+    /// All steps, each after its dependencies.
+    pub const ALL: [Step; 15] = [
+        Step::Load,
+        Step::Cluster,
+        Step::Wkde,
+        Step::LabelConfidence,
+        Step::WkdeEnrichment,
+        Step::LabelData,
+        Step::Benchmark,
+        Step::Prerank,
+        Step::PatientUmap,
+        Step::EnrichmentList,
+        Step::Variance,
+        Step::Fig2e,
+        Step::Zscore,
+        Step::Glsn,
+        Step::GlsnEnrichment,
+    ];
+
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Step::Load => "load",
+            Step::Cluster => "cluster",
+            Step::Wkde => "wkde",
+            Step::LabelConfidence => "label-confidence",
+            Step::WkdeEnrichment => "wkde-enrichment",
+            Step::LabelData => "label-data",
+            Step::Benchmark => "benchmark",
+            Step::Prerank => "prerank",
+            Step::PatientUmap => "patient-umap",
+            Step::EnrichmentList => "enrichment-list",
+            Step::Variance => "variance",
+            Step::Fig2e => "fig2e",
+            Step::Zscore => "zscore",
+            Step::Glsn => "glsn",
+            Step::GlsnEnrichment => "glsn-enrichment",
+        }
+    }
+
+    /// Steps whose outputs this step reads.
+    #[must_use]
+    pub fn deps(self) -> &'static [Step] {
+        match self {
+            Step::Load => &[],
+            Step::Cluster
+            | Step::LabelConfidence
+            | Step::PatientUmap
+            | Step::EnrichmentList
+            | Step::Variance
+            | Step::Zscore
+            | Step::Glsn => &[Step::Load],
+            Step::Wkde => &[Step::Cluster],
+            Step::WkdeEnrichment => &[Step::Wkde],
+            Step::GlsnEnrichment => &[Step::Glsn],
+            Step::LabelData => &[Step::Wkde, Step::LabelConfidence],
+            Step::Benchmark | Step::Prerank | Step::Fig2e => &[Step::LabelData],
+        }
+    }
+
+    /// Whether this step reads, directly or not, the outputs of `other`.
+    #[must_use]
+    pub fn depends_on(self, other: Step) -> bool {
+        self.deps()
+            .iter()
+            .any(|e| *e == other || e.depends_on(other))
+    }
+
+    /// Names of the steps depending on this step.
+    #[must_use]
+    pub fn dependents(self) -> Vec<&'static str> {
+        Step::ALL
+            .into_iter()
+            .filter(|e| e.depends_on(self))
+            .map(Step::name)
+            .collect()
+    }
+}
+
+/// Steps of a run, all of them by default.
+#[derive(Args, Debug, Default)]
+pub struct Selection {
+    /// Runs only these steps
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_delimiter = ',',
+        conflicts_with_all = ["from", "until"]
+    )]
+    pub only: Vec<Step>,
+    /// Runs this step and the steps depending on it
+    #[arg(long, global = true, value_enum)]
+    pub from: Option<Step>,
+    /// Runs this step and the steps it depends on
+    #[arg(long, global = true, value_enum)]
+    pub until: Option<Step>,
+    /// Skips the steps completed by an earlier run
+    #[arg(long, global = true)]
+    pub resume: bool,
+}
+
+impl Selection {
+    /// Selected steps in execution order.
+    #[must_use]
+    pub fn plan(&self) -> Vec<Step> {
+        Step::ALL
+            .into_iter()
+            .filter(|e| self.only.is_empty() || self.only.contains(e))
+            .filter(|e| {
+                self.from
+                    .map_or(true, |from| *e == from || e.depends_on(from))
+            })
+            .filter(|e| {
+                self.until
+                    .map_or(true, |until| *e == until || until.depends_on(*e))
+            })
+            .collect()
+    }
+
+    /// Whether `step` can be skipped: it completed earlier and none of its
+    /// dependencies ran again in this run.
+    #[must_use]
+    pub fn skip(&self, step: Step, done: &HashSet<String>, ran: &HashSet<Step>) -> bool {
+        self.resume && done.contains(step.name()) && !step.deps().iter().any(|e| ran.contains(e))
+    }
+}
+
+/// Refuses a plan with a step whose dependency neither completed earlier nor
+/// is part of the plan.
+pub fn check(plan: &[Step], done: &HashSet<String>) -> iwf::Result<()> {
+    for step in plan {
+        for dep in step.deps() {
+            if !plan.contains(dep) && !done.contains(dep.name()) {
+                return Err(iwf::Error::Defined(format!(
+                    "Step {} needs step {}, which has not completed",
+                    step.name(),
+                    dep.name()
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Removes the outputs of an earlier run of `step`.
+pub async fn clear(mm: &ModelManager, step: Step, atlases: &[String]) -> iwf::Result<()> {
+    let kinds = |v: &[&str]| v.iter().map(|e| (*e).to_string()).collect::<Vec<_>>();
+    match step {
+        Step::Load => (),
+        Step::Cluster => UmapBmc::delete_kinds(mm, atlases).await?,
+        Step::Wkde => {
+            delete_kinds(mm, WkdeTagBmc::TABLE, atlases).await?;
+            delete_kinds(mm, WkdeLabelBmc::TABLE, atlases).await?;
+        }
+        Step::LabelConfidence => truncate(mm, &[LabelConfidenceBmc::TABLE]).await?,
+        Step::WkdeEnrichment => {
+            let wkde: Vec<String> = atlases.iter().map(|e| format!("wkde_{e}")).collect();
+            delete_kinds(mm, GseaEnrBmc::TABLE, &wkde).await?;
+        }
+        Step::LabelData => {
+            truncate(
+                mm,
+                &[
+                    LabelDataBmc::TABLE,
+                    LabelChangeBmc::TABLE,
+                    ScoreSettingBmc::TABLE,
+                ],
+            )
+            .await?;
+        }
+        Step::Benchmark => {
+            truncate(
+                mm,
+                &[
+                    ReferenceBmc::TABLE,
+                    BenchmarkBmc::TABLE,
+                    ConfusionBmc::TABLE,
+                ],
+            )
+            .await?;
+        }
+        Step::Prerank => {
+            delete_kinds(
+                mm,
+                GseaEnrBmc::TABLE,
+                &kinds(&["prerank_atlas", "prerank_cell"]),
+            )
+            .await?;
+        }
+        Step::PatientUmap => {
+            UmapBmc::delete_kinds(mm, &kinds(&["fig4n", "fig4j", "fig4g", "fig4f"])).await?;
+        }
+        Step::EnrichmentList => delete_kinds(mm, GseaEnrBmc::TABLE, &kinds(&["fig3b"])).await?,
+        Step::Variance => truncate(mm, &[VarianceBmc::TABLE]).await?,
+        Step::Fig2e => delete_kinds(mm, GseaEnrBmc::TABLE, &kinds(&["brain_gls"])).await?,
+        Step::Zscore => truncate(mm, &[ZscoreBmc::TABLE]).await?,
+        Step::Glsn => truncate(mm, &[GlsnBmc::TABLE]).await?,
+        Step::GlsnEnrichment => {
+            let glsn: Vec<String> = atlases.iter().map(|e| format!("glsn_{e}")).collect();
+            delete_kinds(mm, GseaEnrBmc::TABLE, &glsn).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Runs `work` for every atlas, `jobs` atlases at a time (one per CPU for 0).
+/// The atlases run on scoped threads sharing the current runtime; a failing
+/// atlas does not stop the others and all failures are reported together.
+pub fn for_each_atlas<'a, F, Fut>(
+    step: Step,
+    atlases: &'a [String],
+    jobs: usize,
+    work: F,
+) -> iwf::Result<()>
+where
+    F: Fn(&'a str) -> Fut + Sync,
+    Fut: Future<Output = iwf::Result<()>>,
+{
+    let jobs = match jobs {
+        0 => std::thread::available_parallelism().map_or(1, NonZeroUsize::get),
+        n => n,
+    }
+    .clamp(1, atlases.len().max(1));
+    let handle = tokio::runtime::Handle::current();
+    let next = AtomicUsize::new(0);
+    let failures: Vec<String> = tokio::task::block_in_place(|| {
+        std::thread::scope(|s| {
+            let workers: Vec<_> = (0..jobs)
+                .map(|_| {
+                    s.spawn(|| {
+                        let mut failures = Vec::new();
+                        while let Some(atlas) = atlases.get(next.fetch_add(1, Ordering::Relaxed)) {
+                            if let Err(e) = handle.block_on(work(atlas)) {
+                                warn!("Step {} failed for {atlas}: {e}", step.name());
+                                failures.push(format!("{atlas}: {e}"));
+                            }
+                        }
+                        failures
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|e| {
+                    e.join()
+                        .unwrap_or_else(|_| vec![String::from("worker thread panicked")])
+                })
+                .collect()
+        })
+    });
+    match failures.is_empty() {
+        true => Ok(()),
+        false => Err(iwf::Error::Defined(format!(
+            "Step {} failed for {} of {} atlases: {}",
+            step.name(),
+            failures.len(),
+            atlases.len(),
+            failures.join("; ")
+        ))),
+    }
+}
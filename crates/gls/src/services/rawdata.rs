@@ -1,23 +1,33 @@
 use camino::Utf8PathBuf;
 use ddbtbl::gls::ann::AnnBmc;
-use ddbtbl::gls::qm::QmBmc;
+use ddbtbl::gls::atlas::{AtlasBmc, AtlasDef, AtlasForCreate};
+use ddbtbl::gls::normfactor::NormFactorBmc;
+use ddbtbl::gls::qm::{QmBmc, QmProcessing};
 use ddbtbl::gls::qmatrix::QmatrixBmc;
+use ddbtbl::gls::resolve::IdResolver;
+use ddbtbl::gls::unresolvedid::UnresolvedIdBmc;
+use ddbtbl::schema::truncate;
 use iwf::ctx::Ctx;
-use iwf::sql::{DbCredentials, ModelManager};
+use iwf::sql::base::DbBmc;
+use iwf::sql::ModelManager;
 use log::debug;
+use std::collections::HashMap;
 
 pub async fn create(
     ctx: &Ctx,
     mm: &ModelManager,
-    dbc: &DbCredentials,
     qmi: &Vec<crate::Qmi>,
+    atlases: &[AtlasDef],
 ) -> iwf::Result<()> {
-    iwf::sql::create_tables(
-        dbc,
-        vec![
-            QmBmc::get_create_sql(true),
-            QmatrixBmc::get_create_sql(true),
-            AnnBmc::get_create_sql(true),
+    truncate(
+        mm,
+        &[
+            QmBmc::TABLE,
+            NormFactorBmc::TABLE,
+            UnresolvedIdBmc::TABLE,
+            QmatrixBmc::TABLE,
+            AnnBmc::TABLE,
+            AtlasBmc::TABLE,
         ],
     )
     .await?;
@@ -25,16 +35,31 @@ pub async fn create(
     sqlx::query("insert into ann values (-2, 'none', 'none' ,'none', 'none', 'none', 'none', 'none') on conflict (id) do nothing;").execute(&mm.db).await?;
     debug!("INSERTED common and None");
     AnnBmc::bulk_import(mm, AnnBmc::parse(&Utf8PathBuf::from("measure_v2.tsv"))?).await?;
+    AtlasBmc::bulk_import(mm, atlases.iter().map(AtlasForCreate::from).collect()).await?;
     qm(ctx, mm, qmi).await?;
-    ddbtbl::gls::qm::create_haatlas_qmatrix(ctx, mm).await?;
+    let processings: HashMap<String, QmProcessing> = qmi
+        .iter()
+        .map(|e| (e.name.clone(), QmProcessing::from(e)))
+        .collect();
+    ddbtbl::gls::qm::create_haatlas_qmatrix(ctx, mm, &processings).await?;
     Ok(())
 }
 
 async fn qm(ctx: &Ctx, mm: &ModelManager, input_filenames: &Vec<crate::Qmi>) -> iwf::Result<()> {
+    let resolver = IdResolver::load(ctx, mm, &Utf8PathBuf::from("target.bincode")).await?;
     for qmi in input_filenames {
         QmBmc::bulk_import(
             mm,
-            QmBmc::parse(ctx, mm, &qmi.filepath, qmi.name.clone(), qmi.ex, qmi.impute).await?,
+            QmBmc::parse(
+                ctx,
+                mm,
+                &qmi.filepath,
+                qmi.name.clone(),
+                qmi.ex,
+                &QmProcessing::from(qmi),
+                &resolver,
+            )
+            .await?,
         )
         .await?;
     }
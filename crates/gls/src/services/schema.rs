@@ -0,0 +1,188 @@
+use iwf::sql::base::DbBmc;
+use iwf::sql::{DbCredentials, ModelManager};
+use log::{debug, warn};
+
+use ddbtbl::cluster::cluster::ClusterBmc;
+use ddbtbl::cluster::cluster_gsea::GseaEnrBmc;
+use ddbtbl::cluster::umap::UmapBmc;
+use ddbtbl::dialect::Dialect;
+use ddbtbl::gls::ac::AcBmc;
+use ddbtbl::gls::ann::AnnBmc;
+use ddbtbl::gls::atlas::AtlasBmc;
+use ddbtbl::gls::benchmark::BenchmarkBmc;
+use ddbtbl::gls::confusion::ConfusionBmc;
+use ddbtbl::gls::glsn::GlsnBmc;
+use ddbtbl::gls::labelchange::LabelChangeBmc;
+use ddbtbl::gls::labelconfidence::LabelConfidenceBmc;
+use ddbtbl::gls::labeldata::LabelDataBmc;
+use ddbtbl::gls::normfactor::NormFactorBmc;
+use ddbtbl::gls::qm::QmBmc;
+use ddbtbl::gls::qmatrix::QmatrixBmc;
+use ddbtbl::gls::reference::ReferenceBmc;
+use ddbtbl::gls::scoresetting::ScoreSettingBmc;
+use ddbtbl::gls::stepstate::StepStateBmc;
+use ddbtbl::gls::unresolvedid::UnresolvedIdBmc;
+use ddbtbl::gls::variance::VarianceBmc;
+use ddbtbl::gls::wkdelabel::WkdeLabelBmc;
+use ddbtbl::gls::wkdetag::WkdeTagBmc;
+use ddbtbl::gls::zscore::ZscoreBmc;
+use ddbtbl::opentarget::constraint::TargetConstraintBmc;
+use ddbtbl::opentarget::go::TargetGoBmc;
+use ddbtbl::opentarget::location::TargetLocationBmc;
+use ddbtbl::opentarget::pathway::TargetPathwayBmc;
+use ddbtbl::opentarget::proteinid::TargetProteinIdBmc;
+use ddbtbl::opentarget::target::TargetBmc;
+use ddbtbl::opentarget::tractability::TargetTractabilityBmc;
+use ddbtbl::opentarget::transcript::TargetTranscriptBmc;
+use ddbtbl::schema::{Migration, SchemaVersionBmc};
+use ddbvws::gls::aclabels::AclabelsBmc;
+use ddbvws::gls::aczscore::AczscoreBmc;
+use ddbvws::gls::gls::GlsBmc;
+use ddbvws::gls::glsna::GlsnaBmc;
+
+/// Tables created by the migrations.
+pub const TABLES: &[&str] = &[
+    AcBmc::TABLE,
+    TargetBmc::TABLE,
+    QmBmc::TABLE,
+    QmatrixBmc::TABLE,
+    AnnBmc::TABLE,
+    AtlasBmc::TABLE,
+    ZscoreBmc::TABLE,
+    UmapBmc::TABLE,
+    ClusterBmc::TABLE,
+    GlsnBmc::TABLE,
+    GseaEnrBmc::TABLE,
+    WkdeLabelBmc::TABLE,
+    WkdeTagBmc::TABLE,
+    VarianceBmc::TABLE,
+    LabelDataBmc::TABLE,
+    LabelChangeBmc::TABLE,
+    LabelConfidenceBmc::TABLE,
+    ReferenceBmc::TABLE,
+    BenchmarkBmc::TABLE,
+    ConfusionBmc::TABLE,
+    ScoreSettingBmc::TABLE,
+    StepStateBmc::TABLE,
+    NormFactorBmc::TABLE,
+    UnresolvedIdBmc::TABLE,
+    TargetConstraintBmc::TABLE,
+    TargetGoBmc::TABLE,
+    TargetLocationBmc::TABLE,
+    TargetPathwayBmc::TABLE,
+    TargetProteinIdBmc::TABLE,
+    TargetTractabilityBmc::TABLE,
+    TargetTranscriptBmc::TABLE,
+];
+
+/// Schema migrations of the analysis database, in version order. Released
+/// migrations are never edited; schema changes are appended as a new version.
+#[must_use]
+pub fn migrations(dialect: Dialect) -> Vec<Migration> {
+    vec![
+        Migration {
+            version: 1,
+            description: "initial schema",
+            statements: vec![
+                AcBmc::get_create_sql(dialect, false),
+                TargetBmc::get_create_sql(dialect, false),
+                QmBmc::get_create_sql(dialect, false),
+                QmatrixBmc::get_create_sql(dialect, false),
+                AnnBmc::get_create_sql(dialect, false),
+                AtlasBmc::get_create_sql(dialect, false),
+                ZscoreBmc::get_create_sql(dialect, false),
+                UmapBmc::get_create_sql(dialect, false),
+                ClusterBmc::get_create_sql(dialect, false),
+                GlsnBmc::get_create_sql(dialect, false),
+                GseaEnrBmc::get_create_sql(dialect, false),
+                WkdeLabelBmc::get_create_sql(dialect, false),
+                WkdeTagBmc::get_create_sql(dialect, false),
+                VarianceBmc::get_create_sql(dialect, false),
+                LabelDataBmc::get_create_sql(dialect, false),
+                LabelChangeBmc::get_create_sql(dialect, false),
+                LabelConfidenceBmc::get_create_sql(dialect, false),
+                ReferenceBmc::get_create_sql(dialect, false),
+                BenchmarkBmc::get_create_sql(dialect, false),
+                ConfusionBmc::get_create_sql(dialect, false),
+                ScoreSettingBmc::get_create_sql(dialect, false),
+                GlsBmc::get_create_sql(dialect),
+                AclabelsBmc::get_create_sql(dialect),
+            ],
+        },
+        Migration {
+            version: 2,
+            description: "pipeline step state",
+            statements: vec![StepStateBmc::get_create_sql(dialect, false)],
+        },
+        Migration {
+            version: 3,
+            description: "normalisation factors",
+            statements: vec![NormFactorBmc::get_create_sql(dialect, false)],
+        },
+        Migration {
+            version: 4,
+            description: "qmatrix scaling",
+            statements: vec![QmatrixBmc::get_add_scaling_sql(dialect)],
+        },
+        Migration {
+            version: 5,
+            description: "zscore kind and view",
+            statements: vec![
+                ZscoreBmc::get_add_kind_sql(dialect),
+                AczscoreBmc::get_create_sql(dialect),
+            ],
+        },
+        Migration {
+            version: 6,
+            description: "glsn multilabel view",
+            statements: vec![GlsnaBmc::get_create_sql(dialect)],
+        },
+        Migration {
+            version: 7,
+            description: "unresolved identifiers",
+            statements: vec![UnresolvedIdBmc::get_create_sql(dialect, false)],
+        },
+        Migration {
+            version: 8,
+            description: "target annotations",
+            statements: vec![
+                TargetConstraintBmc::get_create_sql(dialect, false),
+                TargetGoBmc::get_create_sql(dialect, false),
+                TargetLocationBmc::get_create_sql(dialect, false),
+                TargetPathwayBmc::get_create_sql(dialect, false),
+                TargetProteinIdBmc::get_create_sql(dialect, false),
+                TargetTractabilityBmc::get_create_sql(dialect, false),
+                TargetTranscriptBmc::get_create_sql(dialect, false),
+            ],
+        },
+        Migration {
+            version: 9,
+            description: "qm shares",
+            statements: vec![QmBmc::get_add_share_sql(dialect)],
+        },
+    ]
+}
+
+/// Connects to the analysis database. It is created when `recreate` is set,
+/// or, in rebuild builds, when it cannot be reached.
+pub async fn connect(dbc: &DbCredentials, recreate: bool) -> iwf::Result<ModelManager> {
+    let url = dbc.get_db_url();
+    if !recreate {
+        match ModelManager::new(&url).await {
+            Ok(mm) => return Ok(mm),
+            Err(e) if !cfg!(feature = "rebuild") => return Err(e.into()),
+            Err(e) => warn!("Cannot connect to the database, creating it: {e}"),
+        }
+    }
+    iwf::sql::create_db(dbc).await?;
+    iwf::setup_dgs(dbc).await?;
+    Ok(ModelManager::new(&url).await?)
+}
+
+/// Refuses a database whose schema does not match this build, unless
+/// `upgrade` allows migrating an older one in place.
+pub async fn check(mm: &ModelManager, upgrade: bool) -> iwf::Result<()> {
+    let version = SchemaVersionBmc::migrate(mm, &migrations(Dialect::Postgres), upgrade).await?;
+    debug!("Schema version: {version}");
+    Ok(())
+}
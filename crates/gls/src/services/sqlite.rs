@@ -0,0 +1,144 @@
+use camino::Utf8Path;
+use iwf::sql::ModelManager;
+use log::debug;
+use sqlx::postgres::PgRow;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteConnection};
+use sqlx::{ConnectOptions, Connection, Executor, Row};
+use std::str::FromStr;
+
+use crate::services::schema::{migrations, TABLES};
+use ddbtbl::dialect::Dialect;
+use ddbtbl::gls::atlas::AtlasBmc;
+use ddbtbl::schema::SchemaVersionBmc;
+use ddbvws::gls::labelspivot::LabelspivotBmc;
+
+const PAGE_SIZE: i64 = 50_000;
+
+/// Copies the analysis database into a single SQLite file with the same
+/// tables and views, for reading the results without a Postgres server.
+/// Integer arrays are written as JSON text and column types without a SQLite
+/// counterpart as their text form. The report can be built from the file
+/// (`report_sqlite`), the rebuild steps run on Postgres only.
+pub async fn export(mm: &ModelManager, path: &Utf8Path) -> iwf::Result<()> {
+    if path.exists() {
+        std::fs::remove_file(path)
+            .map_err(|e| iwf::Error::Defined(format!("Cannot replace {path}: {e}")))?;
+    }
+    let mut conn = SqliteConnectOptions::from_str(path.as_str())?
+        .create_if_missing(true)
+        .connect()
+        .await?;
+    conn.execute(SchemaVersionBmc::get_create_sql().as_str())
+        .await?;
+    for migration in migrations(Dialect::Sqlite) {
+        for statement in &migration.statements {
+            conn.execute(statement.as_str()).await?;
+        }
+        sqlx::query("insert into schema_version (version, description) values (?, ?)")
+            .bind(migration.version)
+            .bind(migration.description)
+            .execute(&mut conn)
+            .await?;
+    }
+    let atlases: Vec<String> = AtlasBmc::load(mm)
+        .await?
+        .into_iter()
+        .map(|e| e.name)
+        .collect();
+    conn.execute(LabelspivotBmc::get_create_sql(&atlases).as_str())
+        .await?;
+
+    for table in TABLES {
+        let n = copy_table(mm, &mut conn, table).await?;
+        debug!("Exported {table}: {n} rows");
+    }
+    conn.close().await?;
+    Ok(())
+}
+
+/// Column names and Postgres type names (`udt_name`) of `table`, in order.
+async fn columns(mm: &ModelManager, table: &str) -> iwf::Result<Vec<(String, String)>> {
+    let stmt = "select column_name::text, udt_name::text from information_schema.columns where table_schema = current_schema() and table_name = $1 order by ordinal_position";
+    Ok(sqlx::query_as(stmt).bind(table).fetch_all(mm.db()).await?)
+}
+
+/// Whether `udt_name` is bound with its own type; other columns are read as
+/// text.
+fn is_native(udt_name: &str) -> bool {
+    matches!(
+        udt_name,
+        "int2" | "int4" | "int8" | "float4" | "float8" | "bool" | "varchar" | "text" | "_int4"
+    )
+}
+
+/// Copies the rows of `table`, in pages of `PAGE_SIZE` when it has an
+/// integer `id` and at once otherwise.
+async fn copy_table(
+    mm: &ModelManager,
+    conn: &mut SqliteConnection,
+    table: &str,
+) -> iwf::Result<usize> {
+    let columns = columns(mm, table).await?;
+    let select: Vec<String> = columns
+        .iter()
+        .map(|(name, udt_name)| match is_native(udt_name) {
+            true => format!("\"{name}\""),
+            false => format!("\"{name}\"::text as \"{name}\""),
+        })
+        .collect();
+    let names: Vec<String> = columns.iter().map(|e| format!("\"{}\"", e.0)).collect();
+    let insert = format!(
+        "insert into {table} ({}) values ({})",
+        names.join(","),
+        vec!["?"; names.len()].join(",")
+    );
+    let paged = columns
+        .iter()
+        .any(|(name, udt_name)| name == "id" && udt_name == "int4");
+    let stmt = match paged {
+        true => format!(
+            "select {} from {table} where id > $1 order by id limit {PAGE_SIZE}",
+            select.join(",")
+        ),
+        false => format!("select {} from {table}", select.join(",")),
+    };
+    let mut last_id = i32::MIN;
+    let mut n = 0;
+    loop {
+        let rows: Vec<PgRow> = match paged {
+            true => sqlx::query(&stmt).bind(last_id).fetch_all(mm.db()).await?,
+            false => sqlx::query(&stmt).fetch_all(mm.db()).await?,
+        };
+        let Some(last) = rows.last() else {
+            break;
+        };
+        let mut tx = conn.begin().await?;
+        for row in &rows {
+            let mut query = sqlx::query(&insert);
+            for (i, (_, udt_name)) in columns.iter().enumerate() {
+                query = match udt_name.as_str() {
+                    "int2" => query.bind(row.try_get::<Option<i16>, _>(i)?),
+                    "int4" => query.bind(row.try_get::<Option<i32>, _>(i)?),
+                    "int8" => query.bind(row.try_get::<Option<i64>, _>(i)?),
+                    "float4" => query.bind(row.try_get::<Option<f32>, _>(i)?),
+                    "float8" => query.bind(row.try_get::<Option<f64>, _>(i)?),
+                    "bool" => query.bind(row.try_get::<Option<bool>, _>(i)?),
+                    "_int4" => query.bind(
+                        row.try_get::<Option<Vec<i32>>, _>(i)?
+                            .map(|e| serde_json::to_string(&e))
+                            .transpose()?,
+                    ),
+                    _ => query.bind(row.try_get::<Option<String>, _>(i)?),
+                };
+            }
+            query.execute(&mut *tx).await?;
+        }
+        tx.commit().await?;
+        n += rows.len();
+        if !paged {
+            break;
+        }
+        last_id = last.try_get("id")?;
+    }
+    Ok(n)
+}